@@ -0,0 +1,221 @@
+use crate::map::{Map, Tile};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+const DIRS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+fn is_resource(tile: Tile) -> bool {
+    matches!(tile, Tile::Energy | Tile::Mineral | Tile::Science)
+}
+
+fn neighbours(map: &Map, (r, c): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+    DIRS.iter().filter_map(move |&(dr, dc)| {
+        let nr = r.checked_add_signed(dr)?;
+        let nc = c.checked_add_signed(dc)?;
+        (nr < map.grid.len() && nc < map.cols).then_some((nr, nc))
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapStats {
+    pub tile_counts: HashMap<Tile, usize>,
+    pub passable_cells: usize,
+    pub reachable_cells: usize,
+    pub reachable_fraction: f64,
+    pub unreachable_resources: usize,
+    /// BFS distance from the station -> number of reachable resources at that distance.
+    pub resource_distances: BTreeMap<usize, usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapAnalysis {
+    pub station: (usize, usize),
+    /// Connected component id of every passable cell.
+    pub components: HashMap<(usize, usize), usize>,
+    pub component_count: usize,
+    /// Walking distance from the station for every reachable cell.
+    pub distances: HashMap<(usize, usize), usize>,
+    pub unreachable_resources: Vec<(usize, usize)>,
+}
+
+impl MapAnalysis {
+    pub fn analyze(map: &Map, station: (usize, usize)) -> Self {
+        let mut components = HashMap::new();
+        let mut component_count = 0;
+
+        for r in 0..map.grid.len() {
+            for c in 0..map.cols {
+                if map.grid[r][c] == Tile::Obstacle || components.contains_key(&(r, c)) {
+                    continue;
+                }
+                let mut q = VecDeque::from([(r, c)]);
+                components.insert((r, c), component_count);
+                while let Some(cur) = q.pop_front() {
+                    for next in neighbours(map, cur) {
+                        if map.grid[next.0][next.1] != Tile::Obstacle
+                            && !components.contains_key(&next)
+                        {
+                            components.insert(next, component_count);
+                            q.push_back(next);
+                        }
+                    }
+                }
+                component_count += 1;
+            }
+        }
+
+        let mut distances = HashMap::new();
+        if components.contains_key(&station) {
+            let mut q = VecDeque::from([station]);
+            distances.insert(station, 0);
+            while let Some(cur) = q.pop_front() {
+                let d = distances[&cur];
+                for next in neighbours(map, cur) {
+                    if map.grid[next.0][next.1] != Tile::Obstacle && !distances.contains_key(&next)
+                    {
+                        distances.insert(next, d + 1);
+                        q.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let mut unreachable_resources = Vec::new();
+        for (r, row) in map.grid.iter().enumerate() {
+            for (c, &tile) in row.iter().enumerate() {
+                if is_resource(tile) && !distances.contains_key(&(r, c)) {
+                    unreachable_resources.push((r, c));
+                }
+            }
+        }
+
+        Self {
+            station,
+            components,
+            component_count,
+            distances,
+            unreachable_resources,
+        }
+    }
+
+    pub fn is_reachable(&self, pos: (usize, usize)) -> bool {
+        self.distances.contains_key(&pos)
+    }
+
+    pub fn stats(&self, map: &Map) -> MapStats {
+        let mut tile_counts = HashMap::new();
+        let mut resource_distances = BTreeMap::new();
+
+        for (r, row) in map.grid.iter().enumerate() {
+            for (c, &tile) in row.iter().enumerate() {
+                *tile_counts.entry(tile).or_insert(0) += 1;
+                if is_resource(tile) {
+                    if let Some(&d) = self.distances.get(&(r, c)) {
+                        *resource_distances.entry(d).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let passable_cells = self.components.len();
+        let reachable_cells = self.distances.len();
+
+        MapStats {
+            tile_counts,
+            passable_cells,
+            reachable_cells,
+            reachable_fraction: if passable_cells == 0 {
+                0.0
+            } else {
+                reachable_cells as f64 / passable_cells as f64
+            },
+            unreachable_resources: self.unreachable_resources.len(),
+            resource_distances,
+        }
+    }
+}
+
+/// Carves corridors through obstacles until every resource is reachable from
+/// `station`. Returns the cells that were turned into `Tile::Empty`.
+pub fn repair(map: &mut Map, station: (usize, usize), seed: u64) -> Vec<(usize, usize)> {
+    let mut carved = Vec::new();
+    if station.0 >= map.grid.len() || station.1 >= map.cols {
+        return carved;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    if map.grid[station.0][station.1] == Tile::Obstacle {
+        map.grid[station.0][station.1] = Tile::Empty;
+        carved.push(station);
+    }
+
+    loop {
+        let analysis = MapAnalysis::analyze(map, station);
+        let Some(&start) = analysis.unreachable_resources.first() else {
+            break;
+        };
+        let component = analysis.components[&start];
+
+        let mut dirs = DIRS;
+        dirs.shuffle(&mut rng);
+
+        // 0-1 BFS: walking through a free cell is free, through an obstacle costs one carve
+        let mut sources: Vec<_> = analysis
+            .components
+            .iter()
+            .filter(|&(_, &id)| id == component)
+            .map(|(&pos, _)| pos)
+            .collect();
+        sources.sort();
+
+        let mut cost: HashMap<(usize, usize), usize> =
+            sources.iter().map(|&pos| (pos, 0)).collect();
+        let mut parent = HashMap::new();
+        let mut q = VecDeque::from(sources);
+
+        let mut reached = None;
+        while let Some(cur) = q.pop_front() {
+            if analysis.is_reachable(cur) {
+                reached = Some(cur);
+                break;
+            }
+            for &(dr, dc) in &dirs {
+                let (Some(nr), Some(nc)) =
+                    (cur.0.checked_add_signed(dr), cur.1.checked_add_signed(dc))
+                else {
+                    continue;
+                };
+                if nr >= map.grid.len() || nc >= map.cols {
+                    continue;
+                }
+                let step = usize::from(map.grid[nr][nc] == Tile::Obstacle);
+                let next_cost = cost[&cur] + step;
+                if cost.get(&(nr, nc)).is_none_or(|&c| next_cost < c) {
+                    cost.insert((nr, nc), next_cost);
+                    parent.insert((nr, nc), cur);
+                    if step == 0 {
+                        q.push_front((nr, nc));
+                    } else {
+                        q.push_back((nr, nc));
+                    }
+                }
+            }
+        }
+
+        let Some(mut cur) = reached else {
+            break;
+        };
+        while let Some(&p) = parent.get(&cur) {
+            if map.grid[cur.0][cur.1] == Tile::Obstacle {
+                map.grid[cur.0][cur.1] = Tile::Empty;
+                carved.push(cur);
+            }
+            cur = p;
+        }
+    }
+
+    carved
+}
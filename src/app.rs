@@ -1,95 +1,510 @@
 use crate::coordinator::RobotCoordinator;
-use crate::map::{Map, Tile};
-use crate::robot::{Robot, RobotModule};
-use crate::station;
-use crate::station::StationCmd;
-use std::collections::VecDeque;
+use rust_project::analysis::{self, MapAnalysis};
+use rust_project::ascii::AsciiMap;
+use rust_project::biome::{Biome, BiomeGenerator, BiomeMap};
+use rust_project::console::{self, Command, LogCommand};
+use rust_project::dynamics::{DynamicsConfig, WorldDynamics};
+use rust_project::economy::EconomyStatus;
+use rust_project::heatmap::{HeatLayer, Heatmaps};
+use rust_project::knowledge::{Belief, MapLayer};
+use rust_project::log::{Category, Level, LogBook, LogFilter, LogRecord, LogScroll, Source};
+use rust_project::map::{self, Map};
+use rust_project::robot::{Robot, RobotModule};
+use rust_project::station;
+use rust_project::station::{MergeMetrics, StationCmd, StationRequest, StationSite};
+use rust_project::timeline::{Sample, Timeline};
+use rust_project::traffic::TrafficStats;
+use rust_project::viewport::Viewport;
+use rust_project::watchdog::{Watchdog, WatchdogConfig, WatchdogStats};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Pause between ticks until changed from the console.
+const DEFAULT_TICK_DELAY: Duration = Duration::from_millis(150);
 
 pub struct App {
     pub map: Map,
+    pub biomes: BiomeMap,
     pub robots: Vec<Robot>,
     pub tick_count: u64,
     pub collected_energy: u32,
     pub collected_mineral: u32,
-    tx_report: mpsc::Sender<station::RobotReport>,
     rx_cmd: mpsc::Receiver<StationCmd>,
-    pub logs: VecDeque<String>,
+    pub logs: LogBook,
+    pub log_filter: LogFilter,
+    pub log_scroll: LogScroll,
+    /// Lines the log panel shows at once.
+    pub log_height: usize,
     pub robots_scroll: u16,
-    pub logs_scroll: u16,
+    /// Index into `robots` of the robot whose field of view is overlaid.
+    pub selected_robot: usize,
+    pub show_fov: bool,
+    /// Map cell under the inspection cursor, `None` when it is hidden.
+    pub cursor: Option<(usize, usize)>,
+    /// Shows the selected robot's details instead of the robot list.
+    pub show_detail: bool,
+    /// Mirror of the main station's master map.
+    pub master_map: Belief,
+    pub layer: MapLayer,
+    pub heatmaps: Heatmaps,
+    /// Heatmap drawn over the map, if any.
+    pub heat_layer: Option<HeatLayer>,
+    pub viewport: Viewport,
+    pub timeline: Timeline,
+    /// Recorded samples still to be shown; while replaying the simulation
+    /// does not run.
+    replay: Option<VecDeque<Sample>>,
+    /// Shows the charts instead of the map.
+    pub show_charts: bool,
+    /// Station, master map version and tick of each cell's last change.
+    pub master_updates: HashMap<(usize, usize), (usize, u64, u64)>,
+    pub stations: Vec<StationSite>,
     pub master_version: u64,
+    pub merge_metrics: MergeMetrics,
+    pub economy: EconomyStatus,
+    pub traffic: TrafficStats,
+    pub watchdog: WatchdogStats,
+    /// Text typed at the `:` prompt, `None` while it is closed.
+    pub console: Option<String>,
+    pub tick_delay: Duration,
+    pub paused: bool,
+    last_tick: Instant,
     coordinator: RobotCoordinator,
     tx_coord_cmd: mpsc::Sender<StationCmd>,
+    tx_request: mpsc::Sender<StationRequest>,
 }
 
 impl App {
     pub fn new(
         tx_report: mpsc::Sender<station::RobotReport>,
         rx_cmd: mpsc::Receiver<StationCmd>,
+        tx_request: mpsc::Sender<StationRequest>,
+        layout: Option<AsciiMap>,
+        seed: Option<u64>,
     ) -> Self {
-        let map = Map::generate_with_dynamic_seed(25, 26);
-        let robots = vec![
-            Robot::new(
-                1,
-                (0, 0),
-                vec![
-                    RobotModule::Explorer,
-                    RobotModule::Collector,
-                    RobotModule::Scanner,
-                    RobotModule::Sensor,
-                ],
-            ),
-            Robot::new(
-                2,
-                (map.grid.len() - 1, map.cols - 1),
-                vec![
-                    RobotModule::Explorer,
-                    RobotModule::Collector,
-                    RobotModule::Scanner,
-                    RobotModule::Sensor,
-                ],
-            ),
-        ];
+        let mut logs = LogBook::new();
+        let startup =
+            |message| LogRecord::new(0, Level::Info, Source::App, Category::General, message);
+        let seed = seed
+            .or_else(|| layout.as_ref().map(AsciiMap::seed))
+            .unwrap_or_else(map::dynamic_seed);
+        let (map, biomes, starts) = match layout {
+            Some(layout) => {
+                let rows = layout.map.grid.len();
+                let biomes = BiomeMap {
+                    grid: vec![vec![Biome::Plains; layout.map.cols]; rows],
+                    cols: layout.map.cols,
+                };
+                logs.push(startup(format!(
+                    "Loaded {}x{} map with {} robot starts, seed {}",
+                    rows,
+                    layout.map.cols,
+                    layout.robots.len(),
+                    seed
+                )));
+                let mut starts = layout.robots;
+                if starts.is_empty() {
+                    let home = layout.station.unwrap_or((0, 0));
+                    logs.push(LogRecord::new(
+                        0,
+                        Level::Warn,
+                        Source::App,
+                        Category::General,
+                        format!("Map has no robot starts, spawning one at {:?}", home),
+                    ));
+                    starts.push(home);
+                }
+                (layout.map, biomes, starts)
+            }
+            None => {
+                let (mut map, biomes) =
+                    BiomeGenerator::default().generate_with_biomes(25, 26, seed);
+                let carved = analysis::repair(&mut map, (0, 0), seed);
+                let stats = MapAnalysis::analyze(&map, (0, 0)).stats(&map);
+                logs.push(startup(format!(
+                    "Map seed {} | {:.0}% reachable | {} cells carved | {} resources unreachable",
+                    seed,
+                    stats.reachable_fraction * 100.0,
+                    carved.len(),
+                    stats.unreachable_resources
+                )));
+                let starts = vec![(0, 0), (map.grid.len() - 1, map.cols - 1)];
+                (map, biomes, starts)
+            }
+        };
+
+        let robots = starts
+            .iter()
+            .enumerate()
+            .map(|(idx, &pos)| {
+                Robot::new(
+                    idx + 1,
+                    pos,
+                    vec![
+                        RobotModule::Explorer,
+                        RobotModule::Collector,
+                        RobotModule::Scanner,
+                        RobotModule::Sensor,
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
 
         let (tx_coord_cmd, rx_coord_cmd) = mpsc::channel();
 
         let coordinator =
-            RobotCoordinator::new(map.clone(), tx_report.clone(), rx_coord_cmd, robots.clone());
+            RobotCoordinator::new(map.clone(), tx_report, rx_coord_cmd, robots.clone())
+                .with_dynamics(WorldDynamics::new(DynamicsConfig::default(), seed))
+                .with_watchdog(Watchdog::new(WatchdogConfig::default(), seed));
 
         Self {
             map,
+            biomes,
             robots,
             tick_count: 0,
             collected_energy: 0,
             collected_mineral: 0,
-            tx_report,
             rx_cmd,
-            logs: VecDeque::new(),
+            logs,
             robots_scroll: 0,
-            logs_scroll: 0,
+            log_filter: LogFilter::default(),
+            log_scroll: LogScroll::default(),
+            log_height: 0,
+            selected_robot: 0,
+            show_fov: false,
+            cursor: None,
+            show_detail: false,
+            master_map: Belief::new(),
+            layer: MapLayer::default(),
+            heatmaps: Heatmaps::new(),
+            heat_layer: None,
+            viewport: Viewport::new(),
+            timeline: Timeline::new(),
+            replay: None,
+            show_charts: false,
+            master_updates: HashMap::new(),
+            stations: Vec::new(),
             master_version: 0,
+            merge_metrics: MergeMetrics::default(),
+            economy: EconomyStatus::default(),
+            traffic: TrafficStats::default(),
+            watchdog: WatchdogStats::default(),
+            console: None,
+            tick_delay: DEFAULT_TICK_DELAY,
+            paused: false,
+            last_tick: Instant::now(),
             coordinator,
             tx_coord_cmd,
+            tx_request,
+        }
+    }
+
+    /// Plays `samples` back into the charts, one per tick, instead of
+    /// running the simulation.
+    pub fn replay(&mut self, samples: Vec<Sample>) {
+        self.logs.push(LogRecord::new(
+            self.tick_count,
+            Level::Info,
+            Source::App,
+            Category::General,
+            format!("Replaying {} recorded samples", samples.len()),
+        ));
+        self.replay = Some(samples.into());
+        self.show_charts = true;
+    }
+
+    /// Whether the next tick should run now.
+    pub fn tick_due(&self) -> bool {
+        !self.paused && self.last_tick.elapsed() >= self.tick_delay
+    }
+
+    /// Runs the command typed at the prompt and closes it.
+    pub fn submit_console(&mut self) {
+        let Some(line) = self.console.take() else {
+            return;
+        };
+        if line.trim().is_empty() {
+            return;
+        }
+        self.console_log(Level::Info, format!("> {}", line.trim()));
+        if let Err(err) = Command::parse(&line).and_then(|command| self.run_command(command)) {
+            self.console_log(Level::Warn, format!("Error: {}", err));
+        }
+    }
+
+    /// Checks `command` against what the app knows and passes it on:
+    /// station requests to the main station, robot and map changes to the
+    /// coordinator.
+    pub fn run_command(&mut self, command: Command) -> Result<(), String> {
+        let (rows, cols) = self.map_size();
+        let on_map = |(row, col): (usize, usize)| {
+            if row < rows && col < cols {
+                Ok(())
+            } else {
+                Err(format!(
+                    "({}, {}) is off the {}x{} map",
+                    row, col, rows, cols
+                ))
+            }
+        };
+        match command {
+            Command::Station(request) => {
+                if let StationRequest::Spawn { position, .. } = &request {
+                    on_map(*position)?;
+                    if self.map.grid[position.0][position.1] == map::Tile::Obstacle {
+                        return Err("cannot spawn on an obstacle".to_string());
+                    }
+                }
+                if let StationRequest::Remove { robot_id, .. } = &request {
+                    if !self.robots.iter().any(|robot| robot.id == *robot_id) {
+                        return Err(format!("no robot {}", robot_id));
+                    }
+                }
+                self.tx_request
+                    .send(request)
+                    .map_err(|_| "station is offline".to_string())?;
+            }
+            Command::Recall(id) => {
+                if !self.robots.iter().any(|robot| robot.id == id) {
+                    return Err(format!("no robot {}", id));
+                }
+                let _ = self.tx_coord_cmd.send(StationCmd::Recall(id));
+                self.console_log(Level::Info, format!("Recalled robot {}", id));
+            }
+            Command::Assign { robot, station } => {
+                if !self.robots.iter().any(|r| r.id == robot) {
+                    return Err(format!("no robot {}", robot));
+                }
+                if let Some(id) = station.filter(|&id| !self.stations.iter().any(|s| s.id == id)) {
+                    return Err(format!("no station {}", id));
+                }
+                let _ = self
+                    .tx_coord_cmd
+                    .send(StationCmd::Assign { id: robot, station });
+                let home = match station {
+                    Some(id) => format!("station {}", id),
+                    None => "the nearest station".to_string(),
+                };
+                self.console_log(Level::Info, format!("Robot {} returns to {}", robot, home));
+            }
+            Command::SetTile { cell, tile } => {
+                on_map(cell)?;
+                if tile == map::Tile::Obstacle && self.robots.iter().any(|r| r.position == cell) {
+                    return Err("a robot stands there".to_string());
+                }
+                let _ = self.tx_coord_cmd.send(StationCmd::SetTile { cell, tile });
+                self.console_log(
+                    Level::Info,
+                    format!("Set ({}, {}) to {:?}", cell.0, cell.1, tile),
+                );
+            }
+            Command::Speed(ms) => {
+                self.tick_delay = Duration::from_millis(ms);
+                self.console_log(Level::Info, format!("{} ms between ticks", ms));
+            }
+            Command::Pause => {
+                self.paused = !self.paused;
+                let state = if self.paused { "Paused" } else { "Resumed" };
+                self.console_log(Level::Info, state.to_string());
+            }
+            Command::Log(command) => self.apply_log_command(command),
+            Command::Help => {
+                for line in console::HELP {
+                    self.console_log(Level::Info, line.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn select_next_robot(&mut self) {
+        if !self.robots.is_empty() {
+            self.selected_robot = (self.selected_robot + 1) % self.robots.len();
+        }
+        self.follow_selected();
+    }
+
+    pub fn selected(&self) -> Option<&Robot> {
+        self.robots.get(self.selected_robot)
+    }
+
+    fn map_size(&self) -> (usize, usize) {
+        (self.map.grid.len(), self.map.cols)
+    }
+
+    /// Fits the viewport to `view` screen cells, rows by columns.
+    pub fn resize_view(&mut self, view: (usize, usize)) {
+        let (rows, cols) = self.map_size();
+        self.viewport.resize(view, rows, cols);
+        self.follow_selected();
+    }
+
+    pub fn pan(&mut self, dr: isize, dc: isize) {
+        let (rows, cols) = self.map_size();
+        self.viewport.pan(dr, dc, rows, cols);
+    }
+
+    pub fn zoom(&mut self, out: bool) {
+        let (rows, cols) = self.map_size();
+        self.viewport.zoom(out, rows, cols);
+        self.follow_selected();
+    }
+
+    pub fn toggle_follow(&mut self) {
+        self.viewport.follow = !self.viewport.follow;
+        self.follow_selected();
+    }
+
+    fn follow_selected(&mut self) {
+        let (rows, cols) = self.map_size();
+        let position = self.selected().map(|robot| robot.position);
+        if let Some(position) = position.filter(|_| self.viewport.follow) {
+            self.viewport.center_on(position, rows, cols);
+        }
+    }
+
+    pub fn toggle_cursor(&mut self) {
+        self.cursor = match self.cursor {
+            Some(_) => None,
+            None => Some(self.selected().map_or((0, 0), |robot| robot.position)),
+        };
+        if let Some(cursor) = self.cursor {
+            let (rows, cols) = self.map_size();
+            self.viewport.reveal(cursor, rows, cols);
+        }
+    }
+
+    pub fn move_cursor(&mut self, dr: isize, dc: isize) {
+        if let Some((row, col)) = self.cursor {
+            let rows = self.map.grid.len() as isize;
+            let cols = self.map.cols as isize;
+            let cursor = (
+                (row as isize + dr).clamp(0, rows - 1) as usize,
+                (col as isize + dc).clamp(0, cols - 1) as usize,
+            );
+            self.cursor = Some(cursor);
+            self.viewport.reveal(cursor, rows as usize, cols as usize);
         }
     }
 
-    pub fn update_map_tile(&mut self, row: usize, col: usize, tile: Tile) {
-        if row < self.map.grid.len() && col < self.map.cols {
-            self.map.grid[row][col] = tile;
+    /// Selects the robot under the cursor and opens its details.
+    pub fn select_at_cursor(&mut self) {
+        let Some(cursor) = self.cursor else { return };
+        if let Some(idx) = self.robots.iter().position(|r| r.position == cursor) {
+            self.selected_robot = idx;
+            self.show_detail = true;
         }
     }
 
+    /// Ids of the robots whose known map has `cell`.
+    pub fn robots_knowing(&self, cell: (usize, usize)) -> Vec<usize> {
+        self.robots
+            .iter()
+            .filter(|robot| robot.known_map.contains_key(&cell))
+            .map(|robot| robot.id)
+            .collect()
+    }
+
+    fn record_sample(&mut self) {
+        let cells = (self.map.grid.len() * self.map.cols).max(1);
+        let sample = Sample {
+            tick: self.tick_count,
+            energy_stock: self.economy.energy_trend.last().copied().unwrap_or(0),
+            mineral_stock: self.economy.mineral_trend.last().copied().unwrap_or(0),
+            energy_collected: self.collected_energy as u64,
+            mineral_collected: self.collected_mineral as u64,
+            explored: self.master_map.len() as f64 * 100.0 / cells as f64,
+            robots: self.robots.len() as u64,
+            merges: 0,
+        };
+        self.timeline.record(sample, self.merge_metrics.merged);
+    }
+
+    /// Cells seen by the selected robot when the overlay is on.
+    pub fn fov_overlay(&self) -> HashSet<(usize, usize)> {
+        match self.robots.get(self.selected_robot) {
+            Some(robot) if self.show_fov => robot.field_of_view(&self.map).into_iter().collect(),
+            _ => HashSet::new(),
+        }
+    }
+
+    fn console_log(&mut self, level: Level, message: String) {
+        self.logs.push(LogRecord::new(
+            self.tick_count,
+            level,
+            Source::App,
+            Category::Console,
+            message,
+        ));
+    }
+
+    fn apply_log_command(&mut self, command: LogCommand) {
+        let filter = &mut self.log_filter;
+        match command {
+            LogCommand::Level(level) => filter.min_level = level,
+            LogCommand::Robot(robot) => filter.robot = robot,
+            LogCommand::Category(category) => filter.category = category,
+            LogCommand::Search(text) => filter.search = text,
+            LogCommand::Follow => self.log_scroll.follow = true,
+            LogCommand::Reset => *filter = LogFilter::default(),
+        }
+    }
+
+    /// Records the log panel shows, oldest first.
+    pub fn visible_logs(&self) -> Vec<&LogRecord> {
+        self.logs.filtered(&self.log_filter).collect()
+    }
+
+    pub fn scroll_logs(&mut self, delta: isize) {
+        let total = self.visible_logs().len();
+        self.log_scroll.scroll(delta, total, self.log_height);
+    }
+
+    pub fn cycle_log_level(&mut self) {
+        self.log_filter.min_level = self.log_filter.min_level.next();
+    }
+
+    pub fn cycle_log_category(&mut self) {
+        self.log_filter.category = Category::cycle(self.log_filter.category);
+    }
+
+    /// Steps the robot filter through every robot id, then back to all.
+    pub fn cycle_log_robot(&mut self) {
+        let mut ids: Vec<usize> = self.robots.iter().map(|robot| robot.id).collect();
+        ids.sort();
+        self.log_filter.robot = match self.log_filter.robot {
+            None => ids.first().copied(),
+            Some(current) => ids.into_iter().find(|&id| id > current),
+        };
+    }
+
     pub fn tick(&mut self) -> bool {
+        if let Some(replay) = self.replay.as_mut() {
+            match replay.pop_front() {
+                Some(sample) => {
+                    self.tick_count = sample.tick;
+                    self.timeline.push(sample);
+                }
+                None => {
+                    self.paused = true;
+                    self.logs.push(LogRecord::new(
+                        self.tick_count,
+                        Level::Info,
+                        Source::App,
+                        Category::General,
+                        "Replay finished",
+                    ));
+                }
+            }
+            self.last_tick = Instant::now();
+            return false;
+        }
         self.tick_count += 1;
 
         while let Ok(cmd) = self.rx_cmd.try_recv() {
             match cmd {
-                StationCmd::Log(line) => {
-                    if self.logs.len() >= 50 {
-                        self.logs.pop_front();
-                    }
-                    self.logs.push_back(line);
-                }
+                StationCmd::Log(record) => self.logs.push(record),
                 StationCmd::Spawn {
                     id,
                     modules,
@@ -103,11 +518,14 @@ impl App {
                         start_pos,
                     });
                 }
-                StationCmd::Snapshot { id, version, diff } => {
-                    self.master_version = version;
-
-                    for &((row, col), _, new_tile) in &diff.0 {
-                        self.update_map_tile(row, col, new_tile);
+                StationCmd::Snapshot {
+                    id,
+                    station,
+                    version,
+                    diff,
+                } => {
+                    if station == 0 {
+                        self.master_version = version;
                     }
 
                     if let Some(robot) = self.robots.iter_mut().find(|r| r.id == id as usize) {
@@ -116,17 +534,51 @@ impl App {
 
                     let _ = self.tx_coord_cmd.send(StationCmd::Snapshot {
                         id,
+                        station,
                         version,
                         diff: diff.clone(),
                     });
                 }
+                StationCmd::Retrofit { id, op } => {
+                    if let Some(robot) = self.robots.iter_mut().find(|r| r.id == id) {
+                        let _ = robot.retrofit(&op);
+                    }
+                    let _ = self.tx_coord_cmd.send(StationCmd::Retrofit { id, op });
+                }
+                StationCmd::StationOnline(site) => {
+                    match self.stations.iter_mut().find(|known| known.id == site.id) {
+                        Some(known) => *known = site,
+                        None => self.stations.push(site),
+                    }
+                    let _ = self.tx_coord_cmd.send(StationCmd::StationOnline(site));
+                }
                 StationCmd::Version(v) => {
                     self.master_version = v;
                 }
+                StationCmd::MasterDiff {
+                    station,
+                    version,
+                    diff,
+                } => {
+                    for &(cell, _, tile) in &diff.0 {
+                        self.master_updates
+                            .insert(cell, (station, version, self.tick_count));
+                        if station == 0 {
+                            self.master_map.insert(cell, tile);
+                        }
+                    }
+                }
+                StationCmd::MergeStats(metrics) => self.merge_metrics = metrics,
+                StationCmd::Economy(status) => self.economy = status,
                 StationCmd::ResourceUpdate { energy, mineral } => {
                     self.collected_energy += energy;
                     self.collected_mineral += mineral;
                 }
+                cmd @ (StationCmd::Recall(_)
+                | StationCmd::Assign { .. }
+                | StationCmd::SetTile { .. }) => {
+                    let _ = self.tx_coord_cmd.send(cmd);
+                }
                 StationCmd::Shutdown => {
                     let _ = self.tx_coord_cmd.send(StationCmd::Shutdown);
                     return true;
@@ -134,13 +586,44 @@ impl App {
             }
         }
 
-        let (done, positions) = self.coordinator.tick(self.tick_count);
+        let (done, robots) = self.coordinator.tick(self.tick_count);
+        self.map = self.coordinator.map();
+        self.traffic = self.coordinator.traffic_stats();
+        self.watchdog = self.coordinator.watchdog_stats();
+        self.heatmaps = self.coordinator.heatmaps().clone();
+
+        for event in self.coordinator.take_events() {
+            self.logs.push(LogRecord::new(
+                self.tick_count,
+                Level::Info,
+                Source::Coordinator,
+                Category::World,
+                event.describe(),
+            ));
+        }
+        for incident in self.coordinator.take_incidents() {
+            let record = LogRecord::new(
+                self.tick_count,
+                Level::Warn,
+                Source::Coordinator,
+                Category::Watchdog,
+                incident.describe(),
+            );
+            self.logs.push(record.about(incident.robot));
+        }
+        for record in self.coordinator.take_logs() {
+            self.logs.push(record);
+        }
 
-        for (id, position) in positions {
-            if let Some(robot) = self.robots.iter_mut().find(|r| r.id == id) {
-                robot.position = position;
+        for robot in robots {
+            match self.robots.iter_mut().find(|r| r.id == robot.id) {
+                Some(known) => *known = robot,
+                None => self.robots.push(robot),
             }
         }
+        self.follow_selected();
+        self.record_sample();
+        self.last_tick = Instant::now();
 
         self.tick_count > 200 || done
     }
@@ -0,0 +1,249 @@
+use crate::map::{Map, Tile};
+use std::error::Error;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+/// Hand-authored map: `#` obstacle, `E` energy, `M` mineral, `S` science,
+/// `.` (or `·`) empty, `H` station and `R` robot start (both on empty ground),
+/// `@` a robot starting on the station.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AsciiMap {
+    pub map: Map,
+    pub station: Option<(usize, usize)>,
+    pub robots: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownGlyph(char),
+    RaggedRow { expected: usize, found: usize },
+    DuplicateStation,
+    EmptyMap,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the offending glyph.
+    pub line: usize,
+    /// 1-based column of the offending glyph.
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnknownGlyph(ch) => write!(f, "unknown glyph {:?}", ch),
+            ParseErrorKind::RaggedRow { expected, found } => {
+                write!(f, "row has {} cells, expected {}", found, expected)
+            }
+            ParseErrorKind::DuplicateStation => write!(f, "station placed more than once"),
+            ParseErrorKind::EmptyMap => write!(f, "map has no rows"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug)]
+pub enum MapFileError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for MapFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapFileError::Io(err) => write!(f, "map file: {}", err),
+            MapFileError::Parse(err) => write!(f, "map file: {}", err),
+        }
+    }
+}
+
+impl Error for MapFileError {}
+
+impl From<io::Error> for MapFileError {
+    fn from(err: io::Error) -> Self {
+        MapFileError::Io(err)
+    }
+}
+
+impl From<ParseError> for MapFileError {
+    fn from(err: ParseError) -> Self {
+        MapFileError::Parse(err)
+    }
+}
+
+pub fn tile_glyph(tile: Tile) -> char {
+    match tile {
+        Tile::Empty => '.',
+        Tile::Obstacle => '#',
+        Tile::Energy => 'E',
+        Tile::Mineral => 'M',
+        Tile::Science => 'S',
+    }
+}
+
+/// Inverse of [`tile_glyph`], also accepting `·` for empty ground.
+pub fn glyph_tile(ch: char) -> Option<Tile> {
+    match ch {
+        '#' => Some(Tile::Obstacle),
+        'E' => Some(Tile::Energy),
+        'M' => Some(Tile::Mineral),
+        'S' => Some(Tile::Science),
+        '.' | '·' => Some(Tile::Empty),
+        _ => None,
+    }
+}
+
+impl AsciiMap {
+    pub fn new(map: Map) -> Self {
+        Self {
+            map,
+            station: None,
+            robots: Vec::new(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut grid: Vec<Vec<Tile>> = Vec::new();
+        let mut station = None;
+        let mut robots = Vec::new();
+        let mut last_line = 0;
+
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            last_line = line_idx + 1;
+            if line.is_empty() {
+                continue;
+            }
+
+            let row_idx = grid.len();
+            let mut row = Vec::new();
+            for (col_idx, ch) in line.chars().enumerate() {
+                let error = |kind| ParseError {
+                    line: line_idx + 1,
+                    column: col_idx + 1,
+                    kind,
+                };
+                let tile = match ch {
+                    'H' => {
+                        if station.is_some() {
+                            return Err(error(ParseErrorKind::DuplicateStation));
+                        }
+                        station = Some((row_idx, col_idx));
+                        Tile::Empty
+                    }
+                    'R' => {
+                        robots.push((row_idx, col_idx));
+                        Tile::Empty
+                    }
+                    '@' => {
+                        if station.is_some() {
+                            return Err(error(ParseErrorKind::DuplicateStation));
+                        }
+                        station = Some((row_idx, col_idx));
+                        robots.push((row_idx, col_idx));
+                        Tile::Empty
+                    }
+                    other => match glyph_tile(other) {
+                        Some(tile) => tile,
+                        None => return Err(error(ParseErrorKind::UnknownGlyph(other))),
+                    },
+                };
+                row.push(tile);
+            }
+
+            if let Some(first) = grid.first() {
+                if row.len() != first.len() {
+                    return Err(ParseError {
+                        line: line_idx + 1,
+                        column: row.len().min(first.len()) + 1,
+                        kind: ParseErrorKind::RaggedRow {
+                            expected: first.len(),
+                            found: row.len(),
+                        },
+                    });
+                }
+            }
+            grid.push(row);
+        }
+
+        let Some(cols) = grid.first().map(Vec::len) else {
+            return Err(ParseError {
+                line: last_line.max(1),
+                column: 1,
+                kind: ParseErrorKind::EmptyMap,
+            });
+        };
+
+        Ok(Self {
+            map: Map { grid, cols },
+            station,
+            robots,
+        })
+    }
+
+    /// Seed for the world dynamics of this layout, stable across runs.
+    pub fn seed(&self) -> u64 {
+        // FNV-1a over the text form
+        self.to_string()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MapFileError> {
+        let text = fs::read_to_string(path)?;
+        Ok(Self::parse(&text)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), MapFileError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl Map {
+    pub fn from_ascii(text: &str) -> Result<Self, ParseError> {
+        AsciiMap::parse(text).map(|ascii| ascii.map)
+    }
+
+    pub fn to_ascii(&self) -> String {
+        AsciiMap::new(self.clone()).to_string()
+    }
+}
+
+impl FromStr for AsciiMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for AsciiMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (r, row) in self.map.grid.iter().enumerate() {
+            let line: String = row
+                .iter()
+                .enumerate()
+                .map(|(c, &tile)| {
+                    let robot = self.robots.contains(&(r, c));
+                    match (self.station == Some((r, c)), robot) {
+                        (true, true) => '@',
+                        (true, false) => 'H',
+                        (false, true) => 'R',
+                        (false, false) => tile_glyph(tile),
+                    }
+                })
+                .collect();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
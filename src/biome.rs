@@ -0,0 +1,157 @@
+use crate::generator::{noise_seed, sample_grid, MapGenerator, NoiseConfig, Thresholds};
+use crate::map::Map;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Plains,
+    Highlands,
+    GeothermalVents,
+    IceSheet,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 4] = [
+        Biome::Plains,
+        Biome::Highlands,
+        Biome::GeothermalVents,
+        Biome::IceSheet,
+    ];
+
+    pub fn classify(temperature: f64, moisture: f64) -> Self {
+        if temperature > 0.6 {
+            Biome::GeothermalVents
+        } else if temperature < 0.4 && moisture > 0.5 {
+            Biome::IceSheet
+        } else if moisture < 0.45 {
+            Biome::Highlands
+        } else {
+            Biome::Plains
+        }
+    }
+
+    /// Tile cut-offs and resource mix inside this biome.
+    pub fn thresholds(&self) -> Thresholds {
+        match self {
+            Biome::Plains => Thresholds::default(),
+            Biome::Highlands => Thresholds {
+                obstacle: 0.65,
+                mineral_chance: 0.85,
+                ..Thresholds::default()
+            },
+            Biome::GeothermalVents => Thresholds {
+                resource: 0.35,
+                mineral_chance: 0.15,
+                science_chance: 0.2,
+                ..Thresholds::default()
+            },
+            Biome::IceSheet => Thresholds {
+                obstacle: 0.75,
+                resource: 0.55,
+                science: 0.15,
+                science_chance: 0.7,
+                ..Thresholds::default()
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Plains => "Plains",
+            Biome::Highlands => "Highlands",
+            Biome::GeothermalVents => "Geothermal vents",
+            Biome::IceSheet => "Ice sheet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiomeMap {
+    pub grid: Vec<Vec<Biome>>,
+    pub cols: usize,
+}
+
+impl BiomeMap {
+    pub fn get(&self, row: usize, col: usize) -> Option<Biome> {
+        self.grid.get(row).and_then(|r| r.get(col)).copied()
+    }
+}
+
+/// Terrain noise decides obstacles vs. resources, a slower temperature and
+/// moisture field decides which biome's thresholds apply.
+#[derive(Debug, Clone)]
+pub struct BiomeGenerator {
+    pub terrain: NoiseConfig,
+    pub climate_frequency: f64,
+}
+
+impl Default for BiomeGenerator {
+    fn default() -> Self {
+        Self {
+            terrain: NoiseConfig {
+                octaves: 3,
+                ..NoiseConfig::default()
+            },
+            climate_frequency: 0.04,
+        }
+    }
+}
+
+impl BiomeGenerator {
+    pub fn biomes(&self, rows: usize, cols: usize, seed: u64) -> BiomeMap {
+        let temperature = Fbm::<Perlin>::new(noise_seed(seed.wrapping_add(1000))).set_octaves(2);
+        let moisture = Fbm::<Perlin>::new(noise_seed(seed.wrapping_add(2000))).set_octaves(2);
+        let temperature = sample_grid(rows, cols, self.climate_frequency, |p| temperature.get(p));
+        let moisture = sample_grid(rows, cols, self.climate_frequency, |p| moisture.get(p));
+
+        let grid = temperature
+            .iter()
+            .zip(&moisture)
+            .map(|(t_row, m_row)| {
+                t_row
+                    .iter()
+                    .zip(m_row)
+                    .map(|(&t, &m)| Biome::classify(t, m))
+                    .collect()
+            })
+            .collect();
+
+        BiomeMap { grid, cols }
+    }
+
+    pub fn generate_with_biomes(&self, rows: usize, cols: usize, seed: u64) -> (Map, BiomeMap) {
+        let biomes = self.biomes(rows, cols, seed);
+        let field = self.height_field(rows, cols, seed);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let grid = field
+            .iter()
+            .zip(&biomes.grid)
+            .map(|(row, biome_row)| {
+                row.iter()
+                    .zip(biome_row)
+                    .map(|(&val, biome)| biome.thresholds().classify(val, &mut rng))
+                    .collect()
+            })
+            .collect();
+
+        (Map { grid, cols }, biomes)
+    }
+}
+
+impl MapGenerator for BiomeGenerator {
+    fn config(&self) -> &NoiseConfig {
+        &self.terrain
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let fbm = Fbm::<Perlin>::new(noise_seed(seed)).set_octaves(self.terrain.octaves);
+        sample_grid(rows, cols, self.terrain.frequency, |p| fbm.get(p))
+    }
+
+    fn generate(&self, rows: usize, cols: usize, seed: u64) -> Map {
+        self.generate_with_biomes(rows, cols, seed).0
+    }
+}
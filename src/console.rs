@@ -0,0 +1,191 @@
+use crate::ascii::glyph_tile;
+use crate::economy::code_module;
+use crate::log::{Category, Level};
+use crate::map::Tile;
+use crate::robot::RobotModule;
+use crate::station::{StationQuery, StationRequest};
+
+/// What `help` prints.
+pub const HELP: [&str; 15] = [
+    "spawn <row> <col> [ECSN]  launch a robot with the given modules",
+    "recall <robot>            send a robot back to its station",
+    "assign <robot> <station|nearest>  pick where a robot returns",
+    "tile <row> <col> <# E M S .>  place a tile",
+    "clear <row> <col>         turn a cell into empty ground",
+    "retrofit <robot> remove <ECSN>  return a module to the spare parts",
+    "stock <energy> <mineral>  add to the stocks, negative to take",
+    "speed <ms>                milliseconds between ticks",
+    "pause                     pause or resume the simulation",
+    "save                      write a journal checkpoint",
+    "query <stocks|version|metrics|queue|conflicts>",
+    "query cell <row> <col> | query robot <id>",
+    "log level <debug|info|warn|error> | log robot <id|all>",
+    "log category <name|all> | log search [text] | log follow",
+    "log reset                 show every record from info up",
+];
+
+/// A line typed at the `:` prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Handled by the main station.
+    Station(StationRequest),
+    Recall(usize),
+    /// Station the robot returns to, `None` for the nearest.
+    Assign {
+        robot: usize,
+        station: Option<usize>,
+    },
+    SetTile {
+        cell: (usize, usize),
+        tile: Tile,
+    },
+    /// Milliseconds to wait between ticks.
+    Speed(u64),
+    Pause,
+    Log(LogCommand),
+    Help,
+}
+
+/// Changes to what the log panel shows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogCommand {
+    Level(Level),
+    /// Records about one robot, or all of them.
+    Robot(Option<usize>),
+    Category(Option<Category>),
+    /// Empty text clears the search.
+    Search(String),
+    Follow,
+    Reset,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("empty command")?;
+        let args: Vec<&str> = words.collect();
+
+        let command = match (name, args.as_slice()) {
+            ("spawn", [row, col, rest @ ..]) if rest.len() <= 1 => {
+                let modules = match rest.first() {
+                    Some(codes) => parse_modules(codes)?,
+                    None => vec![
+                        RobotModule::Explorer,
+                        RobotModule::Collector,
+                        RobotModule::Scanner,
+                        RobotModule::Sensor,
+                    ],
+                };
+                Command::Station(StationRequest::Spawn {
+                    modules,
+                    position: parse_cell(row, col)?,
+                })
+            }
+            ("recall", [id]) => Command::Recall(parse_number(id)?),
+            ("assign", [id, station]) => Command::Assign {
+                robot: parse_number(id)?,
+                station: match *station {
+                    "nearest" => None,
+                    station => Some(parse_number(station)?),
+                },
+            },
+            ("tile", [row, col, glyph]) => {
+                let mut chars = glyph.chars();
+                let tile = match (chars.next().and_then(glyph_tile), chars.next()) {
+                    (Some(tile), None) => tile,
+                    _ => return Err(format!("unknown tile {:?}", glyph)),
+                };
+                Command::SetTile {
+                    cell: parse_cell(row, col)?,
+                    tile,
+                }
+            }
+            ("clear", [row, col]) => Command::SetTile {
+                cell: parse_cell(row, col)?,
+                tile: Tile::Empty,
+            },
+            ("retrofit", [id, "remove", code]) => {
+                let module = match parse_modules(code)?.as_slice() {
+                    [module] => module.clone(),
+                    _ => return Err("name one module to remove".to_string()),
+                };
+                Command::Station(StationRequest::Remove {
+                    robot_id: parse_number(id)?,
+                    module,
+                })
+            }
+            ("stock", [energy, mineral]) => Command::Station(StationRequest::AdjustStocks {
+                energy: parse_number(energy)?,
+                mineral: parse_number(mineral)?,
+            }),
+            ("speed", [ms]) => Command::Speed(parse_number(ms)?),
+            ("pause", []) => Command::Pause,
+            ("save", []) => Command::Station(StationRequest::Save),
+            ("query", args) => Command::Station(StationRequest::Query(parse_query(args)?)),
+            ("log", args) => Command::Log(parse_log(args)?),
+            ("help", []) => Command::Help,
+            (
+                "spawn" | "recall" | "assign" | "tile" | "clear" | "retrofit" | "stock" | "speed"
+                | "pause" | "save" | "help",
+                _,
+            ) => return Err(format!("wrong arguments for {}, see help", name)),
+            _ => return Err(format!("unknown command {:?}, see help", name)),
+        };
+        Ok(command)
+    }
+}
+
+fn parse_query(args: &[&str]) -> Result<StationQuery, String> {
+    let query = match args {
+        ["stocks"] => StationQuery::Stocks,
+        ["version"] => StationQuery::Version,
+        ["metrics"] => StationQuery::Metrics,
+        ["queue"] => StationQuery::Queue,
+        ["conflicts"] => StationQuery::Conflicts,
+        ["cell", row, col] => StationQuery::Cell(parse_cell(row, col)?),
+        ["robot", id] => StationQuery::Robot(parse_number(id)?),
+        _ => return Err("unknown query, see help".to_string()),
+    };
+    Ok(query)
+}
+
+fn parse_log(args: &[&str]) -> Result<LogCommand, String> {
+    let command = match args {
+        ["level", name] => LogCommand::Level(
+            Level::parse(name).ok_or_else(|| format!("unknown level {:?}", name))?,
+        ),
+        ["robot", "all"] => LogCommand::Robot(None),
+        ["robot", id] => LogCommand::Robot(Some(parse_number(id)?)),
+        ["category", "all"] => LogCommand::Category(None),
+        ["category", name] => LogCommand::Category(Some(
+            Category::parse(name).ok_or_else(|| format!("unknown category {:?}", name))?,
+        )),
+        ["search", text @ ..] => LogCommand::Search(text.join(" ")),
+        ["follow"] => LogCommand::Follow,
+        ["reset"] => LogCommand::Reset,
+        _ => return Err("unknown log command, see help".to_string()),
+    };
+    Ok(command)
+}
+
+fn parse_modules(codes: &str) -> Result<Vec<RobotModule>, String> {
+    let mut modules = Vec::new();
+    for code in codes.chars() {
+        let module = code_module(code.to_ascii_uppercase())
+            .ok_or_else(|| format!("unknown module {:?}", code))?;
+        if modules.contains(&module) {
+            return Err(format!("{:?} listed twice", module));
+        }
+        modules.push(module);
+    }
+    Ok(modules)
+}
+
+fn parse_cell(row: &str, col: &str) -> Result<(usize, usize), String> {
+    Ok((parse_number(row)?, parse_number(col)?))
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("invalid number {:?}", text))
+}
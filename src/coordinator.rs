@@ -3,9 +3,16 @@ use std::sync::{mpsc, Arc, RwLock};
 use std::thread;
 use std::time::Duration;
 
-use crate::map::{Map, MapDiff, Tile};
-use crate::robot::{Robot, RobotActor, RobotCmd};
-use crate::station::{RobotReport, StationCmd};
+use rust_project::dynamics::{WorldDynamics, WorldEvent};
+use rust_project::heatmap::Heatmaps;
+use rust_project::log::LogRecord;
+use rust_project::map::{Map, MapDiff, Tile};
+use rust_project::robot::{Robot, RobotActor, RobotCmd, RobotStatus};
+use rust_project::station::{DockPhase, RobotReport, StationCmd, StationSite};
+use rust_project::traffic::{self, DockOrder, TrafficController, TrafficStats};
+use rust_project::watchdog::{Incident, Watchdog, WatchdogConfig, WatchdogStats};
+
+pub type RobotPositions = Vec<(usize, (usize, usize))>;
 
 pub struct RobotCoordinator {
     map: Arc<RwLock<Map>>,
@@ -13,7 +20,17 @@ pub struct RobotCoordinator {
     robot_threads: HashMap<usize, thread::JoinHandle<()>>,
     tx_report: mpsc::Sender<RobotReport>,
     rx_cmd: mpsc::Receiver<StationCmd>,
-    next_robot_id: usize,
+    dynamics: Option<WorldDynamics>,
+    events: Vec<WorldEvent>,
+    stations: Vec<StationSite>,
+    traffic: TrafficController,
+    watchdog: Watchdog,
+    incidents: Vec<Incident>,
+    heatmaps: Heatmaps,
+    /// Cells changed from the console, reported with the next tick.
+    edits: Vec<((usize, usize), Option<Tile>, Tile)>,
+    tx_log: mpsc::Sender<LogRecord>,
+    rx_log: mpsc::Receiver<LogRecord>,
 }
 
 impl RobotCoordinator {
@@ -24,23 +41,98 @@ impl RobotCoordinator {
         initial_robots: Vec<Robot>,
     ) -> Self {
         let map = Arc::new(RwLock::new(map));
+        let (tx_log, rx_log) = mpsc::channel();
         let mut coordinator = Self {
             map,
             robot_senders: HashMap::new(),
             robot_threads: HashMap::new(),
             tx_report,
             rx_cmd,
-            next_robot_id: initial_robots.iter().map(|r| r.id).max().unwrap_or(0) + 1,
+            dynamics: None,
+            events: Vec::new(),
+            stations: Vec::new(),
+            traffic: TrafficController::new(),
+            watchdog: Watchdog::new(WatchdogConfig::default(), 0),
+            incidents: Vec::new(),
+            heatmaps: Heatmaps::new(),
+            edits: Vec::new(),
+            tx_log,
+            rx_log,
         };
 
         for robot in initial_robots {
-            coordinator.spawn_robot_actor(robot);
+            coordinator.spawn_robot_actor(robot, 0);
         }
 
         coordinator
     }
 
-    fn spawn_robot_actor(&mut self, robot: Robot) {
+    pub fn with_dynamics(mut self, dynamics: WorldDynamics) -> Self {
+        self.dynamics = Some(dynamics);
+        self
+    }
+
+    pub fn with_watchdog(mut self, watchdog: Watchdog) -> Self {
+        self.watchdog = watchdog;
+        self
+    }
+
+    /// Adds a station, or replaces it when the id is already known, and
+    /// tells every robot.
+    pub fn set_station(&mut self, site: StationSite) {
+        match self.stations.iter_mut().find(|known| known.id == site.id) {
+            Some(known) => *known = site,
+            None => self.stations.push(site),
+        }
+        for tx in self.robot_senders.values() {
+            let _ = tx.send(RobotCmd::Stations(self.stations.clone()));
+        }
+    }
+
+    /// Overwrites a ground-truth cell; cells off the map are ignored.
+    pub fn set_tile(&mut self, (row, col): (usize, usize), tile: Tile) {
+        let mut map = self.map.write().unwrap();
+        if row >= map.grid.len() || col >= map.cols {
+            return;
+        }
+        let before = map.grid[row][col];
+        if before != tile {
+            map.grid[row][col] = tile;
+            self.edits.push(((row, col), Some(before), tile));
+        }
+    }
+
+    pub fn traffic_stats(&self) -> TrafficStats {
+        self.traffic.stats()
+    }
+
+    pub fn watchdog_stats(&self) -> WatchdogStats {
+        self.watchdog.stats()
+    }
+
+    /// Copy of the ground-truth map.
+    pub fn map(&self) -> Map {
+        self.map.read().unwrap().clone()
+    }
+
+    pub fn take_events(&mut self) -> Vec<WorldEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn heatmaps(&self) -> &Heatmaps {
+        &self.heatmaps
+    }
+
+    pub fn take_incidents(&mut self) -> Vec<Incident> {
+        std::mem::take(&mut self.incidents)
+    }
+
+    /// Records logged by the robots since the last call.
+    pub fn take_logs(&self) -> Vec<LogRecord> {
+        self.rx_log.try_iter().collect()
+    }
+
+    fn spawn_robot_actor(&mut self, robot: Robot, tick: u64) {
         let robot_id = robot.id;
         let (tx, rx) = mpsc::channel();
         let tx_report = self.tx_report.clone();
@@ -48,13 +140,18 @@ impl RobotCoordinator {
 
         let _ = self.tx_report.send(RobotReport {
             robot_id,
-            tick: 0,
-            map_diff: crate::map::MapDiff(vec![]),
+            station: 0,
+            tick,
+            base_version: 0,
+            map_diff: rust_project::map::MapDiff(vec![]),
             energy: 0,
             mineral: 0,
+            dock: DockPhase::None,
+            loadout: robot.loadout(),
         });
 
-        let actor = RobotActor::new(robot, map_clone, rx, tx_report);
+        let _ = tx.send(RobotCmd::Stations(self.stations.clone()));
+        let actor = RobotActor::new(robot, map_clone, rx, tx_report).with_log(self.tx_log.clone());
         let handle = thread::spawn(move || {
             actor.run();
         });
@@ -63,7 +160,8 @@ impl RobotCoordinator {
         self.robot_threads.insert(robot_id, handle);
     }
 
-    pub fn tick(&mut self, tick_count: u64) -> (bool, Vec<(usize, (usize, usize))>) {
+    /// Runs one tick and returns copies of the robots as they ended it.
+    pub fn tick(&mut self, tick_count: u64) -> (bool, Vec<Robot>) {
         let mut done = false;
 
         while let Ok(cmd) = self.rx_cmd.try_recv() {
@@ -73,18 +171,48 @@ impl RobotCoordinator {
                     modules,
                     start_pos,
                 } => {
+                    let (rows, cols) = {
+                        let map = self.map.read().unwrap();
+                        (map.grid.len(), map.cols)
+                    };
+                    let start_pos = (
+                        start_pos.0.min(rows.saturating_sub(1)),
+                        start_pos.1.min(cols.saturating_sub(1)),
+                    );
                     let robot = Robot::new(id, start_pos, modules);
-                    self.spawn_robot_actor(robot);
+                    self.spawn_robot_actor(robot, tick_count);
                 }
-                StationCmd::Snapshot { id, version, diff } => {
-                    if let Ok(mut map) = self.map.write() {
-                        diff.apply(&mut map);
-                    }
-
+                StationCmd::Snapshot {
+                    id,
+                    station,
+                    version,
+                    diff,
+                } => {
                     if let Some(tx) = self.robot_senders.get(&(id as usize)) {
-                        let _ = tx.send(RobotCmd::Snapshot { version, diff });
+                        let _ = tx.send(RobotCmd::Snapshot {
+                            station,
+                            version,
+                            diff,
+                        });
                     }
                 }
+                StationCmd::StationOnline(site) => self.set_station(site),
+                StationCmd::Retrofit { id, op } => {
+                    if let Some(tx) = self.robot_senders.get(&id) {
+                        let _ = tx.send(RobotCmd::Retrofit(op));
+                    }
+                }
+                StationCmd::Recall(id) => {
+                    if let Some(tx) = self.robot_senders.get(&id) {
+                        let _ = tx.send(RobotCmd::Recall);
+                    }
+                }
+                StationCmd::Assign { id, station } => {
+                    if let Some(tx) = self.robot_senders.get(&id) {
+                        let _ = tx.send(RobotCmd::Assign(station));
+                    }
+                }
+                StationCmd::SetTile { cell, tile } => self.set_tile(cell, tile),
                 StationCmd::Shutdown => {
                     self.shutdown();
                     done = true;
@@ -93,60 +221,73 @@ impl RobotCoordinator {
             }
         }
 
-        let (tx_pos1, rx_pos1) = mpsc::channel();
-        for (id, tx) in &self.robot_senders {
-            let tx_pos_clone = tx_pos1.clone();
-            let _ = tx.send(RobotCmd::ReportPosition {
-                respond_to: tx_pos_clone,
-            });
-        }
+        let statuses = self.robot_statuses();
+        let current_positions: RobotPositions =
+            statuses.iter().map(|s| (s.id, s.position)).collect();
+        let collectors: Vec<_> = statuses
+            .iter()
+            .filter(|s| s.collects)
+            .map(|s| (s.id, s.position))
+            .collect();
 
-        let mut current_positions = Vec::new();
-        for _ in 0..self.robot_senders.len() {
-            if let Ok((id, pos)) = rx_pos1.recv_timeout(Duration::from_millis(50)) {
-                current_positions.push((id, pos));
-            }
-        }
-
-        let mut total_energy = 0;
-        let mut total_mineral = 0;
-        let mut all_map_updates = Vec::new();
+        let mut all_map_updates = std::mem::take(&mut self.edits);
 
         let mut robot_collections = HashMap::<usize, (u32, u32)>::new();
 
         {
             let mut map = self.map.write().unwrap();
 
-            for &(robot_id, (row, col)) in &current_positions {
+            for &(robot_id, (row, col)) in &collectors {
                 if row < map.grid.len() && col < map.cols {
                     let tile = map.grid[row][col];
                     if matches!(tile, Tile::Energy | Tile::Mineral) {
                         map.grid[row][col] = Tile::Empty;
 
                         all_map_updates.push(((row, col), Some(tile), Tile::Empty));
+                        self.heatmaps.record_collection((row, col));
 
+                        let entry = robot_collections.entry(robot_id).or_insert((0, 0));
                         if tile == Tile::Energy {
-                            total_energy += 1;
-                            let entry = robot_collections.entry(robot_id).or_insert((0, 0));
                             entry.0 += 1;
-                        } else if tile == Tile::Mineral {
-                            total_mineral += 1;
-                            let entry = robot_collections.entry(robot_id).or_insert((0, 0));
+                        } else {
                             entry.1 += 1;
                         }
                     }
                 }
             }
+
+            if let Some(dynamics) = self.dynamics.as_mut() {
+                dynamics.record_collection(&MapDiff(all_map_updates.clone()), tick_count);
+
+                let mut protected: HashSet<(usize, usize)> =
+                    current_positions.iter().map(|&(_, pos)| pos).collect();
+                let (rows, cols) = (map.grid.len(), map.cols);
+                protected.extend(self.stations.iter().flat_map(|site| site.zone(rows, cols)));
+
+                let (world_diff, events) = dynamics.tick(&mut map, tick_count, &protected);
+                all_map_updates.extend(world_diff.0);
+                self.events.extend(events);
+            }
         }
 
-        if !all_map_updates.is_empty() {
-            let _ = self.tx_report.send(RobotReport {
-                robot_id: 0,
-                tick: tick_count,
-                map_diff: MapDiff(all_map_updates),
-                energy: total_energy,
-                mineral: total_mineral,
-            });
+        // sent every tick, even when empty, so the station's watermark advances
+        let _ = self.tx_report.send(RobotReport {
+            robot_id: 0,
+            station: 0,
+            tick: tick_count,
+            base_version: 0,
+            map_diff: MapDiff(all_map_updates),
+            energy: 0,
+            mineral: 0,
+            dock: DockPhase::None,
+            loadout: Vec::new(),
+        });
+
+        // stocks are credited when the robot docks and uploads its cargo
+        for (robot_id, (energy, mineral)) in robot_collections {
+            if let Some(tx) = self.robot_senders.get(&robot_id) {
+                let _ = tx.send(RobotCmd::Cargo { energy, mineral });
+            }
         }
 
         let map_obstacles: HashSet<(usize, usize)> = {
@@ -166,38 +307,128 @@ impl RobotCoordinator {
                 .collect()
         };
 
+        let (orders, lanes) = {
+            let map = self.map.read().unwrap();
+            let orders = self
+                .traffic
+                .plan(&self.stations, &statuses, &map, tick_count);
+            let lanes: HashSet<(usize, usize)> = self
+                .stations
+                .iter()
+                .flat_map(|site| traffic::exit_lane(site, map.grid.len(), map.cols))
+                .collect();
+            (orders, lanes)
+        };
+
+        // queued robots stand still on purpose
+        let excused: HashSet<usize> = orders
+            .iter()
+            .filter(|(_, order)| matches!(order, DockOrder::Wait(_) | DockOrder::Hold))
+            .map(|(&id, _)| id)
+            .collect();
+        let incidents = self.watchdog.observe(&statuses, &excused, tick_count);
+        let recovery_ticks = self.watchdog.config().recovery_ticks;
+        for incident in &incidents {
+            if let Some(tx) = self.robot_senders.get(&incident.robot) {
+                let _ = tx.send(RobotCmd::Recover {
+                    recovery: incident.recovery,
+                    ticks: recovery_ticks,
+                });
+            }
+        }
+        self.incidents.extend(incidents);
+
+        let mut blinded_robots = HashSet::new();
         for (id, tx) in &self.robot_senders {
             let mut occupied = map_obstacles.clone();
+            let mut blinded = false;
+            let mut own = None;
             for &(robot_id, pos) in &current_positions {
                 if robot_id != *id {
                     occupied.insert(pos);
+                } else {
+                    own = Some(pos);
+                    if let Some(dynamics) = &self.dynamics {
+                        blinded = dynamics.is_blinded(pos, tick_count);
+                    }
                 }
             }
 
+            // arriving robots keep off the exit lanes
+            let dock = orders.get(id).copied().unwrap_or_default();
+            if dock != DockOrder::Free {
+                occupied.extend(lanes.iter().filter(|&&cell| Some(cell) != own));
+            }
+
+            if blinded {
+                blinded_robots.insert(*id);
+            }
             let _ = tx.send(RobotCmd::Tick {
                 tick_count,
                 occupied_positions: occupied,
+                blinded,
+                dock,
             });
         }
 
         std::thread::sleep(Duration::from_millis(20));
 
-        let (tx_pos2, rx_pos2) = mpsc::channel();
-        for (id, tx) in &self.robot_senders {
-            let tx_pos_clone = tx_pos2.clone();
-            let _ = tx.send(RobotCmd::ReportPosition {
-                respond_to: tx_pos_clone,
+        let robots = self.inspect();
+        let map = self.map.read().unwrap();
+        for robot in &robots {
+            self.heatmaps.record_visit(robot.position);
+            let started = current_positions
+                .iter()
+                .find(|&&(id, _)| id == robot.id)
+                .map(|&(_, pos)| pos);
+            if started == Some(robot.position) && !excused.contains(&robot.id) {
+                self.heatmaps.record_blocked(robot.position);
+            }
+            // the scan happens before the move; the cells seen from here are
+            // what it sees next tick, close enough for the overlay
+            if !blinded_robots.contains(&robot.id) {
+                self.heatmaps
+                    .record_observed(robot.field_of_view(&map), tick_count);
+            }
+        }
+        drop(map);
+
+        (done, robots)
+    }
+
+    pub fn inspect(&self) -> Vec<Robot> {
+        let (tx_robot, rx_robot) = mpsc::channel();
+        for tx in self.robot_senders.values() {
+            let _ = tx.send(RobotCmd::Inspect {
+                respond_to: tx_robot.clone(),
             });
         }
 
-        let mut final_positions = Vec::new();
+        let mut robots = Vec::new();
         for _ in 0..self.robot_senders.len() {
-            if let Ok((id, pos)) = rx_pos2.recv_timeout(Duration::from_millis(50)) {
-                final_positions.push((id, pos));
+            if let Ok(robot) = rx_robot.recv_timeout(Duration::from_millis(50)) {
+                robots.push(robot);
             }
         }
+        robots.sort_by_key(|robot| robot.id);
+        robots
+    }
+
+    fn robot_statuses(&self) -> Vec<RobotStatus> {
+        let (tx_status, rx_status) = mpsc::channel();
+        for tx in self.robot_senders.values() {
+            let _ = tx.send(RobotCmd::ReportStatus {
+                respond_to: tx_status.clone(),
+            });
+        }
 
-        (done, final_positions)
+        let mut statuses = Vec::new();
+        for _ in 0..self.robot_senders.len() {
+            if let Ok(status) = rx_status.recv_timeout(Duration::from_millis(50)) {
+                statuses.push(status);
+            }
+        }
+        statuses
     }
 
     pub fn shutdown(&mut self) {
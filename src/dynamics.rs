@@ -0,0 +1,264 @@
+use crate::generator::chance;
+use crate::map::{Map, MapDiff, Tile};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashSet};
+
+/// Chances outside `[0, 1]` are clamped when sampled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicsConfig {
+    /// Chance per tick that a depleted cell grows its resource back.
+    pub regrowth_chance: f64,
+    /// Ticks a depleted cell stays empty before it may regrow.
+    pub regrowth_delay: u64,
+    /// Ticks between two environmental events, 0 disables them.
+    pub event_interval: u64,
+    pub storm_radius: usize,
+    pub storm_duration: u64,
+    pub slide_radius: usize,
+    pub meteor_radius: usize,
+    pub meteor_science_chance: f64,
+}
+
+impl Default for DynamicsConfig {
+    fn default() -> Self {
+        Self {
+            regrowth_chance: 0.02,
+            regrowth_delay: 30,
+            event_interval: 40,
+            storm_radius: 3,
+            storm_duration: 15,
+            slide_radius: 1,
+            meteor_radius: 2,
+            meteor_science_chance: 0.5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    DustStorm,
+    RockSlide,
+    MeteorStrike,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorldEvent {
+    Regrowth {
+        cells: Vec<(usize, usize)>,
+    },
+    DustStorm {
+        center: (usize, usize),
+        radius: usize,
+        until: u64,
+    },
+    RockSlide {
+        center: (usize, usize),
+        blocked: Vec<(usize, usize)>,
+    },
+    MeteorStrike {
+        center: (usize, usize),
+        revealed: Vec<(usize, usize)>,
+    },
+}
+
+impl WorldEvent {
+    pub fn describe(&self) -> String {
+        match self {
+            WorldEvent::Regrowth { cells } => format!("{} resources regrew", cells.len()),
+            WorldEvent::DustStorm {
+                center,
+                radius,
+                until,
+            } => format!(
+                "Dust storm at ({}, {}) r{} until tick {}",
+                center.0, center.1, radius, until
+            ),
+            WorldEvent::RockSlide { center, blocked } => format!(
+                "Rock slide at ({}, {}) blocked {} cells",
+                center.0,
+                center.1,
+                blocked.len()
+            ),
+            WorldEvent::MeteorStrike { center, revealed } => format!(
+                "Meteor strike at ({}, {}) revealed {} science",
+                center.0,
+                center.1,
+                revealed.len()
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DustStorm {
+    center: (usize, usize),
+    radius: usize,
+    until: u64,
+}
+
+/// Changes the ground truth over time. Ticked by the coordinator, which
+/// forwards the returned diffs to the station like any other observation.
+pub struct WorldDynamics {
+    config: DynamicsConfig,
+    rng: StdRng,
+    depleted: BTreeMap<(usize, usize), (Tile, u64)>,
+    storms: Vec<DustStorm>,
+}
+
+fn cells_around(map: &Map, (r, c): (usize, usize), radius: usize) -> Vec<(usize, usize)> {
+    let rows = r.saturating_sub(radius)..=(r + radius).min(map.grid.len().saturating_sub(1));
+    rows.flat_map(|row| {
+        let cols = c.saturating_sub(radius)..=(c + radius).min(map.cols.saturating_sub(1));
+        cols.map(move |col| (row, col))
+    })
+    .collect()
+}
+
+impl WorldDynamics {
+    pub fn new(config: DynamicsConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            depleted: BTreeMap::new(),
+            storms: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &DynamicsConfig {
+        &self.config
+    }
+
+    /// Remembers collected cells so they can regrow later.
+    pub fn record_collection(&mut self, diff: &MapDiff, tick: u64) {
+        for &(pos, before, after) in &diff.0 {
+            if let (Some(tile @ (Tile::Energy | Tile::Mineral)), Tile::Empty) = (before, after) {
+                self.depleted.insert(pos, (tile, tick));
+            }
+        }
+    }
+
+    pub fn is_blinded(&self, (r, c): (usize, usize), tick: u64) -> bool {
+        self.storms.iter().any(|storm| {
+            tick <= storm.until
+                && r.abs_diff(storm.center.0) <= storm.radius
+                && c.abs_diff(storm.center.1) <= storm.radius
+        })
+    }
+
+    /// Advances the world by one tick. `protected` cells (robots, station)
+    /// never turn into obstacles.
+    pub fn tick(
+        &mut self,
+        map: &mut Map,
+        tick: u64,
+        protected: &HashSet<(usize, usize)>,
+    ) -> (MapDiff, Vec<WorldEvent>) {
+        let mut diff = MapDiff(Vec::new());
+        let mut events = Vec::new();
+
+        self.storms.retain(|storm| storm.until >= tick);
+
+        let mut regrown = Vec::new();
+        let ready: Vec<_> = self
+            .depleted
+            .iter()
+            .filter(|(_, &(_, since))| tick >= since + self.config.regrowth_delay)
+            .map(|(&pos, &(tile, _))| (pos, tile))
+            .collect();
+        for (pos, tile) in ready {
+            if !self.rng.gen_bool(chance(self.config.regrowth_chance)) {
+                continue;
+            }
+            self.depleted.remove(&pos);
+            if map.grid[pos.0][pos.1] == Tile::Empty && !protected.contains(&pos) {
+                map.grid[pos.0][pos.1] = tile;
+                diff.0.push((pos, Some(Tile::Empty), tile));
+                regrown.push(pos);
+            }
+        }
+        if !regrown.is_empty() {
+            events.push(WorldEvent::Regrowth { cells: regrown });
+        }
+
+        if self.config.event_interval > 0
+            && tick > 0
+            && tick.is_multiple_of(self.config.event_interval)
+            && !map.grid.is_empty()
+            && map.cols > 0
+        {
+            let kind = match self.rng.gen_range(0, 3) {
+                0 => EventKind::DustStorm,
+                1 => EventKind::RockSlide,
+                _ => EventKind::MeteorStrike,
+            };
+            let center = (
+                self.rng.gen_range(0, map.grid.len()),
+                self.rng.gen_range(0, map.cols),
+            );
+            let (event_diff, event) = self.trigger(kind, center, map, tick, protected);
+            diff.merge(event_diff);
+            events.push(event);
+        }
+
+        (diff, events)
+    }
+
+    pub fn trigger(
+        &mut self,
+        kind: EventKind,
+        center: (usize, usize),
+        map: &mut Map,
+        tick: u64,
+        protected: &HashSet<(usize, usize)>,
+    ) -> (MapDiff, WorldEvent) {
+        let mut diff = MapDiff(Vec::new());
+
+        let event = match kind {
+            EventKind::DustStorm => {
+                let storm = DustStorm {
+                    center,
+                    radius: self.config.storm_radius,
+                    until: tick + self.config.storm_duration,
+                };
+                self.storms.push(storm);
+                WorldEvent::DustStorm {
+                    center,
+                    radius: storm.radius,
+                    until: storm.until,
+                }
+            }
+            EventKind::RockSlide => {
+                let mut blocked = Vec::new();
+                for pos in cells_around(map, center, self.config.slide_radius) {
+                    let tile = map.grid[pos.0][pos.1];
+                    if tile == Tile::Empty && !protected.contains(&pos) {
+                        map.grid[pos.0][pos.1] = Tile::Obstacle;
+                        diff.0.push((pos, Some(tile), Tile::Obstacle));
+                        self.depleted.remove(&pos);
+                        blocked.push(pos);
+                    }
+                }
+                WorldEvent::RockSlide { center, blocked }
+            }
+            EventKind::MeteorStrike => {
+                let mut revealed = Vec::new();
+                for pos in cells_around(map, center, self.config.meteor_radius) {
+                    let tile = map.grid[pos.0][pos.1];
+                    if matches!(tile, Tile::Empty | Tile::Energy | Tile::Mineral)
+                        && !protected.contains(&pos)
+                        && self.rng.gen_bool(chance(self.config.meteor_science_chance))
+                    {
+                        map.grid[pos.0][pos.1] = Tile::Science;
+                        diff.0.push((pos, Some(tile), Tile::Science));
+                        self.depleted.remove(&pos);
+                        revealed.push(pos);
+                    }
+                }
+                WorldEvent::MeteorStrike { center, revealed }
+            }
+        };
+
+        (diff, event)
+    }
+}
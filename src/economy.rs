@@ -0,0 +1,501 @@
+use crate::robot::RobotModule;
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+
+const DEFAULT_MAX_QUEUE: usize = 3;
+const TREND_LEN: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cost {
+    pub energy: u32,
+    pub mineral: u32,
+    /// Build time in station ticks.
+    pub ticks: u64,
+}
+
+impl Cost {
+    pub const fn new(energy: u32, mineral: u32, ticks: u64) -> Self {
+        Self {
+            energy,
+            mineral,
+            ticks,
+        }
+    }
+}
+
+impl Add for Cost {
+    type Output = Cost;
+
+    fn add(self, other: Cost) -> Cost {
+        Cost::new(
+            self.energy + other.energy,
+            self.mineral + other.mineral,
+            self.ticks + other.ticks,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stocks {
+    pub energy: u32,
+    pub mineral: u32,
+}
+
+impl Stocks {
+    pub fn can_afford(&self, cost: Cost) -> bool {
+        self.energy >= cost.energy && self.mineral >= cost.mineral
+    }
+
+    fn pay(&mut self, cost: Cost) {
+        self.energy -= cost.energy;
+        self.mineral -= cost.mineral;
+    }
+
+    /// Adds signed amounts, stopping at zero and at `u32::MAX`.
+    pub fn adjust(&mut self, energy: i64, mineral: i64) {
+        let adjust = |stock: u32, delta: i64| {
+            i64::from(stock)
+                .saturating_add(delta)
+                .clamp(0, i64::from(u32::MAX)) as u32
+        };
+        self.energy = adjust(self.energy, energy);
+        self.mineral = adjust(self.mineral, mineral);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Upgrade {
+    Payload,
+    ScanRadius,
+    Speed,
+}
+
+impl Upgrade {
+    /// Module that gains a level from this upgrade.
+    pub fn module(&self) -> RobotModule {
+        match self {
+            Upgrade::Payload => RobotModule::Collector,
+            Upgrade::ScanRadius => RobotModule::Scanner,
+            Upgrade::Speed => RobotModule::Explorer,
+        }
+    }
+
+    /// Upgrade that levels `module` up, if it has levels.
+    pub fn for_module(module: &RobotModule) -> Option<Self> {
+        [Upgrade::Payload, Upgrade::ScanRadius, Upgrade::Speed]
+            .into_iter()
+            .find(|upgrade| upgrade.module() == *module)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildItem {
+    Robot(Vec<RobotModule>),
+    /// Spare module, fitted to a robot later.
+    Module(RobotModule),
+    Upgrade(Upgrade),
+    /// Forward station placed by the station network.
+    Outpost,
+}
+
+impl BuildItem {
+    pub fn label(&self) -> String {
+        match self {
+            BuildItem::Robot(modules) => {
+                let codes: String = modules.iter().map(module_code).collect();
+                format!("Robot [{}]", codes)
+            }
+            BuildItem::Module(module) => format!("{:?} module", module),
+            BuildItem::Upgrade(Upgrade::Payload) => "Payload upgrade".to_string(),
+            BuildItem::Upgrade(Upgrade::ScanRadius) => "Scan radius upgrade".to_string(),
+            BuildItem::Upgrade(Upgrade::Speed) => "Speed upgrade".to_string(),
+            BuildItem::Outpost => "Outpost".to_string(),
+        }
+    }
+
+    pub fn full_robot() -> Self {
+        BuildItem::Robot(vec![
+            RobotModule::Explorer,
+            RobotModule::Collector,
+            RobotModule::Scanner,
+            RobotModule::Sensor,
+        ])
+    }
+}
+
+fn module_code(module: &RobotModule) -> char {
+    match module {
+        RobotModule::Explorer => 'E',
+        RobotModule::Collector => 'C',
+        RobotModule::Scanner => 'S',
+        RobotModule::Sensor => 'N',
+    }
+}
+
+/// Module for a one-letter code: `E`xplorer, `C`ollector, `S`canner, se`N`sor.
+pub fn code_module(code: char) -> Option<RobotModule> {
+    match code {
+        'E' => Some(RobotModule::Explorer),
+        'C' => Some(RobotModule::Collector),
+        'S' => Some(RobotModule::Scanner),
+        'N' => Some(RobotModule::Sensor),
+        _ => None,
+    }
+}
+
+/// Compact form used by the journal: `robot:ECSN`, `module:S`,
+/// `upgrade:payload`, `outpost`.
+impl fmt::Display for BuildItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildItem::Robot(modules) => {
+                let codes: String = modules.iter().map(module_code).collect();
+                write!(f, "robot:{}", codes)
+            }
+            BuildItem::Module(module) => write!(f, "module:{}", module_code(module)),
+            BuildItem::Upgrade(Upgrade::Payload) => write!(f, "upgrade:payload"),
+            BuildItem::Upgrade(Upgrade::ScanRadius) => write!(f, "upgrade:scan"),
+            BuildItem::Upgrade(Upgrade::Speed) => write!(f, "upgrade:speed"),
+            BuildItem::Outpost => write!(f, "outpost"),
+        }
+    }
+}
+
+impl FromStr for BuildItem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid build item {:?}", s);
+        if s == "outpost" {
+            return Ok(BuildItem::Outpost);
+        }
+        let (kind, arg) = s.split_once(':').ok_or_else(invalid)?;
+        match kind {
+            "robot" => arg
+                .chars()
+                .map(code_module)
+                .collect::<Option<_>>()
+                .map(BuildItem::Robot)
+                .ok_or_else(invalid),
+            "module" => {
+                let mut codes = arg.chars();
+                match (codes.next().and_then(code_module), codes.next()) {
+                    (Some(module), None) => Ok(BuildItem::Module(module)),
+                    _ => Err(invalid()),
+                }
+            }
+            "upgrade" => match arg {
+                "payload" => Ok(BuildItem::Upgrade(Upgrade::Payload)),
+                "scan" => Ok(BuildItem::Upgrade(Upgrade::ScanRadius)),
+                "speed" => Ok(BuildItem::Upgrade(Upgrade::Speed)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostTable {
+    pub chassis: Cost,
+    pub module: Cost,
+    pub payload_upgrade: Cost,
+    pub scan_upgrade: Cost,
+    pub speed_upgrade: Cost,
+    pub outpost: Cost,
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self {
+            chassis: Cost::new(2, 2, 4),
+            module: Cost::new(2, 2, 2),
+            payload_upgrade: Cost::new(6, 8, 8),
+            scan_upgrade: Cost::new(8, 6, 8),
+            speed_upgrade: Cost::new(8, 8, 10),
+            outpost: Cost::new(20, 20, 20),
+        }
+    }
+}
+
+impl CostTable {
+    /// Same prices as the default table, but everything is built at once.
+    pub fn instant() -> Self {
+        let timed = Self::default();
+        let instant = |cost: Cost| Cost { ticks: 0, ..cost };
+        Self {
+            chassis: instant(timed.chassis),
+            module: instant(timed.module),
+            payload_upgrade: instant(timed.payload_upgrade),
+            scan_upgrade: instant(timed.scan_upgrade),
+            speed_upgrade: instant(timed.speed_upgrade),
+            outpost: instant(timed.outpost),
+        }
+    }
+
+    pub fn cost(&self, item: &BuildItem) -> Cost {
+        match item {
+            BuildItem::Robot(modules) => modules
+                .iter()
+                .fold(self.chassis, |total, _| total + self.module),
+            BuildItem::Module(_) => self.module,
+            BuildItem::Upgrade(Upgrade::Payload) => self.payload_upgrade,
+            BuildItem::Upgrade(Upgrade::ScanRadius) => self.scan_upgrade,
+            BuildItem::Upgrade(Upgrade::Speed) => self.speed_upgrade,
+            BuildItem::Outpost => self.outpost,
+        }
+    }
+}
+
+/// Decides what the station spends its stocks on.
+pub trait SpendingPolicy: Send {
+    fn name(&self) -> &'static str;
+
+    /// Next item to buy with `stocks`, or `None` to keep saving.
+    fn next_build(&mut self, stocks: Stocks, costs: &CostTable) -> Option<BuildItem>;
+}
+
+/// Builds a full robot whenever it can afford one.
+pub struct Greedy;
+
+impl SpendingPolicy for Greedy {
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn next_build(&mut self, stocks: Stocks, costs: &CostTable) -> Option<BuildItem> {
+        let robot = BuildItem::full_robot();
+        stocks.can_afford(costs.cost(&robot)).then_some(robot)
+    }
+}
+
+/// Keeps the price of `target` in reserve and only spends the surplus on
+/// robots.
+pub struct SavingForUpgrade {
+    pub target: Upgrade,
+}
+
+impl SpendingPolicy for SavingForUpgrade {
+    fn name(&self) -> &'static str {
+        "saving"
+    }
+
+    fn next_build(&mut self, stocks: Stocks, costs: &CostTable) -> Option<BuildItem> {
+        let upgrade = BuildItem::Upgrade(self.target);
+        let reserve = costs.cost(&upgrade);
+        let robot = BuildItem::full_robot();
+        if stocks.can_afford(costs.cost(&robot) + reserve) {
+            Some(robot)
+        } else {
+            stocks.can_afford(reserve).then_some(upgrade)
+        }
+    }
+}
+
+/// Alternates robots with upgrades, waiting until the next one in the
+/// rotation is affordable.
+#[derive(Default)]
+pub struct Balanced {
+    next: usize,
+}
+
+impl SpendingPolicy for Balanced {
+    fn name(&self) -> &'static str {
+        "balanced"
+    }
+
+    fn next_build(&mut self, stocks: Stocks, costs: &CostTable) -> Option<BuildItem> {
+        let rotation = [
+            BuildItem::full_robot(),
+            BuildItem::Upgrade(Upgrade::Payload),
+            BuildItem::full_robot(),
+            BuildItem::Upgrade(Upgrade::ScanRadius),
+            BuildItem::full_robot(),
+            BuildItem::Upgrade(Upgrade::Speed),
+        ];
+        let item = rotation[self.next % rotation.len()].clone();
+        if !stocks.can_afford(costs.cost(&item)) {
+            return None;
+        }
+        self.next += 1;
+        Some(item)
+    }
+}
+
+pub fn policy_by_name(name: &str) -> Option<Box<dyn SpendingPolicy>> {
+    match name {
+        "greedy" => Some(Box::new(Greedy)),
+        "saving" => Some(Box::new(SavingForUpgrade {
+            target: Upgrade::Payload,
+        })),
+        "balanced" => Some(Box::new(Balanced::default())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedBuild {
+    pub item: BuildItem,
+    pub cost: Cost,
+    /// Ticks left; only the front of the queue makes progress.
+    pub remaining: u64,
+}
+
+/// What the TUI shows of the economy.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EconomyStatus {
+    pub policy: &'static str,
+    pub queue: Vec<QueuedBuild>,
+    pub energy_trend: Vec<u64>,
+    pub mineral_trend: Vec<u64>,
+}
+
+/// Build queue of the station. Items are paid when queued and built one at a
+/// time in order.
+pub struct Economy {
+    costs: CostTable,
+    policy: Box<dyn SpendingPolicy>,
+    queue: VecDeque<QueuedBuild>,
+    max_queue: usize,
+    /// Outposts the station may queue in total.
+    outposts: usize,
+    outposts_queued: usize,
+    trend: VecDeque<Stocks>,
+}
+
+impl Economy {
+    pub fn new(costs: CostTable, policy: Box<dyn SpendingPolicy>) -> Self {
+        Self {
+            costs,
+            policy,
+            queue: VecDeque::new(),
+            max_queue: DEFAULT_MAX_QUEUE,
+            outposts: 0,
+            outposts_queued: 0,
+            trend: VecDeque::new(),
+        }
+    }
+
+    /// Greedy robots built at once, one per report: the station's original
+    /// behaviour.
+    pub fn instant() -> Self {
+        Self::new(CostTable::instant(), Box::new(Greedy)).with_max_queue(1)
+    }
+
+    pub fn with_max_queue(mut self, max_queue: usize) -> Self {
+        self.max_queue = max_queue.max(1);
+        self
+    }
+
+    /// Allows up to `outposts` outposts, each queued once the stocks cover
+    /// it and a full robot on top.
+    pub fn with_outposts(mut self, outposts: usize) -> Self {
+        self.outposts = outposts;
+        self
+    }
+
+    /// Outposts queued so far, counted against the allowance.
+    pub fn outposts_queued(&self) -> usize {
+        self.outposts_queued
+    }
+
+    /// Counts outposts queued before a restart against the allowance.
+    pub fn restore_outposts(&mut self, queued: usize) {
+        self.outposts_queued = queued;
+    }
+
+    pub fn policy_name(&self) -> &'static str {
+        self.policy.name()
+    }
+
+    pub fn costs(&self) -> &CostTable {
+        &self.costs
+    }
+
+    pub fn queue(&self) -> &VecDeque<QueuedBuild> {
+        &self.queue
+    }
+
+    /// Pays for `item` and appends it to the queue.
+    pub fn enqueue(&mut self, item: BuildItem, stocks: &mut Stocks) -> Result<Cost, Cost> {
+        let cost = self.costs.cost(&item);
+        if !stocks.can_afford(cost) {
+            return Err(cost);
+        }
+        stocks.pay(cost);
+        self.queue.push_back(QueuedBuild {
+            item,
+            cost,
+            remaining: cost.ticks,
+        });
+        Ok(cost)
+    }
+
+    /// Re-queues an item that was already paid for, e.g. after a restart.
+    pub fn restore(&mut self, item: BuildItem) {
+        let cost = self.costs.cost(&item);
+        self.queue.push_back(QueuedBuild {
+            item,
+            cost,
+            remaining: cost.ticks,
+        });
+    }
+
+    /// Lets the policy fill the queue. Returns the newly queued builds.
+    pub fn plan(&mut self, stocks: &mut Stocks) -> Vec<QueuedBuild> {
+        let mut queued = Vec::new();
+        let reserve = self.costs.cost(&BuildItem::full_robot());
+        if self.outposts_queued < self.outposts
+            && self.queue.len() < self.max_queue
+            && !self.queue.iter().any(|b| b.item == BuildItem::Outpost)
+            && stocks.can_afford(self.costs.outpost + reserve)
+            && self.enqueue(BuildItem::Outpost, stocks).is_ok()
+        {
+            self.outposts_queued += 1;
+            queued.extend(self.queue.back().cloned());
+        }
+        while self.queue.len() < self.max_queue {
+            let Some(item) = self.policy.next_build(*stocks, &self.costs) else {
+                break;
+            };
+            if self.enqueue(item, stocks).is_err() {
+                break;
+            }
+            queued.extend(self.queue.back().cloned());
+        }
+        queued
+    }
+
+    /// Works on the queue for `ticks` and returns the finished items.
+    pub fn advance(&mut self, mut ticks: u64) -> Vec<BuildItem> {
+        let mut finished = Vec::new();
+        while let Some(front) = self.queue.front_mut() {
+            let spent = front.remaining.min(ticks);
+            front.remaining -= spent;
+            ticks -= spent;
+            if front.remaining > 0 {
+                break;
+            }
+            finished.extend(self.queue.pop_front().map(|build| build.item));
+        }
+        finished
+    }
+
+    pub fn record_stocks(&mut self, stocks: Stocks) {
+        if self.trend.len() >= TREND_LEN {
+            self.trend.pop_front();
+        }
+        self.trend.push_back(stocks);
+    }
+
+    pub fn status(&self) -> EconomyStatus {
+        EconomyStatus {
+            policy: self.policy.name(),
+            queue: self.queue.iter().cloned().collect(),
+            energy_trend: self.trend.iter().map(|s| s.energy as u64).collect(),
+            mineral_trend: self.trend.iter().map(|s| s.mineral as u64).collect(),
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use crate::map::{Map, Tile};
+use noise::core::worley::ReturnType;
+use noise::{Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, RidgedMulti, Worley};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::error::Error;
+use std::fmt;
+
+/// Noise cut-offs used to turn a value in `[0, 1]` into a tile.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub obstacle: f64,
+    pub resource: f64,
+    pub science: f64,
+    pub mineral_chance: f64,
+    pub science_chance: f64,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            obstacle: 0.7,
+            resource: 0.4,
+            science: 0.2,
+            mineral_chance: 0.5,
+            science_chance: 0.3,
+        }
+    }
+}
+
+/// A chance outside `[0, 1]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidChance {
+    pub name: &'static str,
+    pub value: f64,
+}
+
+impl fmt::Display for InvalidChance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} must be within [0, 1], got {}", self.name, self.value)
+    }
+}
+
+impl Error for InvalidChance {}
+
+/// `value` as a probability `gen_bool` accepts; NaN counts as never.
+pub(crate) fn chance(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+impl Thresholds {
+    /// Checks the chances, for thresholds built from user input.
+    pub fn validated(self) -> Result<Self, InvalidChance> {
+        for (name, value) in [
+            ("mineral_chance", self.mineral_chance),
+            ("science_chance", self.science_chance),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(InvalidChance { name, value });
+            }
+        }
+        Ok(self)
+    }
+
+    /// Out-of-range chances are clamped rather than trusted.
+    pub fn classify(&self, val: f64, rng: &mut StdRng) -> Tile {
+        if val > self.obstacle {
+            Tile::Obstacle
+        } else if val > self.resource {
+            if rng.gen_bool(chance(self.mineral_chance)) {
+                Tile::Mineral
+            } else {
+                Tile::Energy
+            }
+        } else if val > self.science {
+            if rng.gen_bool(chance(self.science_chance)) {
+                Tile::Science
+            } else {
+                Tile::Empty
+            }
+        } else {
+            Tile::Empty
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoiseConfig {
+    pub frequency: f64,
+    pub octaves: usize,
+    pub thresholds: Thresholds,
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        Self {
+            frequency: 0.1,
+            octaves: 1,
+            thresholds: Thresholds::default(),
+        }
+    }
+}
+
+pub trait MapGenerator {
+    fn config(&self) -> &NoiseConfig;
+
+    /// Noise value in `[0, 1]` for every cell of a `rows x cols` grid.
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>>;
+
+    fn generate(&self, rows: usize, cols: usize, seed: u64) -> Map {
+        let field = self.height_field(rows, cols, seed);
+        let thresholds = self.config().thresholds;
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let grid = field
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&val| thresholds.classify(val, &mut rng))
+                    .collect()
+            })
+            .collect();
+
+        Map { grid, cols }
+    }
+}
+
+// fractal sources are seeded with `seed + octave`, keep room so that never overflows
+pub(crate) fn noise_seed(seed: u64) -> u32 {
+    (seed % u64::from(u32::MAX - Fbm::<Perlin>::MAX_OCTAVES as u32)) as u32
+}
+
+pub(crate) fn sample_grid(
+    rows: usize,
+    cols: usize,
+    frequency: f64,
+    noise: impl Fn([f64; 2]) -> f64,
+) -> Vec<Vec<f64>> {
+    (0..rows)
+        .map(|row| {
+            (0..cols)
+                .map(|col| {
+                    let val = noise([row as f64 * frequency, col as f64 * frequency]);
+                    ((val + 1.0) / 2.0).clamp(0.0, 1.0)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The original map, used by `Map::generate`: unseeded Perlin noise sampled
+/// with the seed as third axis. `octaves` is ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ClassicPerlin {
+    pub config: NoiseConfig,
+}
+
+impl ClassicPerlin {
+    pub fn new(config: NoiseConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl MapGenerator for ClassicPerlin {
+    fn config(&self) -> &NoiseConfig {
+        &self.config
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let perlin = Perlin::default();
+        let wavelength = 1.0 / self.config.frequency;
+        (0..rows)
+            .map(|row| {
+                (0..cols)
+                    .map(|col| {
+                        let p = [
+                            row as f64 / wavelength,
+                            col as f64 / wavelength,
+                            seed as f64,
+                        ];
+                        ((perlin.get(p) + 1.0) / 2.0).clamp(0.0, 1.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Multi-octave (fBm) Perlin noise.
+#[derive(Debug, Clone, Default)]
+pub struct PerlinFbm {
+    pub config: NoiseConfig,
+}
+
+impl PerlinFbm {
+    pub fn new(config: NoiseConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl MapGenerator for PerlinFbm {
+    fn config(&self) -> &NoiseConfig {
+        &self.config
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let fbm = Fbm::<Perlin>::new(noise_seed(seed)).set_octaves(self.config.octaves);
+        sample_grid(rows, cols, self.config.frequency, |p| fbm.get(p))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenSimplexNoise {
+    pub config: NoiseConfig,
+}
+
+impl OpenSimplexNoise {
+    pub fn new(config: NoiseConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for OpenSimplexNoise {
+    fn default() -> Self {
+        Self::new(NoiseConfig {
+            frequency: 0.12,
+            octaves: 3,
+            ..NoiseConfig::default()
+        })
+    }
+}
+
+impl MapGenerator for OpenSimplexNoise {
+    fn config(&self) -> &NoiseConfig {
+        &self.config
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let fbm = Fbm::<OpenSimplex>::new(noise_seed(seed)).set_octaves(self.config.octaves);
+        sample_grid(rows, cols, self.config.frequency, |p| fbm.get(p))
+    }
+}
+
+/// Ridged multifractal noise: long connected ridges that become obstacle walls.
+#[derive(Debug, Clone)]
+pub struct RidgedNoise {
+    pub config: NoiseConfig,
+}
+
+impl RidgedNoise {
+    pub fn new(config: NoiseConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for RidgedNoise {
+    fn default() -> Self {
+        Self::new(NoiseConfig {
+            frequency: 0.08,
+            octaves: 4,
+            thresholds: Thresholds {
+                obstacle: 0.8,
+                resource: 0.55,
+                science: 0.35,
+                ..Thresholds::default()
+            },
+        })
+    }
+}
+
+impl MapGenerator for RidgedNoise {
+    fn config(&self) -> &NoiseConfig {
+        &self.config
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let ridged = RidgedMulti::<Perlin>::new(noise_seed(seed)).set_octaves(self.config.octaves);
+        sample_grid(rows, cols, self.config.frequency, |p| ridged.get(p))
+    }
+}
+
+/// Cellular (Worley) noise: crater floors around feature points, rims in between.
+#[derive(Debug, Clone)]
+pub struct CraterField {
+    pub config: NoiseConfig,
+}
+
+impl CraterField {
+    pub fn new(config: NoiseConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Default for CraterField {
+    fn default() -> Self {
+        Self::new(NoiseConfig {
+            frequency: 0.15,
+            octaves: 1,
+            thresholds: Thresholds {
+                obstacle: 0.75,
+                resource: 0.55,
+                science: 0.1,
+                ..Thresholds::default()
+            },
+        })
+    }
+}
+
+impl MapGenerator for CraterField {
+    fn config(&self) -> &NoiseConfig {
+        &self.config
+    }
+
+    fn height_field(&self, rows: usize, cols: usize, seed: u64) -> Vec<Vec<f64>> {
+        let base_seed = noise_seed(seed);
+        let layers: Vec<(Worley, f64)> = (0..self.config.octaves.max(1))
+            .map(|octave| {
+                let worley = Worley::new(base_seed + octave as u32)
+                    .set_return_type(ReturnType::Distance)
+                    .set_frequency(2f64.powi(octave as i32));
+                (worley, 0.5f64.powi(octave as i32))
+            })
+            .collect();
+        let total: f64 = layers.iter().map(|(_, amplitude)| amplitude).sum();
+
+        sample_grid(rows, cols, self.config.frequency, |p| {
+            layers
+                .iter()
+                .map(|(worley, amplitude)| worley.get(p) * amplitude)
+                .sum::<f64>()
+                / total
+        })
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+/// Overlays the TUI can draw over the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatLayer {
+    /// Ticks robots spent on each cell.
+    Visits,
+    /// Resources picked up on each cell.
+    Collections,
+    /// Ticks robots stood on each cell when they should have moved.
+    Blocked,
+    /// Ticks since each cell was last scanned.
+    Age,
+}
+
+impl HeatLayer {
+    pub const ALL: [HeatLayer; 4] = [
+        HeatLayer::Visits,
+        HeatLayer::Collections,
+        HeatLayer::Blocked,
+        HeatLayer::Age,
+    ];
+
+    /// The layer after `layer`, `None` after the last one and back again.
+    pub fn cycle(layer: Option<HeatLayer>) -> Option<HeatLayer> {
+        match layer {
+            None => Some(Self::ALL[0]),
+            Some(layer) => {
+                let idx = Self::ALL.iter().position(|&l| l == layer)?;
+                Self::ALL.get(idx + 1).copied()
+            }
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HeatLayer::Visits => "visits",
+            HeatLayer::Collections => "collections",
+            HeatLayer::Blocked => "blocked",
+            HeatLayer::Age => "observation age",
+        }
+    }
+}
+
+/// Per-cell statistics gathered over the whole run.
+#[derive(Debug, Clone, Default)]
+pub struct Heatmaps {
+    pub visits: HashMap<(usize, usize), u32>,
+    pub collections: HashMap<(usize, usize), u32>,
+    pub blocked: HashMap<(usize, usize), u32>,
+    /// Tick each cell was last scanned.
+    pub observed: HashMap<(usize, usize), u64>,
+    /// Latest tick recorded.
+    pub tick: u64,
+}
+
+impl Heatmaps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_visit(&mut self, cell: (usize, usize)) {
+        *self.visits.entry(cell).or_insert(0) += 1;
+    }
+
+    pub fn record_collection(&mut self, cell: (usize, usize)) {
+        *self.collections.entry(cell).or_insert(0) += 1;
+    }
+
+    pub fn record_blocked(&mut self, cell: (usize, usize)) {
+        *self.blocked.entry(cell).or_insert(0) += 1;
+    }
+
+    pub fn record_observed(&mut self, cells: impl IntoIterator<Item = (usize, usize)>, tick: u64) {
+        self.tick = self.tick.max(tick);
+        for cell in cells {
+            self.observed.insert(cell, tick);
+        }
+    }
+
+    /// Heat of every cell with data on `layer`, from 0 (coldest) to 1.
+    /// Counts are log scaled so a few busy cells don't wash out the rest.
+    pub fn intensities(&self, layer: HeatLayer) -> HashMap<(usize, usize), f64> {
+        let counts = match layer {
+            HeatLayer::Visits => &self.visits,
+            HeatLayer::Collections => &self.collections,
+            HeatLayer::Blocked => &self.blocked,
+            HeatLayer::Age => {
+                let ages: HashMap<_, _> = self
+                    .observed
+                    .iter()
+                    .map(|(&cell, &tick)| (cell, self.tick.saturating_sub(tick)))
+                    .collect();
+                let oldest = ages.values().copied().max().unwrap_or(0).max(1) as f64;
+                return ages
+                    .into_iter()
+                    .map(|(cell, age)| (cell, age as f64 / oldest))
+                    .collect();
+            }
+        };
+        let max = counts.values().copied().max().unwrap_or(0) as f64;
+        counts
+            .iter()
+            .map(|(&cell, &count)| (cell, (count as f64).ln_1p() / max.ln_1p()))
+            .collect()
+    }
+}
@@ -0,0 +1,540 @@
+use crate::ascii::{glyph_tile, tile_glyph};
+use crate::economy::{BuildItem, Stocks};
+use crate::map::{MapDiff, Tile};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const LOG_FILE: &str = "journal.log";
+const CHECKPOINT_FILE: &str = "checkpoint.txt";
+const DEFAULT_COMPACT_EVERY: usize = 256;
+
+type CellChange = ((usize, usize), Option<Tile>, Tile);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalEntry {
+    /// Cells changed by merging the reports of `tick` into `version`.
+    Merge {
+        version: u64,
+        tick: u64,
+        diff: MapDiff,
+    },
+    Deposit {
+        energy: u32,
+        mineral: u32,
+    },
+    /// Stocks changed from the console, clamped like `Stocks::adjust`.
+    Adjust {
+        energy: i64,
+        mineral: i64,
+    },
+    /// An item was paid for and entered the build queue.
+    Queue {
+        item: BuildItem,
+        energy: u32,
+        mineral: u32,
+    },
+    /// The oldest queued `item` was finished, as robot `robot_id` for robots.
+    Built {
+        item: BuildItem,
+        robot_id: Option<usize>,
+    },
+    /// A part was taken from stock and fitted to a docked robot.
+    Fitted {
+        item: BuildItem,
+        robot_id: usize,
+    },
+    /// A part was taken off robot `robot_id` and put back in stock.
+    Removed {
+        item: BuildItem,
+        robot_id: usize,
+    },
+    /// A robot was launched from the console. It is not launched again on a
+    /// restart, but its id stays taken.
+    Spawned {
+        robot_id: usize,
+    },
+}
+
+/// Everything the station must remember across a restart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StationState {
+    pub master_map: HashMap<(usize, usize), Tile>,
+    pub energy_stock: u32,
+    pub mineral_stock: u32,
+    pub next_robot_id: usize,
+    pub map_version: u64,
+    /// Paid items still waiting to be built, oldest first.
+    pub queue: Vec<BuildItem>,
+    /// Finished modules and upgrades not fitted to a robot yet.
+    pub parts: Vec<BuildItem>,
+    /// Outposts queued so far, counted against the station's allowance.
+    pub outposts_queued: usize,
+}
+
+impl Default for StationState {
+    fn default() -> Self {
+        Self {
+            master_map: HashMap::new(),
+            energy_stock: 0,
+            mineral_stock: 0,
+            // robots 1 and 2 start the mission
+            next_robot_id: 3,
+            map_version: 0,
+            queue: Vec::new(),
+            parts: Vec::new(),
+            outposts_queued: 0,
+        }
+    }
+}
+
+impl StationState {
+    pub fn apply(&mut self, entry: &JournalEntry) {
+        match entry {
+            JournalEntry::Merge { version, diff, .. } => {
+                for &(pos, _, after) in &diff.0 {
+                    self.master_map.insert(pos, after);
+                }
+                self.map_version = *version;
+            }
+            JournalEntry::Deposit { energy, mineral } => {
+                self.energy_stock += energy;
+                self.mineral_stock += mineral;
+            }
+            JournalEntry::Adjust { energy, mineral } => {
+                let mut stocks = Stocks {
+                    energy: self.energy_stock,
+                    mineral: self.mineral_stock,
+                };
+                stocks.adjust(*energy, *mineral);
+                self.energy_stock = stocks.energy;
+                self.mineral_stock = stocks.mineral;
+            }
+            JournalEntry::Queue {
+                item,
+                energy,
+                mineral,
+            } => {
+                self.energy_stock = self.energy_stock.saturating_sub(*energy);
+                self.mineral_stock = self.mineral_stock.saturating_sub(*mineral);
+                if *item == BuildItem::Outpost {
+                    self.outposts_queued += 1;
+                }
+                self.queue.push(item.clone());
+            }
+            JournalEntry::Built { item, robot_id } => {
+                if let Some(idx) = self.queue.iter().position(|queued| queued == item) {
+                    self.queue.remove(idx);
+                }
+                match robot_id {
+                    Some(id) => self.next_robot_id = self.next_robot_id.max(id + 1),
+                    None if *item == BuildItem::Outpost => {}
+                    None => self.parts.push(item.clone()),
+                }
+            }
+            JournalEntry::Fitted { item, .. } => {
+                if let Some(idx) = self.parts.iter().position(|part| part == item) {
+                    self.parts.remove(idx);
+                }
+            }
+            JournalEntry::Removed { item, .. } => self.parts.push(item.clone()),
+            JournalEntry::Spawned { robot_id } => {
+                self.next_robot_id = self.next_robot_id.max(robot_id + 1);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    Corrupt {
+        file: &'static str,
+        /// 1-based line of the unreadable record.
+        line: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalError::Io(err) => write!(f, "journal: {}", err),
+            JournalError::Corrupt { file, line, reason } => {
+                write!(f, "journal: {} line {}: {}", file, line, reason)
+            }
+        }
+    }
+}
+
+impl Error for JournalError {}
+
+impl From<io::Error> for JournalError {
+    fn from(err: io::Error) -> Self {
+        JournalError::Io(err)
+    }
+}
+
+impl fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalEntry::Merge {
+                version,
+                tick,
+                diff,
+            } => {
+                write!(f, "merge {} {}", version, tick)?;
+                for &((r, c), before, after) in &diff.0 {
+                    let before = before.map_or('-', tile_glyph);
+                    write!(f, " {},{},{},{}", r, c, before, tile_glyph(after))?;
+                }
+                Ok(())
+            }
+            JournalEntry::Deposit { energy, mineral } => {
+                write!(f, "deposit {} {}", energy, mineral)
+            }
+            JournalEntry::Adjust { energy, mineral } => {
+                write!(f, "adjust {} {}", energy, mineral)
+            }
+            JournalEntry::Queue {
+                item,
+                energy,
+                mineral,
+            } => write!(f, "queue {} {} {}", item, energy, mineral),
+            JournalEntry::Built { item, robot_id } => match robot_id {
+                Some(id) => write!(f, "built {} {}", item, id),
+                None => write!(f, "built {} -", item),
+            },
+            JournalEntry::Fitted { item, robot_id } => {
+                write!(f, "fitted {} {}", item, robot_id)
+            }
+            JournalEntry::Removed { item, robot_id } => {
+                write!(f, "removed {} {}", item, robot_id)
+            }
+            JournalEntry::Spawned { robot_id } => write!(f, "spawned {}", robot_id),
+        }
+    }
+}
+
+fn number<T: std::str::FromStr>(field: Option<&str>, what: &str) -> Result<T, String> {
+    let field = field.ok_or_else(|| format!("missing {}", what))?;
+    field
+        .parse()
+        .map_err(|_| format!("invalid {} {:?}", what, field))
+}
+
+fn tile(glyph: &str) -> Result<Tile, String> {
+    let mut chars = glyph.chars();
+    match (chars.next().and_then(glyph_tile), chars.next()) {
+        (Some(tile), None) => Ok(tile),
+        _ => Err(format!("invalid tile {:?}", glyph)),
+    }
+}
+
+fn build_item(field: Option<&str>) -> Result<BuildItem, String> {
+    field.ok_or("missing build item")?.parse()
+}
+
+fn parse_cell(field: &str) -> Result<CellChange, String> {
+    let parts: Vec<&str> = field.split(',').collect();
+    let [r, c, before, after] = parts[..] else {
+        return Err(format!("invalid cell {:?}", field));
+    };
+    let before = if before == "-" {
+        None
+    } else {
+        Some(tile(before)?)
+    };
+    Ok((
+        (number(Some(r), "row")?, number(Some(c), "column")?),
+        before,
+        tile(after)?,
+    ))
+}
+
+impl JournalEntry {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("merge") => {
+                let version = number(fields.next(), "version")?;
+                let tick = number(fields.next(), "tick")?;
+                let diff = fields.map(parse_cell).collect::<Result<_, _>>()?;
+                Ok(JournalEntry::Merge {
+                    version,
+                    tick,
+                    diff: MapDiff(diff),
+                })
+            }
+            Some("deposit") => Ok(JournalEntry::Deposit {
+                energy: number(fields.next(), "energy")?,
+                mineral: number(fields.next(), "mineral")?,
+            }),
+            Some("adjust") => Ok(JournalEntry::Adjust {
+                energy: number(fields.next(), "energy")?,
+                mineral: number(fields.next(), "mineral")?,
+            }),
+            Some("queue") => Ok(JournalEntry::Queue {
+                item: build_item(fields.next())?,
+                energy: number(fields.next(), "energy")?,
+                mineral: number(fields.next(), "mineral")?,
+            }),
+            Some("built") => {
+                let item = build_item(fields.next())?;
+                let robot_id = match fields.next() {
+                    Some("-") => None,
+                    field => Some(number(field, "robot id")?),
+                };
+                Ok(JournalEntry::Built { item, robot_id })
+            }
+            Some("fitted") => Ok(JournalEntry::Fitted {
+                item: build_item(fields.next())?,
+                robot_id: number(fields.next(), "robot id")?,
+            }),
+            Some("removed") => Ok(JournalEntry::Removed {
+                item: build_item(fields.next())?,
+                robot_id: number(fields.next(), "robot id")?,
+            }),
+            Some("spawned") => Ok(JournalEntry::Spawned {
+                robot_id: number(fields.next(), "robot id")?,
+            }),
+            Some(other) => Err(format!("unknown record {:?}", other)),
+            None => Err("empty record".to_string()),
+        }
+    }
+}
+
+/// Log line: the entry's sequence number, then the entry.
+fn parse_record(line: &str) -> Result<(u64, JournalEntry), String> {
+    let (seq, entry) = line.split_once(' ').unwrap_or((line, ""));
+    Ok((
+        number(Some(seq), "sequence number")?,
+        JournalEntry::parse(entry)?,
+    ))
+}
+
+/// Append-only log of everything the station merged, spent or built, compacted
+/// into a checkpoint every `compact_every` entries.
+///
+/// Layout inside the journal directory: `checkpoint.txt` holds the state at
+/// the last compaction, `journal.log` one numbered entry per line since then.
+/// The checkpoint records the last sequence number it covers, so entries left
+/// in the log by a crash during compaction are not applied twice.
+pub struct Journal {
+    dir: PathBuf,
+    log: File,
+    /// Sequence number of the last entry written.
+    seq: u64,
+    /// Sequence number of the last entry folded into the checkpoint.
+    checkpoint_seq: u64,
+    entries_since_checkpoint: usize,
+    compact_every: usize,
+}
+
+impl Journal {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, JournalError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        // drop a record torn by a crash so new entries start on a fresh line
+        let log_path = dir.join(LOG_FILE);
+        let bytes = match fs::read(&log_path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        let complete = bytes
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |idx| idx + 1);
+        let text = std::str::from_utf8(&bytes[..complete]).map_err(|err| {
+            let line = bytes[..err.valid_up_to()]
+                .iter()
+                .filter(|&&b| b == b'\n')
+                .count();
+            JournalError::Corrupt {
+                file: LOG_FILE,
+                line: line + 1,
+                reason: "invalid UTF-8".to_string(),
+            }
+        })?;
+        let log = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)?;
+        log.set_len(complete as u64)?;
+
+        let mut journal = Self {
+            dir,
+            log,
+            seq: 0,
+            checkpoint_seq: 0,
+            entries_since_checkpoint: 0,
+            compact_every: DEFAULT_COMPACT_EVERY,
+        };
+        journal.checkpoint_seq = journal.checkpoint()?.1;
+        let logged: Vec<u64> = text
+            .lines()
+            .filter_map(|line| parse_record(line).ok())
+            .map(|(seq, _)| seq)
+            .collect();
+        journal.seq = logged
+            .iter()
+            .copied()
+            .fold(journal.checkpoint_seq, u64::max);
+        journal.entries_since_checkpoint = logged
+            .iter()
+            .filter(|&&seq| seq > journal.checkpoint_seq)
+            .count();
+        Ok(journal)
+    }
+
+    pub fn with_compact_every(mut self, compact_every: usize) -> Self {
+        self.compact_every = compact_every.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Entries written since the last checkpoint, oldest first.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>, JournalError> {
+        let text = fs::read_to_string(self.dir.join(LOG_FILE))?;
+        let mut entries = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let (seq, entry) = parse_record(line).map_err(|reason| JournalError::Corrupt {
+                file: LOG_FILE,
+                line: idx + 1,
+                reason,
+            })?;
+            if seq > self.checkpoint_seq {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Rebuilds the station state from the checkpoint and the log.
+    pub fn load(&self) -> Result<StationState, JournalError> {
+        let (mut state, _) = self.checkpoint()?;
+        for entry in self.entries()? {
+            state.apply(&entry);
+        }
+        Ok(state)
+    }
+
+    /// Writes the entry through to disk before the caller publishes it.
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<(), JournalError> {
+        let seq = self.seq + 1;
+        self.log
+            .write_all(format!("{} {}\n", seq, entry).as_bytes())?;
+        self.log.flush()?;
+        self.log.sync_data()?;
+        self.seq = seq;
+        self.entries_since_checkpoint += 1;
+        Ok(())
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.entries_since_checkpoint >= self.compact_every
+    }
+
+    /// Replaces the checkpoint with `state`, which must include every entry
+    /// appended so far, and empties the log.
+    pub fn compact(&mut self, state: &StationState) -> Result<(), JournalError> {
+        let mut cells: Vec<_> = state.master_map.iter().collect();
+        cells.sort_by_key(|(pos, _)| **pos);
+
+        let mut text = format!(
+            "checkpoint {} {} {} {} {} {}\n",
+            state.map_version,
+            state.energy_stock,
+            state.mineral_stock,
+            state.next_robot_id,
+            state.outposts_queued,
+            self.seq
+        );
+        for item in &state.queue {
+            text.push_str(&format!("queue {}\n", item));
+        }
+        for item in &state.parts {
+            text.push_str(&format!("part {}\n", item));
+        }
+        for (&(r, c), &tile) in cells {
+            text.push_str(&format!("{},{},{}\n", r, c, tile_glyph(tile)));
+        }
+
+        let tmp = self.dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        let mut file = File::create(&tmp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, self.dir.join(CHECKPOINT_FILE))?;
+        // a crash before the truncation leaves entries the checkpoint covers
+        self.checkpoint_seq = self.seq;
+        self.log.set_len(0)?;
+        self.entries_since_checkpoint = 0;
+        Ok(())
+    }
+
+    /// The checkpointed state and the last sequence number it covers.
+    fn checkpoint(&self) -> Result<(StationState, u64), JournalError> {
+        let text = match fs::read_to_string(self.dir.join(CHECKPOINT_FILE)) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok((StationState::default(), 0))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let corrupt = |line: usize, reason: String| JournalError::Corrupt {
+            file: CHECKPOINT_FILE,
+            line,
+            reason,
+        };
+        let mut lines = text.lines();
+        let mut header = lines.next().unwrap_or_default().split_whitespace();
+        if header.next() != Some("checkpoint") {
+            return Err(corrupt(1, "missing checkpoint header".to_string()));
+        }
+        let mut state = StationState {
+            map_version: number(header.next(), "version").map_err(|e| corrupt(1, e))?,
+            energy_stock: number(header.next(), "energy").map_err(|e| corrupt(1, e))?,
+            mineral_stock: number(header.next(), "mineral").map_err(|e| corrupt(1, e))?,
+            next_robot_id: number(header.next(), "robot id").map_err(|e| corrupt(1, e))?,
+            outposts_queued: number(header.next(), "outpost count").map_err(|e| corrupt(1, e))?,
+            master_map: HashMap::new(),
+            queue: Vec::new(),
+            parts: Vec::new(),
+        };
+        let seq = number(header.next(), "sequence number").map_err(|e| corrupt(1, e))?;
+
+        for (idx, line) in lines.enumerate() {
+            if let Some((kind, rest)) = line.split_once(' ') {
+                let item = || build_item(Some(rest)).map_err(|e| corrupt(idx + 2, e));
+                match kind {
+                    "queue" => state.queue.push(item()?),
+                    "part" => state.parts.push(item()?),
+                    _ => return Err(corrupt(idx + 2, format!("unknown record {:?}", kind))),
+                }
+                continue;
+            }
+            let cell = || -> Result<((usize, usize), Tile), String> {
+                let parts: Vec<&str> = line.split(',').collect();
+                let [r, c, glyph] = parts[..] else {
+                    return Err(format!("invalid cell {:?}", line));
+                };
+                Ok((
+                    (number(Some(r), "row")?, number(Some(c), "column")?),
+                    tile(glyph)?,
+                ))
+            };
+            let (pos, tile) = cell().map_err(|e| corrupt(idx + 2, e))?;
+            state.master_map.insert(pos, tile);
+        }
+        Ok((state, seq))
+    }
+}
@@ -0,0 +1,72 @@
+use crate::map::{Map, Tile};
+use std::collections::{HashMap, HashSet};
+
+/// Tiles as someone believes them to be.
+pub type Belief = HashMap<(usize, usize), Tile>;
+
+/// Map layers the TUI can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapLayer {
+    /// The real map, dimmed where some robot knows it.
+    #[default]
+    Truth,
+    /// The main station's master map.
+    Station,
+    /// The selected robot's known map.
+    Robot,
+    /// The real map, marking cells the station or selected robot has wrong.
+    Diff,
+    /// The real map with cells nobody has seen masked out.
+    Unexplored,
+}
+
+impl MapLayer {
+    pub const ALL: [MapLayer; 5] = [
+        MapLayer::Truth,
+        MapLayer::Station,
+        MapLayer::Robot,
+        MapLayer::Diff,
+        MapLayer::Unexplored,
+    ];
+
+    pub fn next(self) -> Self {
+        let idx = Self::ALL
+            .iter()
+            .position(|&layer| layer == self)
+            .unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MapLayer::Truth => "ground truth",
+            MapLayer::Station => "station",
+            MapLayer::Robot => "robot",
+            MapLayer::Diff => "diff",
+            MapLayer::Unexplored => "unexplored",
+        }
+    }
+}
+
+/// Cells `belief` has, but not as they really are.
+pub fn stale_cells(map: &Map, belief: &Belief) -> HashSet<(usize, usize)> {
+    belief
+        .iter()
+        .filter(|&(&(r, c), &tile)| r < map.grid.len() && c < map.cols && map.grid[r][c] != tile)
+        .map(|(&cell, _)| cell)
+        .collect()
+}
+
+/// Cells of `map` none of `beliefs` has.
+pub fn unexplored<'a>(
+    map: &Map,
+    beliefs: impl IntoIterator<Item = &'a Belief>,
+) -> HashSet<(usize, usize)> {
+    let mut cells: HashSet<(usize, usize)> = (0..map.grid.len())
+        .flat_map(|r| (0..map.cols).map(move |c| (r, c)))
+        .collect();
+    for belief in beliefs {
+        cells.retain(|cell| !belief.contains_key(cell));
+    }
+    cells
+}
@@ -1,6 +1,24 @@
+pub mod analysis;
+pub mod ascii;
+pub mod biome;
+pub mod console;
+pub mod dynamics;
+pub mod economy;
+pub mod generator;
+pub mod heatmap;
+pub mod journal;
+pub mod knowledge;
+pub mod log;
 pub mod map;
+pub mod network;
 pub mod robot;
+pub mod scanner;
 pub mod station;
+pub mod timeline;
+pub mod traffic;
 pub mod utils;
+pub mod viewport;
+pub mod visibility;
+pub mod watchdog;
 
 pub use map::{Map, MapDiff, Tile};
@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Records kept for the TUI; the file mirror keeps everything.
+pub const LOG_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub const ALL: [Level; 4] = [Level::Debug, Level::Info, Level::Warn, Level::Error];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    pub fn next(self) -> Level {
+        let idx = Self::ALL.iter().position(|&l| l == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn parse(name: &str) -> Option<Level> {
+        Self::ALL.into_iter().find(|level| level.name() == name)
+    }
+}
+
+/// Who wrote a record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    Station(usize),
+    Coordinator,
+    Robot(usize),
+    /// The TUI itself: startup and console replies.
+    App,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Station(id) => write!(f, "station {}", id),
+            Source::Coordinator => write!(f, "coordinator"),
+            Source::Robot(id) => write!(f, "robot {}", id),
+            Source::App => write!(f, "app"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    General,
+    /// Merging reports into the master map.
+    Merge,
+    Dock,
+    /// Stocks, builds and retrofits.
+    Economy,
+    Journal,
+    /// Outposts and links between stations.
+    Network,
+    /// World dynamics: regrowth, storms, collapses.
+    World,
+    Watchdog,
+    Movement,
+    Collection,
+    Console,
+}
+
+impl Category {
+    pub const ALL: [Category; 11] = [
+        Category::General,
+        Category::Merge,
+        Category::Dock,
+        Category::Economy,
+        Category::Journal,
+        Category::Network,
+        Category::World,
+        Category::Watchdog,
+        Category::Movement,
+        Category::Collection,
+        Category::Console,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::General => "general",
+            Category::Merge => "merge",
+            Category::Dock => "dock",
+            Category::Economy => "economy",
+            Category::Journal => "journal",
+            Category::Network => "network",
+            Category::World => "world",
+            Category::Watchdog => "watchdog",
+            Category::Movement => "movement",
+            Category::Collection => "collection",
+            Category::Console => "console",
+        }
+    }
+
+    /// The category after `category`, `None` after the last one and back again.
+    pub fn cycle(category: Option<Category>) -> Option<Category> {
+        match category {
+            None => Some(Self::ALL[0]),
+            Some(category) => {
+                let idx = Self::ALL.iter().position(|&c| c == category)?;
+                Self::ALL.get(idx + 1).copied()
+            }
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Category> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogRecord {
+    pub tick: u64,
+    pub level: Level,
+    pub source: Source,
+    pub category: Category,
+    /// Robot the record is about, whoever wrote it.
+    pub robot: Option<usize>,
+    pub message: String,
+}
+
+impl LogRecord {
+    pub fn new(
+        tick: u64,
+        level: Level,
+        source: Source,
+        category: Category,
+        message: impl Into<String>,
+    ) -> Self {
+        let robot = match source {
+            Source::Robot(id) => Some(id),
+            _ => None,
+        };
+        Self {
+            tick,
+            level,
+            source,
+            category,
+            robot,
+            message: message.into(),
+        }
+    }
+
+    pub fn about(mut self, robot: usize) -> Self {
+        self.robot = Some(robot);
+        self
+    }
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>4} {:<5} {} [{}] {}",
+            self.tick,
+            self.level.name(),
+            self.source,
+            self.category.name(),
+            self.message
+        )
+    }
+}
+
+/// Which records the log panel shows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    pub min_level: Level,
+    pub robot: Option<usize>,
+    pub category: Option<Category>,
+    /// Case-insensitive text the message must contain; empty for any.
+    pub search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            min_level: Level::Info,
+            robot: None,
+            category: None,
+            search: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        record.level >= self.min_level
+            && self.robot.is_none_or(|id| record.robot == Some(id))
+            && self.category.is_none_or(|c| record.category == c)
+            && (self.search.is_empty()
+                || record
+                    .message
+                    .to_lowercase()
+                    .contains(&self.search.to_lowercase()))
+    }
+
+    /// Short description for the panel title.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!(">={}", self.min_level.name())];
+        if let Some(id) = self.robot {
+            parts.push(format!("robot {}", id));
+        }
+        if let Some(category) = self.category {
+            parts.push(category.name().to_string());
+        }
+        if !self.search.is_empty() {
+            parts.push(format!("/{}", self.search));
+        }
+        parts.join(" ")
+    }
+}
+
+/// The last `LOG_CAPACITY` records, optionally mirrored to a file.
+pub struct LogBook {
+    records: VecDeque<LogRecord>,
+    mirror: Option<File>,
+}
+
+impl Default for LogBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogBook {
+    pub fn new() -> Self {
+        Self {
+            records: VecDeque::new(),
+            mirror: None,
+        }
+    }
+
+    /// Appends every record to the file at `path` as well.
+    pub fn with_mirror(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        self.mirror = Some(file);
+        Ok(self)
+    }
+
+    pub fn push(&mut self, record: LogRecord) {
+        // a failing mirror is dropped rather than stalling the TUI
+        if let Some(file) = self.mirror.as_mut() {
+            if writeln!(file, "{}", record).is_err() {
+                self.mirror = None;
+            }
+        }
+        if self.records.len() >= LOG_CAPACITY {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    pub fn records(&self) -> &VecDeque<LogRecord> {
+        &self.records
+    }
+
+    /// Records passing `filter`, oldest first.
+    pub fn filtered<'a>(&'a self, filter: &'a LogFilter) -> impl Iterator<Item = &'a LogRecord> {
+        self.records.iter().filter(|record| filter.matches(record))
+    }
+}
+
+/// Scroll position of the log panel. While following it sticks to the
+/// newest records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogScroll {
+    pub follow: bool,
+    /// First line shown when not following.
+    pub top: usize,
+}
+
+impl Default for LogScroll {
+    fn default() -> Self {
+        Self {
+            follow: true,
+            top: 0,
+        }
+    }
+}
+
+impl LogScroll {
+    /// First of `total` lines to show in a panel `height` lines high.
+    pub fn first_line(&self, total: usize, height: usize) -> usize {
+        let bottom = total.saturating_sub(height);
+        if self.follow {
+            bottom
+        } else {
+            self.top.min(bottom)
+        }
+    }
+
+    /// Scrolls by `delta` lines; reaching the bottom follows again.
+    pub fn scroll(&mut self, delta: isize, total: usize, height: usize) {
+        let bottom = total.saturating_sub(height);
+        self.top = self
+            .first_line(total, height)
+            .saturating_add_signed(delta)
+            .min(bottom);
+        self.follow = self.top == bottom;
+    }
+}
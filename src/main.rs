@@ -1,10 +1,8 @@
 mod app;
 mod coordinator;
-mod map;
-mod robot;
-mod station;
 mod ui;
-mod utils;
+
+use rust_project::{ascii, economy, heatmap, journal, log, network, station, timeline};
 
 use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
@@ -16,14 +14,94 @@ use std::time::Duration;
 use std::{error::Error, io};
 
 fn main() -> Result<(), Box<dyn Error>> {
-    enable_raw_mode()?;
+    let mut layout_path = None;
+    let mut journal_dir = None;
+    let mut log_file = None;
+    let mut timeline_file = None;
+    let mut replay_file = None;
+    let mut seed = None;
+    let mut policy = None;
+    let mut outposts = String::from("0");
+    let mut dock_radius = String::from("1");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--journal" => journal_dir = args.next(),
+            "--log-file" => log_file = args.next(),
+            "--timeline-file" => timeline_file = args.next(),
+            "--replay" => replay_file = args.next(),
+            "--seed" => seed = args.next(),
+            "--policy" => policy = Some(args.next().unwrap_or_default()),
+            "--outposts" => outposts = args.next().unwrap_or_default(),
+            "--dock-radius" => dock_radius = args.next().unwrap_or_default(),
+            _ => layout_path = Some(arg),
+        }
+    }
+
+    let layout = match layout_path {
+        Some(path) => Some(ascii::AsciiMap::load(path)?),
+        None => None,
+    };
 
     let (tx_report, rx_report) = mpsc::channel();
     let (tx_cmd, rx_cmd) = mpsc::channel();
+    let (tx_request, rx_request) = mpsc::channel();
+
+    // without a policy robots are built at once, as the station always did
+    let station_economy = match policy {
+        Some(name) => economy::Economy::new(
+            economy::CostTable::default(),
+            economy::policy_by_name(&name)
+                .ok_or_else(|| format!("unknown spending policy {:?}", name))?,
+        ),
+        None => economy::Economy::instant(),
+    };
+
+    let outposts: usize = outposts
+        .parse()
+        .map_err(|_| format!("invalid outpost count {:?}", outposts))?;
+    let seed = match seed {
+        Some(seed) => Some(
+            seed.parse::<u64>()
+                .map_err(|_| format!("invalid seed {:?}", seed))?,
+        ),
+        None => None,
+    };
+    let dock_radius: usize = dock_radius
+        .parse()
+        .map_err(|_| format!("invalid docking zone radius {:?}", dock_radius))?;
+    let home = layout
+        .as_ref()
+        .and_then(|layout| layout.station)
+        .unwrap_or((0, 0));
+
+    let mut station = station::Station::detached(tx_cmd.clone())
+        .with_position(home)
+        .with_dock_radius(dock_radius)
+        .with_lateness(3)
+        .with_economy(station_economy.with_outposts(outposts));
+    if let Some(dir) = journal_dir {
+        station = station.with_journal(journal::Journal::open(dir)?)?;
+    }
+    let mut network =
+        network::StationNetwork::new(rx_report, tx_cmd, station).with_requests(rx_request);
+    let mirror = match log_file {
+        Some(path) => Some(log::LogBook::new().with_mirror(path)?),
+        None => None,
+    };
+    let recording = match timeline_file {
+        Some(path) => Some(timeline::Timeline::new().with_recording(path)?),
+        None => None,
+    };
+    let replay = match replay_file {
+        Some(path) => Some(timeline::Timeline::load(path)?),
+        None => None,
+    };
+
+    enable_raw_mode()?;
 
     thread::spawn(move || {
-        let mut station = station::Station::new(rx_report, tx_cmd);
-        station.run();
+        network.run();
     });
 
     let stdout = io::stdout();
@@ -31,24 +109,80 @@ fn main() -> Result<(), Box<dyn Error>> {
     let mut terminal = Terminal::new(backend)?;
     terminal.clear()?;
 
-    let mut app = app::App::new(tx_report.clone(), rx_cmd);
+    let mut app = app::App::new(tx_report.clone(), rx_cmd, tx_request, layout, seed);
+    if let Some(mut mirror) = mirror {
+        for record in app.logs.records() {
+            mirror.push(record.clone());
+        }
+        app.logs = mirror;
+    }
+    if let Some(recording) = recording {
+        app.timeline = recording;
+    }
+    if let Some(samples) = replay {
+        app.replay(samples);
+    }
 
     loop {
+        app.resize_view(ui::map_view_size(terminal.size()?));
+        app.log_height = ui::log_view_height(terminal.size()?);
         if event::poll(Duration::from_millis(20))? {
             if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Up => app.robots_scroll = app.robots_scroll.saturating_sub(1),
-                    KeyCode::Down => app.robots_scroll = app.robots_scroll.saturating_add(1),
-                    KeyCode::PageUp => app.logs_scroll = app.logs_scroll.saturating_sub(3),
-                    KeyCode::PageDown => app.logs_scroll = app.logs_scroll.saturating_add(3),
-                    _ => {}
+                if let Some(input) = app.console.as_mut() {
+                    match key.code {
+                        KeyCode::Enter => app.submit_console(),
+                        KeyCode::Esc => app.console = None,
+                        KeyCode::Backspace => {
+                            input.pop();
+                        }
+                        KeyCode::Char(ch) => input.push(ch),
+                        _ => {}
+                    }
+                } else {
+                    match key.code {
+                        KeyCode::Char(':') => app.console = Some(String::new()),
+                        KeyCode::Char('q') => break,
+                        KeyCode::Up if app.cursor.is_some() => app.move_cursor(-1, 0),
+                        KeyCode::Down if app.cursor.is_some() => app.move_cursor(1, 0),
+                        KeyCode::Left => app.move_cursor(0, -1),
+                        KeyCode::Right => app.move_cursor(0, 1),
+                        KeyCode::Enter => app.select_at_cursor(),
+                        KeyCode::Char('c') => app.toggle_cursor(),
+                        KeyCode::Char('i') => app.show_detail = !app.show_detail,
+                        KeyCode::Esc => {
+                            app.cursor = None;
+                            app.show_detail = false;
+                        }
+                        KeyCode::Up => app.robots_scroll = app.robots_scroll.saturating_sub(1),
+                        KeyCode::Down => app.robots_scroll = app.robots_scroll.saturating_add(1),
+                        KeyCode::PageUp => app.scroll_logs(-3),
+                        KeyCode::PageDown => app.scroll_logs(3),
+                        KeyCode::Char('L') => app.cycle_log_level(),
+                        KeyCode::Char('R') => app.cycle_log_robot(),
+                        KeyCode::Char('C') => app.cycle_log_category(),
+                        KeyCode::Char('F') => app.log_scroll.follow = !app.log_scroll.follow,
+                        KeyCode::Char('/') => app.console = Some("log search ".to_string()),
+                        KeyCode::Tab => app.select_next_robot(),
+                        KeyCode::Char('v') => app.show_fov = !app.show_fov,
+                        KeyCode::Char('b') => app.layer = app.layer.next(),
+                        KeyCode::Char('g') => {
+                            app.heat_layer = heatmap::HeatLayer::cycle(app.heat_layer)
+                        }
+                        KeyCode::Char('h' | 'a') => app.pan(0, -1),
+                        KeyCode::Char('j' | 's') => app.pan(1, 0),
+                        KeyCode::Char('k' | 'w') => app.pan(-1, 0),
+                        KeyCode::Char('l' | 'd') => app.pan(0, 1),
+                        KeyCode::Char('+' | '=') => app.zoom(false),
+                        KeyCode::Char('-') => app.zoom(true),
+                        KeyCode::Char('f') => app.toggle_follow(),
+                        KeyCode::Char('t') => app.show_charts = !app.show_charts,
+                        _ => {}
+                    }
                 }
             }
         }
 
-        let done = app.tick();
-        if done {
+        if app.tick_due() && app.tick() {
             break;
         }
 
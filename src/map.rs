@@ -1,6 +1,4 @@
-use noise::{NoiseFn, Perlin};
-use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use crate::generator::{ClassicPerlin, MapGenerator};
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -30,6 +28,23 @@ impl MapDiff {
         self.0.extend(other.0);
     }
 
+    /// Collapses entries touching the same cell into one, keeping the first
+    /// "before" and the last "after" value.
+    pub fn compact(self) -> MapDiff {
+        let mut index: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut entries: Vec<((usize, usize), Option<Tile>, Tile)> = Vec::new();
+        for (pos, before, after) in self.0 {
+            match index.get(&pos) {
+                Some(&i) => entries[i].2 = after,
+                None => {
+                    index.insert(pos, entries.len());
+                    entries.push((pos, before, after));
+                }
+            }
+        }
+        MapDiff(entries)
+    }
+
     pub fn apply_to_known_map(&self, known_map: &mut HashMap<(usize, usize), Tile>) {
         for &((r, c), _before, after) in &self.0 {
             known_map.insert((r, c), after);
@@ -37,7 +52,7 @@ impl MapDiff {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Map {
     pub grid: Vec<Vec<Tile>>,
     pub cols: usize,
@@ -45,46 +60,17 @@ pub struct Map {
 
 impl Map {
     pub fn generate(rows: usize, cols: usize, seed: u64) -> Self {
-        let perlin = Perlin::default();
-        let mut rng = StdRng::seed_from_u64(seed);
-        let mut grid = vec![vec![Tile::Empty; cols]; rows];
-
-        for row in 0..rows {
-            for col in 0..cols {
-                let x = row as f64 / 10.0;
-                let y = col as f64 / 10.0;
-                let mut val = perlin.get([x, y, seed as f64]);
-
-                val = (val + 1.0) / 2.0;
-
-                grid[row][col] = if val > 0.7 {
-                    Tile::Obstacle
-                } else if val > 0.4 {
-                    if rng.gen_bool(0.5) {
-                        Tile::Mineral
-                    } else {
-                        Tile::Energy
-                    }
-                } else if val > 0.2 {
-                    if rng.gen_bool(0.3) {
-                        Tile::Science
-                    } else {
-                        Tile::Empty
-                    }
-                } else {
-                    Tile::Empty
-                };
-            }
-        }
-
-        Self { grid, cols }
+        ClassicPerlin::default().generate(rows, cols, seed)
     }
 
     pub fn generate_with_dynamic_seed(rows: usize, cols: usize) -> Self {
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        Self::generate(rows, cols, seed)
+        Self::generate(rows, cols, dynamic_seed())
     }
 }
+
+pub fn dynamic_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
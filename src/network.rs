@@ -0,0 +1,257 @@
+use crate::economy::{policy_by_name, Economy, Greedy};
+use crate::log::{Category, Level, LogRecord, Source};
+use crate::map::{MapDiff, Tile};
+use crate::station::{DockPhase, RobotReport, Station, StationCmd, StationRequest};
+use std::cmp::Reverse;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Minimum Manhattan distance between an outpost and any other station.
+pub const OUTPOST_SPACING: usize = 8;
+
+/// Every station on the map, fed from one report channel. Dock exchanges go
+/// to the station the robot docked at, everything else reaches all stations.
+/// Stations are linked: whatever one merges is relayed to the others.
+pub struct StationNetwork {
+    rx: Receiver<RobotReport>,
+    tx_cmd: Sender<StationCmd>,
+    stations: Vec<Station>,
+    /// Master version of each station already relayed to its peers.
+    relayed: Vec<u64>,
+    /// Command channels of the outposts, relayed to the app.
+    outposts: Vec<(usize, Receiver<StationCmd>)>,
+    /// Index of the station for each built outpost still without a site.
+    unplaced: Vec<usize>,
+    /// Operator requests, all handled by the main station.
+    requests: Option<Receiver<StationRequest>>,
+    /// Newest report tick, to date the network's own log records.
+    tick: u64,
+}
+
+impl StationNetwork {
+    pub fn new(rx: Receiver<RobotReport>, tx_cmd: Sender<StationCmd>, main: Station) -> Self {
+        let network = Self {
+            rx,
+            tx_cmd,
+            stations: Vec::new(),
+            relayed: Vec::new(),
+            outposts: Vec::new(),
+            unplaced: Vec::new(),
+            requests: None,
+            tick: 0,
+        };
+        network.with_station(main)
+    }
+
+    /// Adds a station that already has a position and its own command
+    /// channel, sharing robot ids with the first one.
+    pub fn with_station(mut self, station: Station) -> Self {
+        let station = match self.stations.first() {
+            Some(first) => station.with_robot_ids(first.robot_ids()),
+            None => station,
+        };
+        let _ = self.tx_cmd.send(StationCmd::StationOnline(station.site()));
+        self.relayed.push(station.map_version());
+        self.stations.push(station);
+        self
+    }
+
+    /// Takes operator requests from `requests`, checked before each report.
+    pub fn with_requests(mut self, requests: Receiver<StationRequest>) -> Self {
+        self.requests = Some(requests);
+        self
+    }
+
+    pub fn stations(&self) -> &[Station] {
+        &self.stations
+    }
+
+    pub fn station(&self, id: usize) -> Option<&Station> {
+        self.stations.iter().find(|station| station.id() == id)
+    }
+
+    pub fn run(&mut self) {
+        while let Ok(report) = self.rx.recv() {
+            self.handle_report(report);
+        }
+        for station in &mut self.stations {
+            station.flush();
+        }
+        self.sync_links();
+        self.relay_outpost_cmds();
+    }
+
+    pub fn handle_report(&mut self, report: RobotReport) {
+        self.handle_requests();
+        self.tick = self.tick.max(report.tick);
+        match report.dock {
+            DockPhase::None => {
+                for station in &mut self.stations {
+                    station.handle_report(report.clone());
+                }
+            }
+            DockPhase::Upload | DockPhase::Ack(_) => {
+                match self.stations.iter_mut().find(|s| s.id() == report.station) {
+                    Some(station) => station.handle_report(report),
+                    None => {
+                        let record = LogRecord::new(
+                            report.tick,
+                            Level::Warn,
+                            Source::Station(report.station),
+                            Category::Dock,
+                            format!(
+                                "Robot {} docked at unknown station {}",
+                                report.robot_id, report.station
+                            ),
+                        );
+                        let _ = self
+                            .tx_cmd
+                            .send(StationCmd::Log(record.about(report.robot_id)));
+                    }
+                }
+            }
+        }
+
+        self.place_outposts();
+        self.sync_links();
+        self.relay_outpost_cmds();
+    }
+
+    fn handle_requests(&mut self) {
+        let Some(requests) = &self.requests else {
+            return;
+        };
+        while let Ok(request) = requests.try_recv() {
+            if let Some(main) = self.stations.first_mut() {
+                main.handle_request(request);
+            }
+        }
+    }
+
+    /// Relays what each station merged since its last sync to every other
+    /// station. Changes that came in over the link are not echoed back.
+    fn sync_links(&mut self) {
+        for idx in 0..self.stations.len() {
+            let version = self.stations[idx].map_version();
+            if version == self.relayed[idx] {
+                continue;
+            }
+            let diff = self.stations[idx]
+                .delta_since(self.relayed[idx])
+                .unwrap_or_else(|| full_map(&self.stations[idx]));
+            self.relayed[idx] = version;
+
+            let from = self.stations[idx].id();
+            for peer in 0..self.stations.len() {
+                if peer == idx {
+                    continue;
+                }
+                let before = self.stations[peer].map_version();
+                self.stations[peer].apply_link(from, &diff);
+                if self.relayed[peer] == before {
+                    self.relayed[peer] = self.stations[peer].map_version();
+                }
+            }
+        }
+    }
+
+    fn place_outposts(&mut self) {
+        for idx in 0..self.stations.len() {
+            let built = self.stations[idx].take_outposts();
+            self.unplaced.extend(std::iter::repeat_n(idx, built));
+        }
+
+        let mut waiting = Vec::new();
+        for idx in std::mem::take(&mut self.unplaced) {
+            match self.outpost_site(idx) {
+                Some(site) => self.launch_outpost(idx, site),
+                None => waiting.push(idx),
+            }
+        }
+        self.unplaced = waiting;
+    }
+
+    /// Known empty cell of the building station's map farthest from every
+    /// station, at least `OUTPOST_SPACING` away from all of them.
+    fn outpost_site(&self, idx: usize) -> Option<(usize, usize)> {
+        let distance = |pos: (usize, usize)| {
+            self.stations
+                .iter()
+                .map(|s| s.position().0.abs_diff(pos.0) + s.position().1.abs_diff(pos.1))
+                .min()
+                .unwrap_or(usize::MAX)
+        };
+        self.stations[idx]
+            .master_map
+            .iter()
+            .filter(|(_, &tile)| tile == Tile::Empty)
+            .map(|(&pos, _)| (distance(pos), pos))
+            .filter(|&(dist, _)| dist >= OUTPOST_SPACING)
+            .max_by_key(|&(dist, pos)| (dist, Reverse(pos)))
+            .map(|(_, pos)| pos)
+    }
+
+    /// Starts an outpost at `site` with the parent's map, costs, policy and
+    /// lateness, but stocks of its own.
+    fn launch_outpost(&mut self, parent: usize, site: (usize, usize)) {
+        let id = self.stations.iter().map(Station::id).max().unwrap_or(0) + 1;
+        let (tx, rx) = mpsc::channel();
+
+        let parent = &self.stations[parent];
+        let policy =
+            policy_by_name(parent.economy().policy_name()).unwrap_or_else(|| Box::new(Greedy));
+        let mut outpost = Station::detached(tx)
+            .with_id(id)
+            .with_position(site)
+            .with_dock_radius(parent.site().dock_radius)
+            .with_lateness(parent.lateness())
+            .with_robot_ids(parent.robot_ids())
+            .with_economy(Economy::new(parent.economy().costs().clone(), policy));
+        outpost.apply_link(parent.id(), &full_map(parent));
+
+        let _ = self.tx_cmd.send(StationCmd::Log(LogRecord::new(
+            self.tick,
+            Level::Info,
+            Source::Station(parent.id()),
+            Category::Network,
+            format!(
+                "Outpost {} built by station {} at ({}, {})",
+                id,
+                parent.id(),
+                site.0,
+                site.1
+            ),
+        )));
+        self.outposts.push((id, rx));
+        self.relayed.push(outpost.map_version());
+        let _ = self.tx_cmd.send(StationCmd::StationOnline(outpost.site()));
+        self.stations.push(outpost);
+    }
+
+    /// Forwards outpost output to the app. Version, merge and economy
+    /// updates are dropped: the app shows the main station's.
+    fn relay_outpost_cmds(&mut self) {
+        for (_, rx) in &self.outposts {
+            while let Ok(cmd) = rx.try_recv() {
+                match cmd {
+                    StationCmd::Version(_)
+                    | StationCmd::MasterDiff { .. }
+                    | StationCmd::MergeStats(_)
+                    | StationCmd::Economy(_) => {}
+                    cmd => {
+                        let _ = self.tx_cmd.send(cmd);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn full_map(station: &Station) -> MapDiff {
+    MapDiff(
+        station
+            .master_map
+            .iter()
+            .map(|(&pos, &tile)| (pos, None, tile))
+            .collect(),
+    )
+}
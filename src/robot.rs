@@ -1,12 +1,26 @@
+use crate::log::{Category, Level, LogRecord, Source};
 use crate::map::Tile;
 use crate::map::{Map, MapDiff};
-use crate::station::RobotReport;
-use std::collections::{HashSet, VecDeque};
+use crate::scanner::ScannerConfig;
+use crate::station::{DockPhase, RobotReport, StationSite};
+use crate::traffic::DockOrder;
+use crate::watchdog::Recovery;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::mpsc::{Receiver, Sender};
 use std::sync::{Arc, RwLock};
 
 /// nbr of resources a robot can carry
 pub const PAYLOAD_LIMIT: u32 = 10;
+/// extra payload per Collector level above 1
+const PAYLOAD_PER_LEVEL: u32 = 5;
+/// nbr of recent actions a robot remembers
+pub const ACTION_HISTORY: usize = 8;
+
+/// Installed modules with their level.
+pub type Loadout = Vec<(RobotModule, u8)>;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RobotState {
@@ -14,7 +28,7 @@ pub enum RobotState {
     Returning,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum RobotModule {
     Explorer,
     Collector,
@@ -22,17 +36,111 @@ pub enum RobotModule {
     Sensor,
 }
 
+impl RobotModule {
+    pub fn max_level(&self) -> u8 {
+        match self {
+            RobotModule::Explorer => 2,
+            RobotModule::Collector => 3,
+            RobotModule::Scanner => 3,
+            RobotModule::Sensor => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Retrofit {
+    Install(RobotModule),
+    Remove(RobotModule),
+    Upgrade(RobotModule),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetrofitError {
+    AlreadyInstalled,
+    NotInstalled,
+    MaxLevel,
+}
+
+impl Retrofit {
+    /// Whether the change applies to a robot with `loadout`.
+    pub fn check(&self, loadout: &Loadout) -> Result<(), RetrofitError> {
+        let (Retrofit::Install(module) | Retrofit::Remove(module) | Retrofit::Upgrade(module)) =
+            self;
+        let level = loadout
+            .iter()
+            .find(|(m, _)| m == module)
+            .map_or(0, |(_, level)| *level);
+        match self {
+            Retrofit::Install(_) if level > 0 => Err(RetrofitError::AlreadyInstalled),
+            Retrofit::Remove(_) | Retrofit::Upgrade(_) if level == 0 => {
+                Err(RetrofitError::NotInstalled)
+            }
+            Retrofit::Upgrade(_) if level >= module.max_level() => Err(RetrofitError::MaxLevel),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What the coordinator learns about a robot each tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RobotStatus {
+    pub id: usize,
+    pub position: (usize, usize),
+    /// Station the robot is heading back to, `None` while exploring.
+    pub returning_to: Option<usize>,
+    /// Resources carried.
+    pub cargo: u32,
+    /// Has a Collector, so the coordinator picks up resources under it.
+    pub collects: bool,
+}
+
+/// What the current loadout lets a robot do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Scan radius in cells, 0 without a Scanner.
+    pub scan_radius: usize,
+    /// Resources carried before returning, 0 without a Collector.
+    pub payload: u32,
+    /// Cells moved per tick while exploring, 0 without an Explorer.
+    pub speed: usize,
+}
+
 pub enum RobotCmd {
     Tick {
         tick_count: u64,
         occupied_positions: HashSet<(usize, usize)>,
+        /// Caught in a dust storm, the scanner sees nothing this tick.
+        blinded: bool,
+        dock: DockOrder,
     },
     Snapshot {
+        station: usize,
         version: u64,
         diff: MapDiff,
     },
-    ReportPosition {
-        respond_to: Sender<(usize, (usize, usize))>,
+    /// Stations the robot can dock at.
+    Stations(Vec<StationSite>),
+    /// Resources the coordinator picked up at the robot's position.
+    Cargo {
+        energy: u32,
+        mineral: u32,
+    },
+    ReportStatus {
+        respond_to: Sender<RobotStatus>,
+    },
+    /// Asks for a copy of the robot, for display.
+    Inspect {
+        respond_to: Sender<Robot>,
+    },
+    Retrofit(Retrofit),
+    /// Heads back to the station whatever the cargo.
+    Recall,
+    /// Returns to station `Some(id)` from now on, or to the nearest one.
+    Assign(Option<usize>),
+    /// Replaces the robot's usual move for `ticks` ticks.
+    Recover {
+        recovery: Recovery,
+        ticks: u64,
     },
     Shutdown,
 }
@@ -42,6 +150,14 @@ pub struct RobotActor {
     map: Arc<RwLock<Map>>,
     rx: Receiver<RobotCmd>,
     tx_report: Sender<RobotReport>,
+    stations: Vec<StationSite>,
+    /// Active recovery, the ticks it has left and its random walk rng.
+    recovery: Option<(Recovery, u64, StdRng)>,
+    /// Last tick processed, to date actions taken between ticks.
+    tick: u64,
+    log: Option<Sender<LogRecord>>,
+    /// Resources loaded since the last tick, logged when it runs.
+    loaded: Vec<(Tile, (usize, usize))>,
 }
 
 impl RobotActor {
@@ -56,78 +172,261 @@ impl RobotActor {
             map,
             rx,
             tx_report,
+            stations: Vec::new(),
+            recovery: None,
+            tick: 0,
+            log: None,
+            loaded: Vec::new(),
         }
     }
 
+    /// Sends a log record for every recorded action to `log`.
+    pub fn with_log(mut self, log: Sender<LogRecord>) -> Self {
+        self.log = Some(log);
+        self
+    }
+
+    /// Records an action in the robot's history and logs it.
+    fn note(&mut self, level: Level, category: Category, action: String) {
+        if let Some(log) = &self.log {
+            let _ = log.send(LogRecord::new(
+                self.tick,
+                level,
+                Source::Robot(self.robot.id),
+                category,
+                action.clone(),
+            ));
+        }
+        self.robot.record(self.tick, action);
+    }
+
     pub fn run(mut self) {
         while let Ok(cmd) = self.rx.recv() {
             match cmd {
                 RobotCmd::Tick {
                     tick_count,
                     occupied_positions,
+                    blinded,
+                    dock,
                 } => {
-                    self.process_tick(tick_count, &occupied_positions);
+                    self.process_tick(tick_count, &occupied_positions, blinded, dock);
                 }
-                RobotCmd::Snapshot { version: _, diff } => {
+                RobotCmd::Snapshot {
+                    station,
+                    version,
+                    diff,
+                } => {
                     diff.apply_to_known_map(&mut self.robot.known_map);
+                    let known = self.robot.known_versions.entry(station).or_insert(0);
+                    *known = (*known).max(version);
+                    let _ = self.tx_report.send(self.robot.make_ack(station, version));
+                }
+                RobotCmd::Stations(stations) => self.stations = stations,
+                RobotCmd::Cargo { energy, mineral } => {
+                    self.robot.energy_collected += energy;
+                    self.robot.mineral_collected += mineral;
+                    for (tile, count) in [(Tile::Energy, energy), (Tile::Mineral, mineral)] {
+                        if count > 0 {
+                            self.loaded.push((tile, self.robot.position));
+                        }
+                    }
+                }
+                RobotCmd::ReportStatus { respond_to } => {
+                    let _ = respond_to.send(self.status());
+                }
+                RobotCmd::Inspect { respond_to } => {
+                    let _ = respond_to.send(self.robot.clone());
+                }
+                RobotCmd::Retrofit(op) => {
+                    if self.robot.retrofit(&op).is_ok() {
+                        self.note(
+                            Level::Info,
+                            Category::Economy,
+                            format!("retrofit: {:?}", op),
+                        );
+                    }
+                }
+                RobotCmd::Recall => {
+                    self.robot.state = RobotState::Returning;
+                    self.note(Level::Info, Category::Console, "recalled".to_string());
+                }
+                RobotCmd::Assign(station) => {
+                    self.robot.station = station;
+                    let home = match station {
+                        Some(id) => format!("station {}", id),
+                        None => "the nearest station".to_string(),
+                    };
+                    self.note(
+                        Level::Info,
+                        Category::Network,
+                        format!("returns to {}", home),
+                    );
                 }
-                RobotCmd::ReportPosition { respond_to } => {
-                    let _ = respond_to.send((self.robot.id, self.robot.position));
+                RobotCmd::Recover { recovery, ticks } => {
+                    let seed = match recovery {
+                        Recovery::RandomWalk { seed } => seed,
+                        _ => 0,
+                    };
+                    self.recovery = Some((recovery, ticks, StdRng::seed_from_u64(seed)));
+                    self.note(
+                        Level::Warn,
+                        Category::Watchdog,
+                        format!("recovering: {:?}", recovery),
+                    );
                 }
                 RobotCmd::Shutdown => break,
             }
         }
     }
 
-    fn process_tick(&mut self, tick_count: u64, occupied: &HashSet<(usize, usize)>) {
-        let map = self.map.read().unwrap();
+    fn status(&self) -> RobotStatus {
+        let returning_to = match self.robot.state {
+            RobotState::Returning => self.robot.home(&self.stations).map(|home| home.id),
+            RobotState::Exploring => None,
+        };
+        RobotStatus {
+            id: self.robot.id,
+            position: self.robot.position,
+            returning_to,
+            cargo: self.robot.energy_collected + self.robot.mineral_collected,
+            collects: self.robot.capabilities().payload > 0,
+        }
+    }
 
-        if self.robot.modules.contains(&RobotModule::Scanner) {
-            self.robot.scan_surroundings(&map);
+    fn process_tick(
+        &mut self,
+        tick_count: u64,
+        occupied: &HashSet<(usize, usize)>,
+        blinded: bool,
+        dock: DockOrder,
+    ) {
+        self.tick = tick_count;
+        let map_lock = Arc::clone(&self.map);
+        let map = map_lock.read().unwrap();
+        let caps = self.robot.capabilities();
+        let start = self.robot.position;
+
+        for (tile, (row, col)) in std::mem::take(&mut self.loaded) {
+            self.note(
+                Level::Info,
+                Category::Collection,
+                format!("collected {:?} at ({}, {})", tile, row, col),
+            );
         }
 
-        if self.robot.modules.contains(&RobotModule::Collector) {
-            let (row, col) = self.robot.position;
-            if row < map.grid.len() && col < map.cols {
-                let tile = map.grid[row][col];
-                if matches!(tile, Tile::Energy | Tile::Mineral) {
-                    self.robot
-                        .dirty_tiles
-                        .push(((row, col), Some(tile), Tile::Empty));
-
-                    if tile == Tile::Energy {
-                        self.robot.energy_collected += 1;
-                    } else if tile == Tile::Mineral {
-                        self.robot.mineral_collected += 1;
-                    }
-                }
-            }
+        if caps.scan_radius > 0 && !blinded {
+            self.robot.scan_surroundings(&map);
         }
 
         if self.robot.state == RobotState::Exploring
-            && self.robot.energy_collected + self.robot.mineral_collected >= PAYLOAD_LIMIT
+            && caps.payload > 0
+            && self.robot.energy_collected + self.robot.mineral_collected >= caps.payload
         {
             self.robot.state = RobotState::Returning;
+            self.note(
+                Level::Info,
+                Category::Movement,
+                "full, returning".to_string(),
+            );
+        }
+
+        let recovery = self.recovery.as_mut().map(|(recovery, ticks, rng)| {
+            *ticks = ticks.saturating_sub(1);
+            match *recovery {
+                Recovery::RandomWalk { .. } => self.robot.random_step(&map, occupied, rng),
+                Recovery::Yield { blocker } => self.robot.step_away(blocker, &map, occupied),
+                Recovery::Replan => {}
+            }
+            *recovery
+        });
+        if self
+            .recovery
+            .as_ref()
+            .is_some_and(|(_, ticks, _)| *ticks == 0)
+        {
+            self.recovery = None;
         }
+        let replan = recovery == Some(Recovery::Replan);
 
         match self.robot.state {
+            _ if recovery.is_some() && !replan => {}
             RobotState::Exploring => {
-                if self.robot.modules.contains(&RobotModule::Explorer) {
+                for _ in 0..caps.speed {
                     self.robot.smart_move(&map, occupied);
                 }
             }
             RobotState::Returning => {
-                self.robot.step_towards((0, 0), &map, occupied);
+                if let Some(home) = self.robot.home(&self.stations) {
+                    let (row, col) = self.robot.position;
+                    let zone = home.zone(map.grid.len(), map.cols);
+                    // queued robots keep out of the zone
+                    let waiting: HashSet<_>;
+                    let blocked = match dock {
+                        DockOrder::Wait(_) => {
+                            waiting = occupied.iter().chain(&zone).copied().collect();
+                            &waiting
+                        }
+                        _ => occupied,
+                    };
+                    let target = match dock {
+                        DockOrder::Slot(slot) => Some(slot),
+                        DockOrder::Wait(spot) => Some(spot),
+                        DockOrder::Hold => None,
+                        // without a traffic controller: nearest free zone
+                        // cell, queueing outside while every one is taken
+                        DockOrder::Free => zone
+                            .into_iter()
+                            .filter(|cell| !occupied.contains(cell))
+                            .min_by_key(|&(r, c)| r.abs_diff(row) + c.abs_diff(col)),
+                    };
+                    self.robot.target = target;
+                    if let Some(target) = target {
+                        for _ in 0..caps.speed.max(1) {
+                            if self.robot.position == target
+                                || (home.in_zone(self.robot.position)
+                                    && !matches!(dock, DockOrder::Wait(_)))
+                            {
+                                break;
+                            }
+                            if !replan || !self.robot.path_towards(target, &map, blocked) {
+                                self.robot.step_towards(target, &map, blocked);
+                            }
+                        }
+                    }
+                    self.robot.path = target
+                        .and_then(|target| find_path(self.robot.position, target, &map, blocked))
+                        .unwrap_or_default();
+                }
             }
         }
 
-        if self.robot.state == RobotState::Returning && self.robot.position == (0, 0) {
-            let report = self.robot.make_report(tick_count);
-            let _ = self.tx_report.send(report);
-            self.robot.state = RobotState::Exploring;
+        if let Some(home) = self.robot.home(&self.stations) {
+            if self.robot.state == RobotState::Returning
+                && home.in_zone(self.robot.position)
+                && matches!(dock, DockOrder::Free | DockOrder::Slot(_))
+            {
+                let report = self.robot.make_report(home.id, tick_count);
+                let _ = self.tx_report.send(report);
+                self.robot.state = RobotState::Exploring;
+                self.robot.target = None;
+                self.robot.path.clear();
+                self.note(
+                    Level::Info,
+                    Category::Dock,
+                    format!("docked at station {}", home.id),
+                );
+            }
         }
 
         drop(map);
+        if self.robot.position != start {
+            let (row, col) = self.robot.position;
+            self.note(
+                Level::Debug,
+                Category::Movement,
+                format!("moved to ({}, {})", row, col),
+            );
+        }
     }
 }
 
@@ -138,10 +437,23 @@ pub struct Robot {
     pub position: (usize, usize),
     pub last_position: Option<(usize, usize)>,
     pub modules: Vec<RobotModule>,
+    /// Level of each installed module above 1.
+    pub levels: HashMap<RobotModule, u8>,
+    pub scanner: ScannerConfig,
     pub energy_collected: u32,
     pub mineral_collected: u32,
     pub state: RobotState,
     pub dirty_tiles: Vec<((usize, usize), Option<Tile>, Tile)>,
+    /// Last master map version received from each station.
+    pub known_versions: HashMap<usize, u64>,
+    /// Station the robot always returns to; the nearest one when `None`.
+    pub station: Option<usize>,
+    /// Cell the robot is heading for, if any.
+    pub target: Option<(usize, usize)>,
+    /// Planned cells from the next step up to the target.
+    pub path: Vec<(usize, usize)>,
+    /// Most recent actions with their tick, oldest first.
+    pub actions: VecDeque<(u64, String)>,
 }
 
 impl Robot {
@@ -152,24 +464,107 @@ impl Robot {
             position,
             last_position: None,
             modules,
+            levels: HashMap::new(),
+            scanner: ScannerConfig::default(),
             energy_collected: 0,
             mineral_collected: 0,
             state: RobotState::Exploring,
             dirty_tiles: Vec::new(),
+            known_versions: HashMap::new(),
+            station: None,
+            target: None,
+            path: Vec::new(),
+            actions: VecDeque::new(),
         }
     }
 
-    pub fn scan_surroundings(&mut self, map: &crate::map::Map) {
+    pub fn record(&mut self, tick: u64, action: String) {
+        if self.actions.len() >= ACTION_HISTORY {
+            self.actions.pop_front();
+        }
+        self.actions.push_back((tick, action));
+    }
+
+    /// Station to return to: the assigned one when it is known, otherwise the
+    /// nearest by Manhattan distance.
+    pub fn home(&self, stations: &[StationSite]) -> Option<StationSite> {
         let (row, col) = self.position;
-        for dr in -1..=1 {
-            for dc in -1..=1 {
-                let r = row.wrapping_add(dr as usize);
-                let c = col.wrapping_add(dc as usize);
-                if r < map.grid.len() && c < map.cols {
-                    let tile = map.grid[r][c];
-                    self.known_map.insert((r, c), tile);
-                    self.dirty_tiles.push(((r, c), None, tile));
-                }
+        self.station
+            .and_then(|id| stations.iter().find(|site| site.id == id))
+            .or_else(|| {
+                stations.iter().min_by_key(|site| {
+                    site.position.0.abs_diff(row) + site.position.1.abs_diff(col)
+                })
+            })
+            .copied()
+    }
+
+    /// Level of `module`, 0 when it is not installed.
+    pub fn level(&self, module: &RobotModule) -> u8 {
+        if !self.modules.contains(module) {
+            return 0;
+        }
+        self.levels.get(module).copied().unwrap_or(1)
+    }
+
+    pub fn loadout(&self) -> Loadout {
+        self.modules
+            .iter()
+            .map(|module| (module.clone(), self.level(module)))
+            .collect()
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        let payload = match self.level(&RobotModule::Collector) {
+            0 => 0,
+            level => PAYLOAD_LIMIT + (level as u32 - 1) * PAYLOAD_PER_LEVEL,
+        };
+        let scan_radius = match self.level(&RobotModule::Scanner) {
+            0 => 0,
+            level => self.scanner.radius + level as usize - 1,
+        };
+        Capabilities {
+            scan_radius,
+            payload,
+            speed: self.level(&RobotModule::Explorer) as usize,
+        }
+    }
+
+    pub fn retrofit(&mut self, op: &Retrofit) -> Result<(), RetrofitError> {
+        op.check(&self.loadout())?;
+        match op {
+            Retrofit::Install(module) => {
+                self.modules.push(module.clone());
+                self.levels.remove(module);
+            }
+            Retrofit::Remove(module) => {
+                self.modules.retain(|m| m != module);
+                self.levels.remove(module);
+            }
+            Retrofit::Upgrade(module) => {
+                let level = self.level(module);
+                self.levels.insert(module.clone(), level + 1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Cells the robot's scanner would see from its current position.
+    pub fn field_of_view(&self, map: &crate::map::Map) -> Vec<(usize, usize)> {
+        if self.level(&RobotModule::Scanner) == 0 {
+            return Vec::new();
+        }
+        self.scanner
+            .cells(map, self.position, self.capabilities().scan_radius)
+    }
+
+    /// Scans with the robot's scanner model and marks only new or changed
+    /// cells dirty.
+    pub fn scan_surroundings(&mut self, map: &crate::map::Map) {
+        for (r, c) in self.field_of_view(map) {
+            let tile = map.grid[r][c];
+            if self.known_map.insert((r, c), tile) != Some(tile) {
+                self.dirty_tiles.push(((r, c), None, tile));
             }
         }
     }
@@ -184,7 +579,7 @@ impl Robot {
         q.push_back((sr, sc));
         visited.insert((sr, sc));
 
-        let target = 'search: loop {
+        let target = 'search: {
             while let Some((r, c)) = q.pop_front() {
                 if matches!(map.grid[r][c], Tile::Energy | Tile::Mineral) && (r, c) != (sr, sc) {
                     break 'search Some((r, c));
@@ -207,16 +602,21 @@ impl Robot {
                     q.push_back((nr, nc));
                 }
             }
-            break None;
+            None
         };
 
+        self.target = target;
+        self.path.clear();
         let next = if let Some(mut cur) = target {
+            self.path.push(cur);
             while let Some(&p) = parent.get(&cur) {
                 if p == (sr, sc) {
                     break;
                 }
                 cur = p;
+                self.path.push(cur);
             }
+            self.path.reverse();
             Some(cur)
         } else {
             let dirs = [(0, 1), (1, 0), (0, usize::MAX), (usize::MAX, 0)];
@@ -263,13 +663,12 @@ impl Robot {
             for dc in -1..=1 {
                 let r = row.wrapping_add(dr as usize);
                 let c = col.wrapping_add(dc as usize);
-                if (r, c) != self.position {
-                    if robot_snapshots
+                if (r, c) != self.position
+                    && robot_snapshots
                         .iter()
                         .any(|&(id, pos)| id != self.id && pos == (r, c))
-                    {
-                        nearby.insert((r, c));
-                    }
+                {
+                    nearby.insert((r, c));
                 }
             }
         }
@@ -277,16 +676,34 @@ impl Robot {
         nearby
     }
 
-    pub fn make_report(&mut self, tick: u64) -> RobotReport {
+    pub fn make_report(&mut self, station: usize, tick: u64) -> RobotReport {
         let diff_vec = std::mem::take(&mut self.dirty_tiles);
-        let report = RobotReport {
+        RobotReport {
             robot_id: self.id,
+            station,
             tick,
+            base_version: self.known_versions.get(&station).copied().unwrap_or(0),
             map_diff: MapDiff(diff_vec),
             energy: std::mem::take(&mut self.energy_collected),
             mineral: std::mem::take(&mut self.mineral_collected),
-        };
-        report
+            dock: DockPhase::Upload,
+            loadout: self.loadout(),
+        }
+    }
+
+    /// Confirms to `station` that its download up to `version` was applied.
+    pub fn make_ack(&self, station: usize, version: u64) -> RobotReport {
+        RobotReport {
+            robot_id: self.id,
+            station,
+            tick: 0,
+            base_version: self.known_versions.get(&station).copied().unwrap_or(0),
+            map_diff: MapDiff(Vec::new()),
+            energy: 0,
+            mineral: 0,
+            dock: DockPhase::Ack(version),
+            loadout: Vec::new(),
+        }
     }
 
     pub fn step_towards(
@@ -323,4 +740,107 @@ impl Robot {
             }
         }
     }
+
+    pub fn random_step(
+        &mut self,
+        map: &crate::map::Map,
+        occupied: &HashSet<(usize, usize)>,
+        rng: &mut StdRng,
+    ) {
+        if let Some(&next) = free_neighbours(self.position, map, occupied).choose(rng) {
+            self.last_position = Some(self.position);
+            self.position = next;
+        }
+    }
+
+    /// Moves to the free neighbour farthest from `blocker`, if that is
+    /// farther than where the robot stands.
+    pub fn step_away(
+        &mut self,
+        blocker: (usize, usize),
+        map: &crate::map::Map,
+        occupied: &HashSet<(usize, usize)>,
+    ) {
+        let distance = |(r, c): (usize, usize)| r.abs_diff(blocker.0) + c.abs_diff(blocker.1);
+        let best = free_neighbours(self.position, map, occupied)
+            .into_iter()
+            .max_by_key(|&cell| distance(cell));
+        if let Some(next) = best.filter(|&next| distance(next) > distance(self.position)) {
+            self.last_position = Some(self.position);
+            self.position = next;
+        }
+    }
+
+    /// One step along a shortest path to `target`, going round obstacles
+    /// and robots. Returns false when no path exists.
+    pub fn path_towards(
+        &mut self,
+        target: (usize, usize),
+        map: &crate::map::Map,
+        occupied: &HashSet<(usize, usize)>,
+    ) -> bool {
+        match find_path(self.position, target, map, occupied) {
+            Some(path) => {
+                if let Some(&next) = path.first() {
+                    self.last_position = Some(self.position);
+                    self.position = next;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Shortest path from `start` to `target`, without `start` itself.
+pub fn find_path(
+    start: (usize, usize),
+    target: (usize, usize),
+    map: &crate::map::Map,
+    occupied: &HashSet<(usize, usize)>,
+) -> Option<Vec<(usize, usize)>> {
+    let mut q = VecDeque::from([start]);
+    let mut parent = HashMap::new();
+    parent.insert(start, start);
+
+    while let Some(cell) = q.pop_front() {
+        if cell == target {
+            let mut path = vec![cell];
+            let mut step = cell;
+            while parent[&step] != start {
+                step = parent[&step];
+                path.push(step);
+            }
+            if cell == start {
+                path.clear();
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in free_neighbours(cell, map, occupied) {
+            if let std::collections::hash_map::Entry::Vacant(entry) = parent.entry(next) {
+                entry.insert(cell);
+                q.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Free cells next to `(row, col)`, in a fixed order.
+fn free_neighbours(
+    (row, col): (usize, usize),
+    map: &crate::map::Map,
+    occupied: &HashSet<(usize, usize)>,
+) -> Vec<(usize, usize)> {
+    [(0, 1), (1, 0), (0, usize::MAX), (usize::MAX, 0)]
+        .iter()
+        .map(|&(dr, dc)| (row.wrapping_add(dr), col.wrapping_add(dc)))
+        .filter(|&(r, c)| {
+            r < map.grid.len()
+                && c < map.cols
+                && !occupied.contains(&(r, c))
+                && map.grid[r][c] != Tile::Obstacle
+        })
+        .collect()
 }
@@ -0,0 +1,69 @@
+use crate::map::Map;
+use crate::visibility;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanShape {
+    Square,
+    Diamond,
+    Circle,
+}
+
+impl ScanShape {
+    pub fn contains(&self, dr: isize, dc: isize, radius: usize) -> bool {
+        let radius = radius as isize;
+        match self {
+            ScanShape::Square => dr.abs().max(dc.abs()) <= radius,
+            ScanShape::Diamond => dr.abs() + dc.abs() <= radius,
+            // r² + r rounds the disc outwards so radius 1 still covers diagonals
+            ScanShape::Circle => dr * dr + dc * dc <= radius * radius + radius,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScannerConfig {
+    /// Radius of a level 1 scanner, each level above adds one cell.
+    pub radius: usize,
+    pub shape: ScanShape,
+    /// Obstacles hide the cells behind them.
+    pub line_of_sight: bool,
+}
+
+impl Default for ScannerConfig {
+    fn default() -> Self {
+        Self {
+            radius: 1,
+            shape: ScanShape::Circle,
+            line_of_sight: true,
+        }
+    }
+}
+
+impl ScannerConfig {
+    /// Cells seen from `origin` with the given radius, in row-major order.
+    pub fn cells(&self, map: &Map, origin: (usize, usize), radius: usize) -> Vec<(usize, usize)> {
+        let (row, col) = (origin.0 as isize, origin.1 as isize);
+        let radius_i = radius as isize;
+        let fov = self
+            .line_of_sight
+            .then(|| visibility::field_of_view(map, origin, radius));
+        let mut cells = Vec::new();
+        for dr in -radius_i..=radius_i {
+            for dc in -radius_i..=radius_i {
+                let (r, c) = (row + dr, col + dc);
+                if r < 0 || c < 0 || r as usize >= map.grid.len() || c as usize >= map.cols {
+                    continue;
+                }
+                if !self.shape.contains(dr, dc, radius) {
+                    continue;
+                }
+                let cell = (r as usize, c as usize);
+                if fov.as_ref().is_some_and(|fov| !fov.contains(&cell)) {
+                    continue;
+                }
+                cells.push(cell);
+            }
+        }
+        cells
+    }
+}
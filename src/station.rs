@@ -1,22 +1,52 @@
+use crate::economy::{BuildItem, Economy, EconomyStatus, Stocks, Upgrade};
+use crate::journal::{Journal, JournalEntry, JournalError, StationState};
+use crate::log::{Category, Level, LogRecord, Source};
 use crate::map::{MapDiff, Tile};
-use crate::robot::RobotModule;
-use std::collections::HashMap;
-use std::sync::mpsc::{Receiver, Sender};
+use crate::robot::{Loadout, Retrofit, RobotModule};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+const MAX_CONFLICT_RECORDS: usize = 256;
+const DEFAULT_HISTORY_LEN: usize = 64;
+/// Ticks behind the newest report for which duplicates are still detected;
+/// anything older is dropped as expired.
+const DEDUP_HORIZON: u64 = 1024;
+const DEFAULT_DOCK_RADIUS: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DockPhase {
+    /// Not part of a docking exchange (spawn announcements, coordinator updates).
+    None,
+    /// The robot reached the station and uploads its report.
+    Upload,
+    /// The robot applied the station's download up to this version.
+    Ack(u64),
+}
 
 #[derive(Debug, Clone)]
 pub struct RobotReport {
     #[allow(dead_code)]
     pub robot_id: usize,
+    /// Station the robot docks at; ignored for reports outside a docking
+    /// exchange, which every station receives.
+    pub station: usize,
     pub tick: u64,
+    /// Master map version the diff was computed against.
+    pub base_version: u64,
     pub map_diff: MapDiff,
     pub energy: u32,
     pub mineral: u32,
+    pub dock: DockPhase,
+    /// Robot's modules at upload time, empty otherwise.
+    pub loadout: Loadout,
 }
 
 #[derive(Debug)]
 #[allow(dead_code)]
 pub enum StationCmd {
-    Log(String),
+    Log(LogRecord),
     Spawn {
         id: usize,
         modules: Vec<RobotModule>,
@@ -24,93 +54,912 @@ pub enum StationCmd {
     },
     Snapshot {
         id: u32,
+        station: usize,
         version: u64,
         diff: MapDiff,
     },
     Shutdown,
     Version(u64),
+    /// Cells a station's master map changed to reach `version`.
+    MasterDiff {
+        station: usize,
+        version: u64,
+        diff: MapDiff,
+    },
     ResourceUpdate {
         energy: u32,
         mineral: u32,
     },
+    MergeStats(MergeMetrics),
+    Retrofit {
+        id: usize,
+        op: Retrofit,
+    },
+    Economy(EconomyStatus),
+    /// A station (or newly built outpost) robots can dock at.
+    StationOnline(StationSite),
+    /// From the console: sends robot `id` back to its station.
+    Recall(usize),
+    /// From the console: ties robot `id` to a station, `None` for the nearest.
+    Assign {
+        id: usize,
+        station: Option<usize>,
+    },
+    /// From the console: overwrites a cell of the ground-truth map.
+    SetTile {
+        cell: (usize, usize),
+        tile: Tile,
+    },
+}
+
+/// Where a station sits and the zone around it in which robots dock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StationSite {
+    pub id: usize,
+    pub position: (usize, usize),
+    /// Robots dock anywhere within this many cells (Chebyshev) of the
+    /// station; 0 means on the station cell itself.
+    pub dock_radius: usize,
+}
+
+impl StationSite {
+    pub fn in_zone(&self, pos: (usize, usize)) -> bool {
+        pos.0
+            .abs_diff(self.position.0)
+            .max(pos.1.abs_diff(self.position.1))
+            <= self.dock_radius
+    }
+
+    /// Docking zone cells inside a `rows` x `cols` map, row-major.
+    pub fn zone(&self, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+        let (row, col) = self.position;
+        let radius = self.dock_radius;
+        let last_col = col.saturating_add(radius).min(cols.saturating_sub(1));
+        (row.saturating_sub(radius)..=row.saturating_add(radius).min(rows.saturating_sub(1)))
+            .flat_map(|r| (col.saturating_sub(radius)..=last_col).map(move |c| (r, c)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeMetrics {
+    pub merged: u64,
+    /// Reports that arrived after their tick was finalised (still merged).
+    pub late: u64,
+    pub duplicates: u64,
+    /// Reports older than the dedup horizon.
+    pub expired: u64,
+    pub pending: usize,
+}
+
+impl MergeMetrics {
+    pub fn dropped(&self) -> u64 {
+        self.duplicates + self.expired
+    }
+}
+
+/// Operator requests from the TUI console, answered over `StationCmd`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StationRequest {
+    /// Launches a robot at `position` without paying for it.
+    Spawn {
+        modules: Vec<RobotModule>,
+        position: (usize, usize),
+    },
+    /// Adds to (or takes from) the stocks, stopping at zero.
+    AdjustStocks {
+        energy: i64,
+        mineral: i64,
+    },
+    /// Takes `module` off a robot and returns it, with its upgrades, to the
+    /// spare parts.
+    Remove {
+        robot_id: usize,
+        module: RobotModule,
+    },
+    /// Writes a journal checkpoint now.
+    Save,
+    Query(StationQuery),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StationQuery {
+    Stocks,
+    Version,
+    Metrics,
+    Queue,
+    /// Most recent conflicts, newest first.
+    Conflicts,
+    Cell((usize, usize)),
+    /// Map versions a robot acknowledged and still has to acknowledge.
+    Robot(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Applied,
+    Rejected,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConflictRecord {
+    pub pos: (usize, usize),
+    pub robot_id: usize,
+    pub tick: u64,
+    pub base_version: u64,
+    /// "Before" value carried by the diff, `None` for a plain observation.
+    pub expected: Option<Tile>,
+    pub found: Tile,
+    pub proposed: Tile,
+    pub resolution: Resolution,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CellMeta {
+    version: u64,
+    tick: u64,
+    collected: bool,
 }
 
 pub struct Station {
     rx: Receiver<RobotReport>,
     tx_cmd: Sender<StationCmd>,
+    id: usize,
+    position: (usize, usize),
+    dock_radius: usize,
 
     pub master_map: HashMap<(usize, usize), Tile>,
     pending: Vec<RobotReport>,
     energy_stock: u32,
     mineral_stock: u32,
-    next_robot_id: usize,
+    /// Next robot id, shared by every station of a network.
+    robot_ids: Arc<AtomicUsize>,
     map_version: u64,
+    cell_meta: HashMap<(usize, usize), CellMeta>,
+    pub conflicts: VecDeque<ConflictRecord>,
+    /// Diff produced by each of the last `history_len` versions, oldest first.
+    history: VecDeque<(u64, MapDiff)>,
+    history_len: usize,
+    /// Last master version each robot acknowledged.
+    robot_versions: HashMap<usize, u64>,
+    /// Loadout of each robot as last announced, docked or fitted here.
+    loadouts: HashMap<usize, Loadout>,
+    /// Version of the download each robot still has to acknowledge.
+    awaiting_ack: HashMap<usize, u64>,
+    /// Ticks a report may trail the newest one before its tick is finalised.
+    lateness: u64,
+    newest_tick: Option<u64>,
+    finalized_tick: Option<u64>,
+    /// (robot, tick, dock phase) of every report accepted within the dedup
+    /// horizon; a spawn announcement and an upload may share a tick.
+    seen: HashSet<(usize, u64, DockPhase)>,
+    metrics: MergeMetrics,
+    journal: Option<Journal>,
+    economy: Economy,
+    /// Station tick the build queue last advanced to.
+    economy_tick: u64,
+    /// Finished modules and upgrades waiting to be fitted.
+    pub parts: Vec<BuildItem>,
+    /// Outposts built but not yet placed by the network.
+    outposts_ready: usize,
 }
 
 impl Station {
     pub fn new(rx: Receiver<RobotReport>, tx_cmd: Sender<StationCmd>) -> Self {
+        let state = StationState::default();
         Self {
             rx,
             tx_cmd,
-            master_map: HashMap::new(),
+            id: 0,
+            position: (0, 0),
+            dock_radius: DEFAULT_DOCK_RADIUS,
+            master_map: state.master_map,
             pending: Vec::new(),
-            energy_stock: 0,
-            mineral_stock: 0,
-            next_robot_id: 3,
-            map_version: 0,
+            energy_stock: state.energy_stock,
+            mineral_stock: state.mineral_stock,
+            robot_ids: Arc::new(AtomicUsize::new(state.next_robot_id)),
+            map_version: state.map_version,
+            cell_meta: HashMap::new(),
+            conflicts: VecDeque::new(),
+            history: VecDeque::new(),
+            history_len: DEFAULT_HISTORY_LEN,
+            robot_versions: HashMap::new(),
+            loadouts: HashMap::new(),
+            awaiting_ack: HashMap::new(),
+            lateness: 0,
+            newest_tick: None,
+            finalized_tick: None,
+            seen: HashSet::new(),
+            metrics: MergeMetrics::default(),
+            journal: None,
+            economy: Economy::instant(),
+            economy_tick: 0,
+            parts: state.parts,
+            outposts_ready: 0,
         }
     }
 
-    pub fn run(&mut self) {
-        while let Ok(report) = self.rx.recv() {
-            self.pending.push(report);
-            self.merge_pending_diffs();
+    /// Station fed by a `StationNetwork` instead of its own report channel.
+    pub fn detached(tx_cmd: Sender<StationCmd>) -> Self {
+        let (_, rx) = mpsc::channel();
+        Self::new(rx, tx_cmd)
+    }
 
-            if self.energy_stock >= 10 && self.mineral_stock >= 10 {
-                self.energy_stock -= 10;
-                self.mineral_stock -= 10;
+    pub fn with_id(mut self, id: usize) -> Self {
+        self.id = id;
+        self
+    }
 
-                let id = self.next_robot_id;
-                self.next_robot_id += 1;
+    pub fn with_position(mut self, position: (usize, usize)) -> Self {
+        self.position = position;
+        self
+    }
 
-                let start_pos = match id % 4 {
-                    0 => (0, 0),
-                    1 => (1, 1),
-                    2 => (2, 0),
-                    _ => (0, 2),
-                };
+    pub fn with_dock_radius(mut self, dock_radius: usize) -> Self {
+        self.dock_radius = dock_radius;
+        self
+    }
 
-                let _ = self.tx_cmd.send(StationCmd::Spawn {
-                    id,
-                    modules: vec![
-                        RobotModule::Explorer,
-                        RobotModule::Collector,
-                        RobotModule::Scanner,
-                        RobotModule::Sensor,
-                    ],
-                    start_pos,
-                });
+    /// Draws robot ids from `robot_ids` so stations never hand out the same
+    /// id twice.
+    pub fn with_robot_ids(mut self, robot_ids: Arc<AtomicUsize>) -> Self {
+        robot_ids.fetch_max(self.robot_ids.load(Ordering::SeqCst), Ordering::SeqCst);
+        self.robot_ids = robot_ids;
+        self
+    }
+
+    /// Replaces the build queue's costs and policy; items already paid for
+    /// stay queued.
+    pub fn with_economy(mut self, mut economy: Economy) -> Self {
+        for build in self.economy.queue() {
+            economy.restore(build.item.clone());
+        }
+        self.economy = economy;
+        self
+    }
 
-                let full_diff = MapDiff(
-                    self.master_map
+    /// Restores the state recorded in `journal` and keeps journaling to it.
+    pub fn with_journal(mut self, journal: Journal) -> Result<Self, JournalError> {
+        let state = journal.load()?;
+        if state.map_version > 0 {
+            self.log(
+                Level::Info,
+                Category::Journal,
+                format!(
+                    "Restored v{} from {}: {} cells | stocks {}E {}M",
+                    state.map_version,
+                    journal.dir().display(),
+                    state.master_map.len(),
+                    state.energy_stock,
+                    state.mineral_stock
+                ),
+            );
+            let _ = self.tx_cmd.send(StationCmd::MasterDiff {
+                station: self.id,
+                version: state.map_version,
+                diff: MapDiff(
+                    state
+                        .master_map
                         .iter()
-                        .map(|(&(r, c), &tile)| ((r, c), None, tile))
+                        .map(|(&pos, &tile)| (pos, None, tile))
                         .collect(),
-                );
-                let _ = self.tx_cmd.send(StationCmd::Snapshot {
-                    id: id as u32,
-                    version: self.map_version,
-                    diff: full_diff,
-                });
+                ),
+            });
+            let _ = self.tx_cmd.send(StationCmd::Version(state.map_version));
+        }
+        self.master_map = state.master_map;
+        self.energy_stock = state.energy_stock;
+        self.mineral_stock = state.mineral_stock;
+        self.robot_ids
+            .fetch_max(state.next_robot_id, Ordering::SeqCst);
+        self.map_version = state.map_version;
+        for item in state.queue {
+            self.economy.restore(item);
+        }
+        self.parts = state.parts;
+        self.economy.restore_outposts(state.outposts_queued);
+        self.journal = Some(journal);
+        Ok(self)
+    }
+
+    pub fn state(&self) -> StationState {
+        StationState {
+            master_map: self.master_map.clone(),
+            energy_stock: self.energy_stock,
+            mineral_stock: self.mineral_stock,
+            next_robot_id: self.robot_ids.load(Ordering::SeqCst),
+            map_version: self.map_version,
+            queue: self
+                .economy
+                .queue()
+                .iter()
+                .map(|build| build.item.clone())
+                .collect(),
+            parts: self.parts.clone(),
+            outposts_queued: self.economy.outposts_queued(),
+        }
+    }
+
+    pub fn with_lateness(mut self, lateness: u64) -> Self {
+        self.lateness = lateness;
+        self
+    }
+
+    pub fn with_history_len(mut self, history_len: usize) -> Self {
+        self.history_len = history_len;
+        self
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn position(&self) -> (usize, usize) {
+        self.position
+    }
+
+    pub fn site(&self) -> StationSite {
+        StationSite {
+            id: self.id,
+            position: self.position,
+            dock_radius: self.dock_radius,
+        }
+    }
+
+    pub fn robot_ids(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.robot_ids)
+    }
+
+    pub fn lateness(&self) -> u64 {
+        self.lateness
+    }
+
+    pub fn economy(&self) -> &Economy {
+        &self.economy
+    }
+
+    pub fn stocks(&self) -> Stocks {
+        Stocks {
+            energy: self.energy_stock,
+            mineral: self.mineral_stock,
+        }
+    }
+
+    /// Number of finished outposts waiting for a site; resets the count.
+    pub fn take_outposts(&mut self) -> usize {
+        std::mem::take(&mut self.outposts_ready)
+    }
+
+    pub fn map_version(&self) -> u64 {
+        self.map_version
+    }
+
+    pub fn awaiting_ack(&self, robot_id: usize) -> Option<u64> {
+        self.awaiting_ack.get(&robot_id).copied()
+    }
+
+    pub fn acked_version(&self, robot_id: usize) -> u64 {
+        self.robot_versions.get(&robot_id).copied().unwrap_or(0)
+    }
+
+    /// Newest tick that may still receive reports without being late;
+    /// every pending report at or below it gets merged.
+    pub fn watermark(&self) -> Option<u64> {
+        self.newest_tick
+            .map(|tick| tick.saturating_sub(self.lateness))
+    }
+
+    pub fn metrics(&self) -> MergeMetrics {
+        MergeMetrics {
+            pending: self.pending.len(),
+            ..self.metrics
+        }
+    }
+
+    pub fn run(&mut self) {
+        while let Ok(report) = self.rx.recv() {
+            self.handle_report(report);
+        }
+        self.flush();
+    }
+
+    /// Merges every pending report regardless of the watermark.
+    pub fn flush(&mut self) {
+        self.finalize(None);
+        self.run_economy();
+    }
+
+    /// Docking is a three-step handshake: the robot uploads its report, the
+    /// station answers with everything merged since the robot's last
+    /// acknowledged version, and the robot acknowledges that download.
+    pub fn handle_report(&mut self, report: RobotReport) {
+        let robot_id = report.robot_id;
+        let dock = report.dock;
+        let uploaded = (report.map_diff.0.len(), report.energy, report.mineral);
+        let loadout = report.loadout.clone();
+
+        match dock {
+            DockPhase::Ack(version) => {
+                self.acknowledge(robot_id, version);
+                return;
             }
+            DockPhase::Upload => {
+                // the upload is computed against what the robot already holds
+                let acked = self.robot_versions.entry(robot_id).or_insert(0);
+                *acked = (*acked).max(report.base_version);
+            }
+            DockPhase::None => {}
+        }
+
+        if !self.accept(&report) {
+            return;
         }
+        self.pending.push(report);
+        self.finalize(self.watermark());
+
+        if dock == DockPhase::Upload {
+            let from = self.acked_version(robot_id);
+            let cells = self.sync_robot(robot_id);
+            self.log_about(
+                robot_id,
+                Level::Info,
+                Category::Dock,
+                format!(
+                    "Robot {} docked: up {} cells {}E {}M | down v{}->v{} {} cells",
+                    robot_id, uploaded.0, uploaded.1, uploaded.2, from, self.map_version, cells
+                ),
+            );
+            self.fit_parts(robot_id, loadout);
+        } else if !loadout.is_empty() {
+            self.loadouts.insert(robot_id, loadout);
+        }
+
+        self.run_economy();
     }
 
-    fn merge_pending_diffs(&mut self) {
-        let Some(min_tick) = self.pending.iter().map(|r| r.tick).min() else {
+    /// Advances the build queue to the finalised tick and lets the spending
+    /// policy queue new builds from the stocks.
+    fn run_economy(&mut self) {
+        let now = self.finalized_tick.unwrap_or(0);
+        let elapsed = now.saturating_sub(self.economy_tick);
+        self.economy_tick = self.economy_tick.max(now);
+
+        let mut finished = self.economy.advance(elapsed);
+
+        let mut stocks = Stocks {
+            energy: self.energy_stock,
+            mineral: self.mineral_stock,
+        };
+        let queued = self.economy.plan(&mut stocks);
+        self.energy_stock = stocks.energy;
+        self.mineral_stock = stocks.mineral;
+        for build in &queued {
+            self.record(JournalEntry::Queue {
+                item: build.item.clone(),
+                energy: build.cost.energy,
+                mineral: build.cost.mineral,
+            });
+            self.log(
+                Level::Info,
+                Category::Economy,
+                format!(
+                    "Queued {} for {}E {}M ({} ticks)",
+                    build.item.label(),
+                    build.cost.energy,
+                    build.cost.mineral,
+                    build.cost.ticks
+                ),
+            );
+        }
+        finished.extend(self.economy.advance(0));
+
+        for item in &finished {
+            self.complete_build(item);
+        }
+        if elapsed > 0 {
+            self.economy.record_stocks(stocks);
+        }
+        if elapsed > 0 || !queued.is_empty() || !finished.is_empty() {
+            self.compact_journal();
+            let _ = self.tx_cmd.send(StationCmd::Economy(self.economy.status()));
+        }
+    }
+
+    /// Fits every spare part the docked robot can use.
+    fn fit_parts(&mut self, robot_id: usize, mut loadout: Loadout) {
+        let mut idx = 0;
+        while idx < self.parts.len() {
+            let Some(op) = retrofit_for(&self.parts[idx], &loadout) else {
+                idx += 1;
+                continue;
+            };
+            let item = self.parts.remove(idx);
+            match &op {
+                Retrofit::Install(module) => loadout.push((module.clone(), 1)),
+                Retrofit::Upgrade(module) => {
+                    if let Some(slot) = loadout.iter_mut().find(|(m, _)| m == module) {
+                        slot.1 += 1;
+                    }
+                }
+                Retrofit::Remove(_) => {}
+            }
+
+            self.record(JournalEntry::Fitted {
+                item: item.clone(),
+                robot_id,
+            });
+            self.log_about(
+                robot_id,
+                Level::Info,
+                Category::Economy,
+                format!("Fitted {} to robot {}", item.label(), robot_id),
+            );
+            let _ = self.tx_cmd.send(StationCmd::Retrofit { id: robot_id, op });
+        }
+        self.loadouts.insert(robot_id, loadout);
+    }
+
+    fn remove_module(&mut self, robot_id: usize, module: RobotModule) {
+        let op = Retrofit::Remove(module.clone());
+        let Some(loadout) = self.loadouts.get_mut(&robot_id) else {
+            self.log_about(
+                robot_id,
+                Level::Warn,
+                Category::Console,
+                format!("Robot {} has not docked here yet", robot_id),
+            );
+            return;
+        };
+        if let Err(err) = op.check(loadout) {
+            self.log_about(
+                robot_id,
+                Level::Warn,
+                Category::Console,
+                format!(
+                    "Cannot remove {:?} from robot {}: {:?}",
+                    module, robot_id, err
+                ),
+            );
+            return;
+        }
+        let level = loadout
+            .iter()
+            .find(|(m, _)| *m == module)
+            .map_or(1, |(_, level)| *level);
+        loadout.retain(|(m, _)| *m != module);
+
+        let mut refund = vec![BuildItem::Module(module.clone())];
+        if let Some(upgrade) = Upgrade::for_module(&module) {
+            refund.extend((1..level).map(|_| BuildItem::Upgrade(upgrade)));
+        }
+        for item in refund {
+            self.record(JournalEntry::Removed {
+                item: item.clone(),
+                robot_id,
+            });
+            self.parts.push(item);
+        }
+        self.log_about(
+            robot_id,
+            Level::Info,
+            Category::Console,
+            format!("Removed {:?} from robot {}", module, robot_id),
+        );
+        let _ = self.tx_cmd.send(StationCmd::Retrofit { id: robot_id, op });
+    }
+
+    fn complete_build(&mut self, item: &BuildItem) {
+        if *item == BuildItem::Outpost {
+            self.outposts_ready += 1;
+            self.record(JournalEntry::Built {
+                item: item.clone(),
+                robot_id: None,
+            });
+            return;
+        }
+        let BuildItem::Robot(modules) = item else {
+            self.parts.push(item.clone());
+            self.record(JournalEntry::Built {
+                item: item.clone(),
+                robot_id: None,
+            });
+            self.log(
+                Level::Info,
+                Category::Economy,
+                format!("Built {}", item.label()),
+            );
             return;
         };
 
+        let id = self.robot_ids.fetch_add(1, Ordering::SeqCst);
+        self.record(JournalEntry::Built {
+            item: item.clone(),
+            robot_id: Some(id),
+        });
+
+        // spread new robots over the docking zone; the coordinator clamps
+        // cells past the far edges of the map
+        let zone = self.site().zone(usize::MAX, usize::MAX);
+        self.launch(id, modules.clone(), zone[id % zone.len()]);
+    }
+
+    /// Announces robot `id` and sends it the master map.
+    fn launch(&mut self, id: usize, modules: Vec<RobotModule>, start_pos: (usize, usize)) {
+        self.loadouts.insert(
+            id,
+            modules.iter().map(|module| (module.clone(), 1)).collect(),
+        );
+        let _ = self.tx_cmd.send(StationCmd::Spawn {
+            id,
+            modules,
+            start_pos,
+        });
+
+        self.robot_versions.insert(id, 0);
+        self.sync_robot(id);
+    }
+
+    pub fn handle_request(&mut self, request: StationRequest) {
+        match request {
+            StationRequest::Spawn { modules, position } => {
+                let id = self.robot_ids.fetch_add(1, Ordering::SeqCst);
+                self.record(JournalEntry::Spawned { robot_id: id });
+                self.launch(id, modules, position);
+                self.compact_journal();
+                self.log_about(
+                    id,
+                    Level::Info,
+                    Category::Console,
+                    format!("Spawned robot {} at ({}, {})", id, position.0, position.1),
+                );
+            }
+            StationRequest::AdjustStocks { energy, mineral } => {
+                let mut stocks = self.stocks();
+                stocks.adjust(energy, mineral);
+                self.energy_stock = stocks.energy;
+                self.mineral_stock = stocks.mineral;
+                self.record(JournalEntry::Adjust { energy, mineral });
+                self.compact_journal();
+                self.log(
+                    Level::Info,
+                    Category::Console,
+                    format!(
+                        "Stocks set to {}E {}M",
+                        self.energy_stock, self.mineral_stock
+                    ),
+                );
+                self.run_economy();
+            }
+            StationRequest::Remove { robot_id, module } => self.remove_module(robot_id, module),
+            StationRequest::Save => {
+                if self.save() {
+                    self.log(
+                        Level::Info,
+                        Category::Journal,
+                        format!("Saved v{} to the journal", self.map_version),
+                    );
+                }
+            }
+            StationRequest::Query(query) => {
+                self.log(Level::Info, Category::Console, self.answer(query));
+            }
+        }
+    }
+
+    /// One-line answer to an operator query.
+    pub fn answer(&self, query: StationQuery) -> String {
+        let prefix = format!("Station {}:", self.id);
+        match query {
+            StationQuery::Stocks => format!(
+                "{} stocks {}E {}M",
+                prefix, self.energy_stock, self.mineral_stock
+            ),
+            StationQuery::Version => format!(
+                "{} master v{}, {} cells known",
+                prefix,
+                self.map_version,
+                self.master_map.len()
+            ),
+            StationQuery::Metrics => {
+                let metrics = self.metrics();
+                format!(
+                    "{} merged {} | late {} | duplicates {} | expired {} | pending {}",
+                    prefix,
+                    metrics.merged,
+                    metrics.late,
+                    metrics.duplicates,
+                    metrics.expired,
+                    metrics.pending
+                )
+            }
+            StationQuery::Queue => {
+                let queue: Vec<_> = self
+                    .economy
+                    .queue()
+                    .iter()
+                    .map(|build| format!("{} ({} ticks)", build.item.label(), build.remaining))
+                    .collect();
+                if queue.is_empty() {
+                    format!("{} build queue empty", prefix)
+                } else {
+                    format!("{} building {}", prefix, queue.join(", "))
+                }
+            }
+            StationQuery::Conflicts => {
+                let recent: Vec<_> = self
+                    .conflicts
+                    .iter()
+                    .rev()
+                    .take(3)
+                    .map(|c| {
+                        format!(
+                            "({}, {}) robot {} {:?}->{:?} {:?}",
+                            c.pos.0, c.pos.1, c.robot_id, c.found, c.proposed, c.resolution
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{} {} conflicts recorded{}{}",
+                    prefix,
+                    self.conflicts.len(),
+                    if recent.is_empty() { "" } else { ", latest: " },
+                    recent.join("; ")
+                )
+            }
+            StationQuery::Cell(pos) => {
+                match (self.master_map.get(&pos), self.cell_meta.get(&pos)) {
+                    (Some(tile), Some(meta)) => format!(
+                        "{} ({}, {}) is {:?} since v{} (tick {})",
+                        prefix, pos.0, pos.1, tile, meta.version, meta.tick
+                    ),
+                    (Some(tile), None) => {
+                        format!("{} ({}, {}) is {:?}", prefix, pos.0, pos.1, tile)
+                    }
+                    (None, _) => format!("{} ({}, {}) is unknown", prefix, pos.0, pos.1),
+                }
+            }
+            StationQuery::Robot(id) => format!(
+                "{} robot {} acked v{}, awaiting {}",
+                prefix,
+                id,
+                self.acked_version(id),
+                self.awaiting_ack(id)
+                    .map_or("nothing".to_string(), |version| format!("v{}", version))
+            ),
+        }
+    }
+
+    /// Changes between `version` and the current master version, or `None`
+    /// when that range has already fallen out of the history.
+    pub fn delta_since(&self, version: u64) -> Option<MapDiff> {
+        if version >= self.map_version {
+            return Some(MapDiff(Vec::new()));
+        }
+        let oldest = self.history.front().map(|(v, _)| *v)?;
+        if version + 1 < oldest {
+            return None;
+        }
+
+        let mut delta = MapDiff(Vec::new());
+        for (_, diff) in self.history.iter().filter(|(v, _)| *v > version) {
+            delta.merge(diff.clone());
+        }
+        Some(delta.compact())
+    }
+
+    /// Sends one batched delta bringing the robot from its acknowledged
+    /// version up to the current one, falling back to a full snapshot when it
+    /// is too far behind. Returns the number of cells sent.
+    pub fn sync_robot(&mut self, robot_id: usize) -> usize {
+        let known = self.acked_version(robot_id);
+        let diff = self.delta_since(known).unwrap_or_else(|| {
+            MapDiff(
+                self.master_map
+                    .iter()
+                    .map(|(&(r, c), &tile)| ((r, c), None, tile))
+                    .collect(),
+            )
+        });
+        let cells = diff.0.len();
+        let _ = self.tx_cmd.send(StationCmd::Snapshot {
+            id: robot_id as u32,
+            station: self.id,
+            version: self.map_version,
+            diff,
+        });
+        self.awaiting_ack.insert(robot_id, self.map_version);
+        cells
+    }
+
+    fn acknowledge(&mut self, robot_id: usize, version: u64) {
+        if self
+            .awaiting_ack
+            .get(&robot_id)
+            .is_some_and(|&expected| version >= expected)
+        {
+            self.awaiting_ack.remove(&robot_id);
+        }
+        let acked = self.robot_versions.entry(robot_id).or_insert(0);
+        *acked = (*acked).max(version);
+    }
+
+    /// Deduplicates the report and updates the late/dropped counters.
+    /// Returns whether it should be queued for merging.
+    fn accept(&mut self, report: &RobotReport) -> bool {
+        let (robot_id, tick) = (report.robot_id, report.tick);
+        if self
+            .newest_tick
+            .is_some_and(|newest| tick + DEDUP_HORIZON < newest)
+        {
+            self.metrics.expired += 1;
+            self.log_about(
+                robot_id,
+                Level::Warn,
+                Category::Merge,
+                format!(
+                    "Dropped expired report from robot {} (tick {})",
+                    robot_id, tick
+                ),
+            );
+            return false;
+        }
+        if !self.seen.insert((robot_id, tick, report.dock)) {
+            self.metrics.duplicates += 1;
+            self.log_about(
+                robot_id,
+                Level::Warn,
+                Category::Merge,
+                format!(
+                    "Dropped duplicate report from robot {} (tick {})",
+                    robot_id, tick
+                ),
+            );
+            return false;
+        }
+
+        if let Some(done) = self.finalized_tick.filter(|&done| tick <= done) {
+            self.metrics.late += 1;
+            self.log_about(
+                robot_id,
+                Level::Warn,
+                Category::Merge,
+                format!(
+                    "Late report from robot {} for tick {} (finalised up to {})",
+                    robot_id, tick, done
+                ),
+            );
+        }
+        self.newest_tick = Some(self.newest_tick.map_or(tick, |newest| newest.max(tick)));
+        true
+    }
+
+    /// Merges pending reports up to `watermark` (everything when `None`),
+    /// one version per tick in tick order.
+    fn finalize(&mut self, watermark: Option<u64>) {
+        let before = self.metrics.merged;
+        while let Some(tick) = self
+            .pending
+            .iter()
+            .map(|r| r.tick)
+            .filter(|&tick| watermark.is_none_or(|w| tick <= w))
+            .min()
+        {
+            self.merge_tick(tick);
+        }
+
+        if let Some(w) = watermark.or(self.newest_tick) {
+            self.finalized_tick = Some(self.finalized_tick.map_or(w, |done| done.max(w)));
+        }
+        if let Some(newest) = self.newest_tick {
+            self.seen
+                .retain(|&(_, tick, _)| tick + DEDUP_HORIZON >= newest);
+        }
+        if self.metrics.merged != before {
+            let _ = self.tx_cmd.send(StationCmd::MergeStats(self.metrics()));
+        }
+    }
+
+    fn merge_tick(&mut self, min_tick: u64) {
         let mut same_tick: Vec<RobotReport> = Vec::new();
         self.pending.retain(|r| {
             if r.tick == min_tick {
@@ -121,21 +970,36 @@ impl Station {
             }
         });
 
-        if same_tick.is_empty() {
+        self.metrics.merged += same_tick.len() as u64;
+
+        if same_tick
+            .iter()
+            .all(|r| r.map_diff.0.is_empty() && r.energy == 0 && r.mineral == 0)
+        {
             return;
         }
 
-        let mut cell_updates: HashMap<(usize, usize), (usize, Tile)> = HashMap::new();
+        let new_version = self.map_version + 1;
+        let mut cell_updates: Vec<((usize, usize), Option<Tile>, Tile)> = Vec::new();
+        let mut conflicts = 0;
+        let mut rejected = 0;
         let mut total_energy = 0;
         let mut total_mineral = 0;
 
-        for (arrival_idx, rep) in same_tick.iter().enumerate() {
-            for &((row, col), _before, after) in &rep.map_diff.0 {
-                let entry = cell_updates
-                    .entry((row, col))
-                    .or_insert((arrival_idx, after));
-                if arrival_idx > entry.0 {
-                    *entry = (arrival_idx, after);
+        for rep in &same_tick {
+            for &(pos, before, after) in &rep.map_diff.0 {
+                let previous = self.master_map.get(&pos).copied();
+                match self.merge_entry(rep, pos, before, after, new_version) {
+                    Some(Resolution::Applied) => conflicts += 1,
+                    Some(Resolution::Rejected) => {
+                        conflicts += 1;
+                        rejected += 1;
+                        continue;
+                    }
+                    None => {}
+                }
+                if previous != Some(after) && self.master_map.get(&pos) == Some(&after) {
+                    cell_updates.push((pos, previous, after));
                 }
             }
             self.energy_stock += rep.energy;
@@ -144,32 +1008,255 @@ impl Station {
             total_mineral += rep.mineral;
         }
 
+        let cell_updates = MapDiff(cell_updates).compact();
+        self.record(JournalEntry::Merge {
+            version: new_version,
+            tick: min_tick,
+            diff: cell_updates.clone(),
+        });
         if total_energy > 0 || total_mineral > 0 {
+            self.record(JournalEntry::Deposit {
+                energy: total_energy,
+                mineral: total_mineral,
+            });
             let _ = self.tx_cmd.send(StationCmd::ResourceUpdate {
                 energy: total_energy,
                 mineral: total_mineral,
             });
         }
 
-        for ((row, col), &(_, tile_after)) in &cell_updates {
-            self.master_map.insert((*row, *col), tile_after);
+        if conflicts > 0 {
+            self.log(
+                Level::Warn,
+                Category::Merge,
+                format!(
+                    "{} conflicts at tick {} ({} rejected)",
+                    conflicts, min_tick, rejected
+                ),
+            );
+        }
+
+        self.log(
+            Level::Debug,
+            Category::Merge,
+            format!(
+                "Merged {} diffs (tick {}) | stocks {}E {}M",
+                same_tick.len(),
+                min_tick,
+                self.energy_stock,
+                self.mineral_stock
+            ),
+        );
 
-            let _ = self.tx_cmd.send(StationCmd::Snapshot {
-                id: 0,
-                version: self.map_version,
-                diff: MapDiff(vec![((*row, *col), None, tile_after)]),
-            });
+        self.map_version = new_version;
+        self.publish(cell_updates);
+    }
+
+    /// Applies changes already merged by a linked station. They are settled
+    /// there, so they overwrite master without conflict checks. Returns the
+    /// number of cells that changed.
+    pub fn apply_link(&mut self, from: usize, diff: &MapDiff) -> usize {
+        let updates: Vec<_> = diff
+            .0
+            .iter()
+            .filter_map(|&(pos, _, after)| {
+                let previous = self.master_map.get(&pos).copied();
+                (previous != Some(after)).then_some((pos, previous, after))
+            })
+            .collect();
+        if updates.is_empty() {
+            return 0;
+        }
+
+        let version = self.map_version + 1;
+        let tick = self.finalized_tick.unwrap_or(0);
+        for &(pos, previous, after) in &updates {
+            self.master_map.insert(pos, after);
+            self.cell_meta.insert(
+                pos,
+                CellMeta {
+                    version,
+                    tick,
+                    collected: matches!(previous, Some(Tile::Energy | Tile::Mineral))
+                        && after == Tile::Empty,
+                },
+            );
         }
 
-        let _ = self.tx_cmd.send(StationCmd::Log(format!(
-            "Merged {} diffs (tick {}) | stocks {}E {}M",
-            same_tick.len(),
-            min_tick,
-            self.energy_stock,
-            self.mineral_stock
-        )));
+        let updates = MapDiff(updates);
+        self.record(JournalEntry::Merge {
+            version,
+            tick,
+            diff: updates.clone(),
+        });
+        let cells = updates.0.len();
+        self.map_version = version;
+        self.log(
+            Level::Debug,
+            Category::Network,
+            format!(
+                "Synced {} cells from station {} (v{})",
+                cells, from, version
+            ),
+        );
+        self.publish(updates);
+        cells
+    }
 
-        self.map_version += 1;
+    /// Keeps the changes that made the current version for robot deltas and
+    /// announces them.
+    fn publish(&mut self, diff: MapDiff) {
+        self.history.push_back((self.map_version, diff.clone()));
+        while self.history.len() > self.history_len {
+            self.history.pop_front();
+        }
+        self.compact_journal();
+        let _ = self.tx_cmd.send(StationCmd::MasterDiff {
+            station: self.id,
+            version: self.map_version,
+            diff,
+        });
         let _ = self.tx_cmd.send(StationCmd::Version(self.map_version));
     }
+
+    /// Log record from this station, dated to the newest report's tick.
+    fn log_record(&self, level: Level, category: Category, message: String) -> LogRecord {
+        let tick = self.newest_tick.unwrap_or(0);
+        LogRecord::new(tick, level, Source::Station(self.id), category, message)
+    }
+
+    fn log(&self, level: Level, category: Category, message: impl Into<String>) {
+        let record = self.log_record(level, category, message.into());
+        let _ = self.tx_cmd.send(StationCmd::Log(record));
+    }
+
+    fn log_about(&self, robot: usize, level: Level, category: Category, message: String) {
+        let record = self.log_record(level, category, message).about(robot);
+        let _ = self.tx_cmd.send(StationCmd::Log(record));
+    }
+
+    fn record(&mut self, entry: JournalEntry) {
+        let Some(journal) = self.journal.as_mut() else {
+            return;
+        };
+        if let Err(err) = journal.append(&entry) {
+            self.log(
+                Level::Error,
+                Category::Journal,
+                format!("Journal write failed: {}", err),
+            );
+        }
+    }
+
+    /// Checkpoints the journal; false when there is none or it failed.
+    fn save(&mut self) -> bool {
+        let state = self.state();
+        match self.journal.as_mut().map(|j| j.compact(&state)) {
+            Some(Ok(())) => true,
+            Some(Err(err)) => {
+                self.log(
+                    Level::Error,
+                    Category::Journal,
+                    format!("Journal save failed: {}", err),
+                );
+                false
+            }
+            None => {
+                self.log(Level::Warn, Category::Journal, "No journal to save to");
+                false
+            }
+        }
+    }
+
+    fn compact_journal(&mut self) {
+        if !self.journal.as_ref().is_some_and(Journal::needs_compaction) {
+            return;
+        }
+        let state = self.state();
+        if let Some(Err(err)) = self.journal.as_mut().map(|j| j.compact(&state)) {
+            self.log(
+                Level::Error,
+                Category::Journal,
+                format!("Journal compaction failed: {}", err),
+            );
+        }
+    }
+
+    /// Merges one diff entry into the master map.
+    ///
+    /// An entry conflicts when its "before" value does not match master, or
+    /// when it is a plain observation of a cell master changed after the
+    /// report's base version. Conflicts are resolved as follows: a collection
+    /// always wins, nothing overrides a collection, otherwise the newest tick
+    /// wins. Returns the resolution when the entry was a conflict.
+    fn merge_entry(
+        &mut self,
+        rep: &RobotReport,
+        pos: (usize, usize),
+        before: Option<Tile>,
+        after: Tile,
+        version: u64,
+    ) -> Option<Resolution> {
+        let is_collection =
+            matches!(before, Some(Tile::Energy | Tile::Mineral)) && after == Tile::Empty;
+        let meta = self.cell_meta.get(&pos).copied().unwrap_or_default();
+
+        let mut resolution = None;
+        if let Some(&current) = self.master_map.get(&pos) {
+            if current == after {
+                return None;
+            }
+
+            let conflict = match before {
+                Some(expected) => expected != current,
+                None => meta.version > rep.base_version,
+            };
+            if conflict {
+                let applied = is_collection || (!meta.collected && rep.tick >= meta.tick);
+                let outcome = if applied {
+                    Resolution::Applied
+                } else {
+                    Resolution::Rejected
+                };
+                if self.conflicts.len() >= MAX_CONFLICT_RECORDS {
+                    self.conflicts.pop_front();
+                }
+                self.conflicts.push_back(ConflictRecord {
+                    pos,
+                    robot_id: rep.robot_id,
+                    tick: rep.tick,
+                    base_version: rep.base_version,
+                    expected: before,
+                    found: current,
+                    proposed: after,
+                    resolution: outcome,
+                });
+                if !applied {
+                    return Some(outcome);
+                }
+                resolution = Some(outcome);
+            }
+        }
+
+        self.master_map.insert(pos, after);
+        self.cell_meta.insert(
+            pos,
+            CellMeta {
+                version,
+                tick: rep.tick,
+                collected: is_collection,
+            },
+        );
+        resolution
+    }
+}
+
+/// Retrofit that installs `part` on a robot with `loadout`, if it fits.
+fn retrofit_for(part: &BuildItem, loadout: &Loadout) -> Option<Retrofit> {
+    let op = match part {
+        BuildItem::Module(module) => Retrofit::Install(module.clone()),
+        BuildItem::Upgrade(upgrade) => Retrofit::Upgrade(upgrade.module()),
+        BuildItem::Robot(_) | BuildItem::Outpost => return None,
+    };
+    op.check(loadout).is_ok().then_some(op)
 }
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+
+/// Samples kept before the oldest are dropped.
+pub const TIMELINE_LEN: usize = 1000;
+
+/// Run-wide figures at one tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sample {
+    pub tick: u64,
+    pub energy_stock: u64,
+    pub mineral_stock: u64,
+    /// Resources delivered to stations since the start.
+    pub energy_collected: u64,
+    pub mineral_collected: u64,
+    /// Share of the map in the main station's master map, in percent.
+    pub explored: f64,
+    pub robots: u64,
+    /// Reports merged since the previous sample.
+    pub merges: u64,
+}
+
+/// One line of a recording: the fields in declaration order.
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {} {}",
+            self.tick,
+            self.energy_stock,
+            self.mineral_stock,
+            self.energy_collected,
+            self.mineral_collected,
+            self.explored,
+            self.robots,
+            self.merges
+        )
+    }
+}
+
+fn field<T: FromStr>(fields: &mut SplitWhitespace, what: &str) -> Result<T, String> {
+    let field = fields.next().ok_or_else(|| format!("missing {}", what))?;
+    field
+        .parse()
+        .map_err(|_| format!("invalid {} {:?}", what, field))
+}
+
+impl Sample {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut fields = line.split_whitespace();
+        Ok(Self {
+            tick: field(&mut fields, "tick")?,
+            energy_stock: field(&mut fields, "energy stock")?,
+            mineral_stock: field(&mut fields, "mineral stock")?,
+            energy_collected: field(&mut fields, "energy collected")?,
+            mineral_collected: field(&mut fields, "mineral collected")?,
+            explored: field(&mut fields, "explored share")?,
+            robots: field(&mut fields, "robot count")?,
+            merges: field(&mut fields, "merge count")?,
+        })
+    }
+}
+
+/// History the charts are drawn from. Fed live by the app, or from a
+/// recording of an earlier run when replaying.
+#[derive(Debug, Default)]
+pub struct Timeline {
+    samples: VecDeque<Sample>,
+    /// Cumulative merge count at the last sample.
+    merged: u64,
+    recording: Option<File>,
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes every sample to the file at `path` as well, replacing it.
+    pub fn with_recording(mut self, path: impl AsRef<Path>) -> io::Result<Self> {
+        self.recording = Some(File::create(path)?);
+        Ok(self)
+    }
+
+    /// Samples recorded by [`Timeline::with_recording`], oldest first.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Sample>> {
+        fs::read_to_string(path)?
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                Sample::parse(line).map_err(|reason| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("timeline line {}: {}", idx + 1, reason),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn push(&mut self, sample: Sample) {
+        // like the log mirror, a failing recording is dropped
+        if let Some(file) = self.recording.as_mut() {
+            if writeln!(file, "{}", sample).is_err() {
+                self.recording = None;
+            }
+        }
+        if self.samples.len() >= TIMELINE_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Records a sample, turning the station's cumulative `merged` count
+    /// into merges per sample.
+    pub fn record(&mut self, sample: Sample, merged: u64) {
+        let merges = merged.saturating_sub(self.merged);
+        self.merged = merged;
+        self.push(Sample { merges, ..sample });
+    }
+
+    pub fn samples(&self) -> &VecDeque<Sample> {
+        &self.samples
+    }
+
+    pub fn latest(&self) -> Option<&Sample> {
+        self.samples.back()
+    }
+
+    /// `(tick, value)` points for a chart.
+    pub fn points(&self, value: impl Fn(&Sample) -> f64) -> Vec<(f64, f64)> {
+        self.samples
+            .iter()
+            .map(|sample| (sample.tick as f64, value(sample)))
+            .collect()
+    }
+
+    /// First and last tick, at least one tick apart.
+    pub fn tick_bounds(&self) -> [f64; 2] {
+        let first = self.samples.front().map_or(0, |s| s.tick) as f64;
+        let last = self.samples.back().map_or(0, |s| s.tick) as f64;
+        [first, last.max(first + 1.0)]
+    }
+}
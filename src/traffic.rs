@@ -0,0 +1,207 @@
+use crate::map::{Map, Tile};
+use crate::robot::RobotStatus;
+use crate::station::StationSite;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// What the traffic controller tells a robot this tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DockOrder {
+    /// Not docking: move as usual.
+    #[default]
+    Free,
+    /// Admitted: go to this slot and dock.
+    Slot((usize, usize)),
+    /// Queued: wait on this cell of the ring around the zone.
+    Wait((usize, usize)),
+    /// Queued with the ring full: stay put.
+    Hold,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrafficStats {
+    /// Robots waiting for a slot.
+    pub queued: usize,
+    /// Robots holding a slot.
+    pub docking: usize,
+    pub admitted: u64,
+    /// Ticks admitted robots spent queued, summed.
+    pub total_wait: u64,
+}
+
+impl TrafficStats {
+    pub fn avg_wait(&self) -> f64 {
+        if self.admitted == 0 {
+            return 0.0;
+        }
+        self.total_wait as f64 / self.admitted as f64
+    }
+}
+
+/// Zone cells kept free for robots leaving the station: a straight line
+/// from the station towards the middle of the map, one cell past the zone.
+pub fn exit_lane(site: &StationSite, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+    let (row, col) = site.position;
+    let (dr, dc): (isize, isize) = if rows >= cols {
+        (if row < rows / 2 { 1 } else { -1 }, 0)
+    } else {
+        (0, if col < cols / 2 { 1 } else { -1 })
+    };
+    (1..=site.dock_radius as isize + 1)
+        .map(|k| (row as isize + dr * k, col as isize + dc * k))
+        .take_while(|&(r, c)| r >= 0 && c >= 0 && (r as usize) < rows && (c as usize) < cols)
+        .map(|(r, c)| (r as usize, c as usize))
+        .collect()
+}
+
+/// Cells just outside the docking zone where queued robots wait.
+pub fn waiting_ring(site: &StationSite, rows: usize, cols: usize) -> Vec<(usize, usize)> {
+    let outer = StationSite {
+        dock_radius: site.dock_radius + 1,
+        ..*site
+    };
+    outer
+        .zone(rows, cols)
+        .into_iter()
+        .filter(|&cell| !site.in_zone(cell))
+        .collect()
+}
+
+/// Admits returning robots to docking slots in priority order and parks
+/// the rest on the waiting ring.
+#[derive(Debug, Default)]
+pub struct TrafficController {
+    /// Slot held by each admitted robot, with its station.
+    admitted: HashMap<usize, (usize, (usize, usize))>,
+    /// Tick each queued robot was first refused a slot.
+    waiting_since: HashMap<usize, u64>,
+    stats: TrafficStats,
+}
+
+impl TrafficController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> TrafficStats {
+        self.stats
+    }
+
+    /// Orders for every returning robot. Higher cargo goes first, then the
+    /// longest wait, then the nearest robot.
+    pub fn plan(
+        &mut self,
+        sites: &[StationSite],
+        robots: &[RobotStatus],
+        map: &Map,
+        tick: u64,
+    ) -> HashMap<usize, DockOrder> {
+        let returning: HashMap<usize, &RobotStatus> = robots
+            .iter()
+            .filter(|r| r.returning_to.is_some())
+            .map(|r| (r.id, r))
+            .collect();
+        // docked robots go back to exploring and give up their slot
+        self.admitted.retain(|id, (station, _)| {
+            returning
+                .get(id)
+                .is_some_and(|r| r.returning_to == Some(*station))
+        });
+        self.waiting_since
+            .retain(|id, _| returning.contains_key(id));
+
+        let (rows, cols) = (map.grid.len(), map.cols);
+        let open = |cell: &(usize, usize)| map.grid[cell.0][cell.1] != Tile::Obstacle;
+        let mut orders = HashMap::new();
+        let mut queued = 0;
+
+        for site in sites {
+            let lane: HashSet<_> = exit_lane(site, rows, cols).into_iter().collect();
+            let held: HashSet<_> = self
+                .admitted
+                .values()
+                .filter(|(station, _)| *station == site.id)
+                .map(|&(_, cell)| cell)
+                .collect();
+            // robots without a slot block the cell they stand on
+            let standing: HashMap<(usize, usize), usize> = robots
+                .iter()
+                .filter(|r| !self.admitted.contains_key(&r.id))
+                .map(|r| (r.position, r.id))
+                .collect();
+            let taken = |cell: &(usize, usize), by: usize| {
+                standing.get(cell).is_some_and(|&other| other != by)
+            };
+            let mut free_slots: Vec<_> = site
+                .zone(rows, cols)
+                .into_iter()
+                .filter(|cell| open(cell) && !lane.contains(cell) && !held.contains(cell))
+                .collect();
+
+            let mut waiting: Vec<&RobotStatus> = returning
+                .values()
+                .filter(|r| r.returning_to == Some(site.id) && !self.admitted.contains_key(&r.id))
+                .copied()
+                .collect();
+            waiting.sort_by_key(|r| {
+                let since = self.waiting_since.get(&r.id).copied().unwrap_or(tick);
+                let distance =
+                    r.position.0.abs_diff(site.position.0) + r.position.1.abs_diff(site.position.1);
+                (Reverse(r.cargo), since, distance, r.id)
+            });
+
+            let mut refused = Vec::new();
+            for robot in waiting {
+                let nearest = free_slots
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| !taken(cell, robot.id))
+                    .min_by_key(|(_, cell)| {
+                        cell.0.abs_diff(robot.position.0) + cell.1.abs_diff(robot.position.1)
+                    })
+                    .map(|(idx, _)| idx);
+                match nearest {
+                    Some(idx) => {
+                        let slot = free_slots.remove(idx);
+                        let since = self.waiting_since.remove(&robot.id).unwrap_or(tick);
+                        self.admitted.insert(robot.id, (site.id, slot));
+                        self.stats.admitted += 1;
+                        self.stats.total_wait += tick - since;
+                    }
+                    None => {
+                        self.waiting_since.entry(robot.id).or_insert(tick);
+                        refused.push(robot);
+                    }
+                }
+            }
+
+            let mut ring: Vec<_> = waiting_ring(site, rows, cols)
+                .into_iter()
+                .filter(|cell| open(cell) && !lane.contains(cell))
+                .collect();
+            for robot in &refused {
+                let spot = ring
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| !taken(cell, robot.id))
+                    .min_by_key(|(_, cell)| {
+                        cell.0.abs_diff(robot.position.0) + cell.1.abs_diff(robot.position.1)
+                    })
+                    .map(|(idx, _)| idx);
+                let order = match spot {
+                    Some(idx) => DockOrder::Wait(ring.remove(idx)),
+                    None => DockOrder::Hold,
+                };
+                orders.insert(robot.id, order);
+            }
+            queued += refused.len();
+        }
+
+        for (&id, &(_, slot)) in &self.admitted {
+            orders.insert(id, DockOrder::Slot(slot));
+        }
+        self.stats.queued = queued;
+        self.stats.docking = self.admitted.len();
+        orders
+    }
+}
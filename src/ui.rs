@@ -1,88 +1,454 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Scrollbar, ScrollbarState, Table},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, GraphType, Paragraph, Row, Scrollbar,
+        ScrollbarState, Sparkline, Table,
+    },
     Frame,
 };
 
 use crate::app::App;
-use crate::map::Tile;
-use std::thread;
-use std::time::Duration;
+use rust_project::biome::Biome;
+use rust_project::knowledge::{self, MapLayer};
+use rust_project::log::{Level, LogRecord};
+use rust_project::map::Tile;
+use rust_project::robot::Robot;
+use rust_project::viewport;
+use std::collections::{HashMap, HashSet};
+
+/// Characters per map cell, and between cells.
+const CELL_WIDTH: u16 = 3;
+const CELL_SPACING: u16 = 1;
+/// Largest minimap, rows by columns, borders included.
+const MINIMAP_MAX: (u16, u16) = (12, 24);
 
 fn make_state(content_len: usize, view_height: u16, offset: usize) -> ScrollbarState {
     ScrollbarState::new(content_len)
         .position(offset.min(content_len.saturating_sub(view_height as usize)))
 }
 
-pub fn render(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
+fn biome_color(biome: Biome) -> Color {
+    match biome {
+        Biome::Plains => Color::Reset,
+        Biome::Highlands => Color::Rgb(58, 42, 28),
+        Biome::GeothermalVents => Color::Rgb(70, 24, 18),
+        Biome::IceSheet => Color::Rgb(24, 42, 70),
+    }
+}
+
+/// Blue through green and yellow to red as `heat` goes from 0 to 1.
+fn heat_color(heat: f64) -> Color {
+    let stops = [(0, 0, 160), (0, 160, 80), (220, 200, 0), (200, 0, 0)];
+    let scaled = heat.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+    let idx = (scaled as usize).min(stops.len() - 2);
+    let t = scaled - idx as f64;
+    let lerp = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    let (from, to) = (stops[idx], stops[idx + 1]);
+    Color::Rgb(lerp(from.0, to.0), lerp(from.1, to.1), lerp(from.2, to.2))
+}
+
+fn tile_glyph(tile: Tile, dim: bool) -> (&'static str, Style) {
+    let (symbol, color) = match tile {
+        Tile::Empty => (" · ", Color::DarkGray),
+        Tile::Obstacle => (" # ", Color::Red),
+        Tile::Energy => (" E ", Color::Yellow),
+        Tile::Mineral => (" M ", Color::Blue),
+        Tile::Science => (" S ", Color::Green),
+    };
+    let style = Style::default().fg(color);
+    if dim {
+        (symbol, style.add_modifier(Modifier::DIM))
+    } else {
+        (symbol, style)
+    }
+}
+
+fn render_economy(f: &mut Frame, app: &App, area: Rect) {
+    let economy = &app.economy;
+    let block = Block::default()
+        .title(format!("Build queue ({})", economy.policy))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),
+            Constraint::Length(2),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    let queue_lines: Vec<Line> = if economy.queue.is_empty() {
+        vec![Line::from("(idle)")]
+    } else {
+        economy
+            .queue
+            .iter()
+            .map(|build| {
+                Line::from(format!(
+                    "{} {}/{}",
+                    build.item.label(),
+                    build.cost.ticks - build.remaining,
+                    build.cost.ticks
+                ))
+            })
+            .collect()
+    };
+    f.render_widget(Paragraph::new(queue_lines), rows[0]);
+
+    for (trend, name, color, row) in [
+        (&economy.energy_trend, "Energy", Color::Yellow, rows[1]),
+        (&economy.mineral_trend, "Mineral", Color::Magenta, rows[2]),
+    ] {
+        let latest = trend.last().copied().unwrap_or(0);
+        let recent = &trend[trend.len().saturating_sub(row.width as usize)..];
+        let sparkline = Sparkline::default()
+            .block(Block::default().title(format!("{} {}", name, latest)))
+            .data(recent)
+            .style(Style::default().fg(color));
+        f.render_widget(sparkline, row);
+    }
+}
+
+fn tile_info(app: &App, cell: (usize, usize)) -> String {
+    let tile = app.map.grid[cell.0][cell.1];
+    let here = app
+        .robots
+        .iter()
+        .find(|r| r.position == cell)
+        .map_or(String::new(), |r| format!(", robot #{} here", r.id));
+    let known = app.robots_knowing(cell);
+    let known = if known.is_empty() {
+        "nobody".to_string()
+    } else {
+        known
+            .iter()
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let master = app.master_updates.get(&cell).map_or(
+        "never downloaded".to_string(),
+        |(station, version, tick)| format!("station {} v{} at tick {}", station, version, tick),
+    );
+    format!(
+        "Cursor ({}, {}): {:?}{}\n  Known by: {}\n  Master: {}\n",
+        cell.0, cell.1, tile, here, known, master
+    )
+}
+
+fn robot_detail(app: &App, r: &Robot) -> String {
+    let modules = r
+        .modules
+        .iter()
+        .map(|m| format!("{:?} L{}", m, r.level(m)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let target = r.target.map_or("none".to_string(), |(row, col)| {
+        format!("({}, {})", row, col)
+    });
+    let actions = r
+        .actions
+        .iter()
+        .rev()
+        .map(|(tick, action)| format!("    t{} {}", tick, action))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "Robot #{} at ({}, {})  {:?}\n  Home: {}\n  Cargo: {} energy, {} mineral of {}\n  Modules: [{}]\n  Target: {}  Path: {} cells\n  Recent actions:\n{}\n",
+        r.id,
+        r.position.0,
+        r.position.1,
+        r.state,
+        r.home(&app.stations)
+            .map_or("-".to_string(), |site| format!("station {}", site.id)),
+        r.energy_collected,
+        r.mineral_collected,
+        r.capabilities().payload,
+        modules,
+        target,
+        r.path.len(),
+        actions
+    )
+}
+
+fn main_chunks(size: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
         .direction(Direction::Horizontal)
         .margin(1)
         .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
-        .split(f.size());
+        .split(size)
+}
 
-    let map_grid: Vec<Row> = app
-        .map
-        .grid
-        .iter()
-        .enumerate()
-        .map(|(row_idx, row)| {
-            let cells: Vec<Cell> = row
-                .iter()
-                .enumerate()
-                .map(|(col_idx, tile)| {
-                    let (symbol, style) =
-                        if app.robots.iter().any(|r| r.position == (row_idx, col_idx)) {
-                            (
-                                " 🤖 ",
-                                Style::default()
-                                    .fg(Color::Cyan)
-                                    .add_modifier(Modifier::BOLD),
-                            )
-                        } else if app
-                            .robots
-                            .iter()
-                            .any(|r| r.known_map.contains_key(&(row_idx, col_idx)))
+fn right_chunks(area: Rect) -> std::rc::Rc<[Rect]> {
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ])
+        .split(area)
+}
+
+/// Lines the log panel shows for a terminal of `size`.
+pub fn log_view_height(size: Rect) -> usize {
+    let panel = right_chunks(main_chunks(size)[1])[1];
+    Block::default().borders(Borders::ALL).inner(panel).height as usize
+}
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Debug => Color::DarkGray,
+        Level::Info => Color::White,
+        Level::Warn => Color::Yellow,
+        Level::Error => Color::Red,
+    }
+}
+
+fn log_line(record: &LogRecord) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(
+            format!("{:>3} ", record.tick),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            format!("{} ", record.source),
+            Style::default().fg(Color::Cyan),
+        ),
+        Span::styled(
+            format!("[{}] ", record.category.name()),
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::styled(
+            record.message.clone(),
+            Style::default().fg(level_color(record.level)),
+        ),
+    ])
+}
+
+/// Map cells that fit in the PlanetMap panel for a terminal of `size`,
+/// rows by columns.
+pub fn map_view_size(size: Rect) -> (usize, usize) {
+    let inner = Block::default()
+        .borders(Borders::ALL)
+        .inner(main_chunks(size)[0]);
+    let width = inner.width + CELL_SPACING;
+    (
+        inner.height as usize,
+        (width / (CELL_WIDTH + CELL_SPACING)) as usize,
+    )
+}
+
+/// Scaled-down map in the top-right corner of `area`, with the viewport
+/// highlighted.
+fn render_minimap(f: &mut Frame, app: &App, area: Rect) {
+    let (rows, cols) = (app.map.grid.len(), app.map.cols);
+    let width = (area.width / 3).clamp(4, MINIMAP_MAX.1);
+    let height = (area.height / 3).clamp(4, MINIMAP_MAX.0);
+    let rect = Rect::new(
+        area.x + area.width.saturating_sub(width + 1),
+        area.y + 1,
+        width.min(area.width),
+        height.min(area.height),
+    );
+    let block = Block::default().title("Minimap").borders(Borders::ALL);
+    let inner = block.inner(rect);
+    if inner.width == 0 || inner.height == 0 {
+        return;
+    }
+    let step_rows = rows.div_ceil(inner.height as usize).max(1);
+    let step_cols = cols.div_ceil(inner.width as usize).max(1);
+
+    let lines: Vec<Line> = (0..inner.height as usize)
+        .map(|y| {
+            let spans: Vec<Span> = (0..inner.width as usize)
+                .map(|x| {
+                    let top = (y * step_rows, x * step_cols);
+                    let cells: Vec<(usize, usize)> = (top.0..(top.0 + step_rows).min(rows))
+                        .flat_map(|r| (top.1..(top.1 + step_cols).min(cols)).map(move |c| (r, c)))
+                        .collect();
+                    let (symbol, fg) = if cells.is_empty() {
+                        (" ", Color::Reset)
+                    } else if app.robots.iter().any(|r| cells.contains(&r.position)) {
+                        ("•", Color::Cyan)
+                    } else if app
+                        .stations
+                        .iter()
+                        .any(|site| cells.contains(&site.position))
+                    {
+                        ("⌂", Color::Magenta)
+                    } else {
+                        match viewport::dominant_tile(
+                            cells.iter().map(|&(r, c)| app.map.grid[r][c]),
+                        ) {
+                            Some(Tile::Obstacle) => ("#", Color::Red),
+                            Some(Tile::Empty) | None => ("·", Color::DarkGray),
+                            Some(_) => ("+", Color::Yellow),
+                        }
+                    };
+                    let style = Style::default().fg(fg);
+                    let style = if cells.iter().any(|&cell| app.viewport.contains(cell)) {
+                        style.bg(Color::Indexed(240))
+                    } else {
+                        style
+                    };
+                    Span::styled(symbol, style)
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(lines).block(block), rect);
+}
+
+fn render_map(f: &mut Frame, app: &App, area: Rect) {
+    let fov = app.fov_overlay();
+    let (path, target): (HashSet<_>, _) = match app.selected() {
+        Some(robot) if app.show_detail => (robot.path.iter().copied().collect(), robot.target),
+        _ => (HashSet::new(), None),
+    };
+    let robot_belief = app.selected().map(|robot| &robot.known_map);
+    let (stale_station, stale_robot) = match app.layer {
+        MapLayer::Diff => (
+            knowledge::stale_cells(&app.map, &app.master_map),
+            robot_belief.map_or_else(HashSet::new, |known| {
+                knowledge::stale_cells(&app.map, known)
+            }),
+        ),
+        _ => (HashSet::new(), HashSet::new()),
+    };
+    let masked = match app.layer {
+        MapLayer::Unexplored => knowledge::unexplored(
+            &app.map,
+            std::iter::once(&app.master_map).chain(app.robots.iter().map(|r| &r.known_map)),
+        ),
+        _ => HashSet::new(),
+    };
+    let heat = app
+        .heat_layer
+        .map_or_else(HashMap::new, |layer| app.heatmaps.intensities(layer));
+    let layer_title = match app.layer {
+        MapLayer::Truth => String::new(),
+        MapLayer::Station => format!(
+            " [station: {} known, {} stale]",
+            app.master_map.len(),
+            knowledge::stale_cells(&app.map, &app.master_map).len()
+        ),
+        MapLayer::Robot => match app.selected() {
+            Some(robot) => format!(
+                " [robot #{}: {} known, {} stale]",
+                robot.id,
+                robot.known_map.len(),
+                knowledge::stale_cells(&app.map, &robot.known_map).len()
+            ),
+            None => " [robot: none]".to_string(),
+        },
+        MapLayer::Diff => format!(
+            " [diff: station {} stale, robot {} stale]",
+            stale_station.len(),
+            stale_robot.len()
+        ),
+        MapLayer::Unexplored => format!(" [unexplored: {} cells]", masked.len()),
+    };
+    let (rows, cols) = (app.map.grid.len(), app.map.cols);
+    let viewport = &app.viewport;
+    let (screen_rows, screen_cols) = viewport.screen_size(rows, cols);
+    let map_grid: Vec<Row> = (0..screen_rows)
+        .map(|screen_row| {
+            let cells: Vec<Cell> = (0..screen_cols)
+                .map(|screen_col| {
+                    let block = viewport.block((screen_row, screen_col), rows, cols);
+                    let any = |cells: &HashSet<(usize, usize)>| {
+                        block.iter().any(|cell| cells.contains(cell))
+                    };
+                    let (symbol, style) = if app.robots.iter().any(|r| block.contains(&r.position))
+                    {
+                        (
+                            " 🤖 ",
+                            Style::default()
+                                .fg(Color::Cyan)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                    } else if app
+                        .stations
+                        .iter()
+                        .any(|site| block.contains(&site.position))
+                    {
+                        (
+                            " ⌂ ",
+                            Style::default()
+                                .fg(Color::Magenta)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                    } else if block.iter().all(|cell| masked.contains(cell)) {
+                        ("   ", Style::default().bg(Color::Indexed(236)))
+                    } else {
+                        let believed =
+                            viewport::dominant_tile(block.iter().filter_map(|&(row, col)| {
+                                match app.layer {
+                                    MapLayer::Station => app.master_map.get(&(row, col)).copied(),
+                                    MapLayer::Robot => robot_belief
+                                        .and_then(|known| known.get(&(row, col)))
+                                        .copied(),
+                                    _ => Some(app.map.grid[row][col]),
+                                }
+                            }));
+                        // the truth layer dims what robots have already seen
+                        let dim = app.layer == MapLayer::Truth
+                            && block.iter().any(|cell| {
+                                app.robots.iter().any(|r| r.known_map.contains_key(cell))
+                            });
+                        match believed {
+                            Some(tile) => tile_glyph(tile, dim),
+                            None => ("   ", Style::default()),
+                        }
+                    };
+
+                    let hottest = block
+                        .iter()
+                        .filter_map(|cell| heat.get(cell).copied())
+                        .reduce(f64::max);
+                    let style = if target.is_some_and(|target| block.contains(&target)) {
+                        style.bg(Color::Indexed(28))
+                    } else if any(&path) {
+                        style.bg(Color::Indexed(24))
+                    } else if let Some(value) = hottest {
+                        style.bg(heat_color(value))
+                    } else if any(&stale_station) {
+                        style.bg(Color::Indexed(52))
+                    } else if any(&stale_robot) {
+                        style.bg(Color::Indexed(94))
+                    } else if any(&fov) {
+                        style.bg(Color::Indexed(238))
+                    } else if app
+                        .stations
+                        .iter()
+                        .any(|site| block.iter().any(|&cell| site.in_zone(cell)))
+                    {
+                        style.bg(Color::Indexed(53))
+                    } else {
+                        match block
+                            .first()
+                            .and_then(|&(row, col)| app.biomes.get(row, col))
                         {
-                            match tile {
-                                Tile::Empty => (
-                                    " · ",
-                                    Style::default()
-                                        .fg(Color::DarkGray)
-                                        .add_modifier(Modifier::DIM),
-                                ),
-                                Tile::Obstacle => (
-                                    " # ",
-                                    Style::default().fg(Color::Red).add_modifier(Modifier::DIM),
-                                ),
-                                Tile::Energy => (
-                                    " E ",
-                                    Style::default()
-                                        .fg(Color::Yellow)
-                                        .add_modifier(Modifier::DIM),
-                                ),
-                                Tile::Mineral => (
-                                    " M ",
-                                    Style::default().fg(Color::Blue).add_modifier(Modifier::DIM),
-                                ),
-                                Tile::Science => (
-                                    " S ",
-                                    Style::default()
-                                        .fg(Color::Green)
-                                        .add_modifier(Modifier::DIM),
-                                ),
-                            }
-                        } else {
-                            match tile {
-                                Tile::Empty => (" · ", Style::default().fg(Color::DarkGray)),
-                                Tile::Obstacle => (" # ", Style::default().fg(Color::Red)),
-                                Tile::Energy => (" E ", Style::default().fg(Color::Yellow)),
-                                Tile::Mineral => (" M ", Style::default().fg(Color::Blue)),
-                                Tile::Science => (" S ", Style::default().fg(Color::Green)),
-                            }
-                        };
+                            Some(biome) => style.bg(biome_color(biome)),
+                            None => style,
+                        }
+                    };
+
+                    let style = if app.cursor.is_some_and(|cursor| block.contains(&cursor)) {
+                        style.add_modifier(Modifier::REVERSED)
+                    } else {
+                        style
+                    };
 
                     Cell::from(Span::styled(symbol, style))
                 })
@@ -91,37 +457,176 @@ pub fn render(f: &mut Frame, app: &App) {
         })
         .collect();
 
+    let view_title = if viewport.shows_all(rows, cols) && viewport.zoom == 1 {
+        String::new()
+    } else {
+        format!(
+            " [({}, {}) 1:{}{}]",
+            viewport.origin.0,
+            viewport.origin.1,
+            viewport.zoom,
+            if viewport.follow { " following" } else { "" }
+        )
+    };
     let map_widget = Table::default()
-        .block(Block::default().title("PlanetMap").borders(Borders::ALL))
-        .widths(&vec![Constraint::Length(3); app.map.cols])
+        .block(
+            Block::default()
+                .title(format!(
+                    "PlanetMap{}{}{}",
+                    view_title,
+                    layer_title,
+                    app.heat_layer
+                        .map_or(String::new(), |layer| format!(" [heat: {}]", layer.name()))
+                ))
+                .borders(Borders::ALL),
+        )
+        .widths(vec![Constraint::Length(CELL_WIDTH); screen_cols])
+        .column_spacing(CELL_SPACING)
         .rows(map_grid);
 
-    f.render_widget(map_widget, chunks[0]);
+    f.render_widget(map_widget, area);
+    if !viewport.shows_all(rows, cols) {
+        render_minimap(f, app, area);
+    }
+}
+
+/// Named, coloured `(tick, value)` points.
+type Series<'a> = (&'a str, Color, Vec<(f64, f64)>);
+
+/// Line chart of `series` over the run, scaled to fit.
+fn line_chart<'a>(title: &'a str, app: &App, series: &'a [Series<'a>]) -> Chart<'a> {
+    let ticks = app.timeline.tick_bounds();
+    let top = series
+        .iter()
+        .flat_map(|(_, _, points)| points.iter().map(|&(_, y)| y))
+        .fold(0.0, f64::max)
+        .max(1.0)
+        * 1.1;
+    let datasets = series
+        .iter()
+        .map(|(name, color, points)| {
+            Dataset::default()
+                .name(*name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(points)
+        })
+        .collect();
+    Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .bounds(ticks)
+                .labels(vec![
+                    Span::raw(format!("{}", ticks[0])),
+                    Span::raw(format!("{}", ticks[1])),
+                ])
+                .style(Style::default().fg(Color::DarkGray)),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, top])
+                .labels(vec![Span::raw("0"), Span::raw(format!("{:.0}", top))])
+                .style(Style::default().fg(Color::DarkGray)),
+        )
+}
+
+fn render_charts(f: &mut Frame, app: &App, area: Rect) {
+    let timeline = &app.timeline;
+    let block = Block::default()
+        .title(format!("Charts, {} ticks", timeline.samples().len()))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
 
-    let right_chunks = Layout::default()
+    let rows = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(40),
             Constraint::Percentage(30),
             Constraint::Percentage(30),
+            Constraint::Percentage(20),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
         ])
-        .split(chunks[1]);
+        .split(inner);
+
+    let stocks = [
+        (
+            "energy",
+            Color::Yellow,
+            timeline.points(|s| s.energy_stock as f64),
+        ),
+        (
+            "mineral",
+            Color::Magenta,
+            timeline.points(|s| s.mineral_stock as f64),
+        ),
+    ];
+    f.render_widget(line_chart("Stocks", app, &stocks), rows[0]);
+    let collected = [
+        (
+            "energy",
+            Color::Yellow,
+            timeline.points(|s| s.energy_collected as f64),
+        ),
+        (
+            "mineral",
+            Color::Magenta,
+            timeline.points(|s| s.mineral_collected as f64),
+        ),
+    ];
+    f.render_widget(line_chart("Collected", app, &collected), rows[1]);
+    let explored = [("explored", Color::Green, timeline.points(|s| s.explored))];
+    f.render_widget(line_chart("Explored %", app, &explored), rows[2]);
+
+    let robots: Vec<u64> = timeline.samples().iter().map(|s| s.robots).collect();
+    render_sparkline(f, "Robots", &robots, Color::Cyan, rows[3]);
+    let merges: Vec<u64> = timeline.samples().iter().map(|s| s.merges).collect();
+    render_sparkline(f, "Merges/tick", &merges, Color::Blue, rows[4]);
+}
+
+/// Sparkline of the most recent `data` that fits in `area`.
+fn render_sparkline(f: &mut Frame, name: &str, data: &[u64], color: Color, area: Rect) {
+    let recent = &data[data.len().saturating_sub(area.width as usize)..];
+    let now = recent.last().copied().unwrap_or(0);
+    let sparkline = Sparkline::default()
+        .block(Block::default().title(format!("{} {}", name, now)))
+        .data(recent)
+        .style(Style::default().fg(color));
+    f.render_widget(sparkline, area);
+}
 
-    let robot_info_text = app
+pub fn render(f: &mut Frame, app: &App) {
+    let chunks = main_chunks(f.size());
+
+    if app.show_charts {
+        render_charts(f, app, chunks[0]);
+    } else {
+        render_map(f, app, chunks[0]);
+    }
+
+    let right_chunks = right_chunks(chunks[1]);
+
+    let robot_list = app
         .robots
         .iter()
-        .map(|r| {
+        .enumerate()
+        .map(|(idx, r)| {
             let modules = r
                 .modules
                 .iter()
-                .map(|m| format!("{:?}", m))
+                .map(|m| format!("{:?} L{}", m, r.level(m)))
                 .collect::<Vec<_>>()
                 .join(", ");
             format!(
-                "Robot #{} at ({}, {})\n  Modules: [{}]\n  Energy: {}  Mineral: {}  Known tiles: {}\n  Nearby Robots: {}\n",
+                "{}Robot #{} at ({}, {})  Home: {}\n  Modules: [{}]\n  Energy: {}  Mineral: {}  Known tiles: {}\n  Nearby Robots: {}\n",
+                if (app.show_fov || app.show_detail) && idx == app.selected_robot { "> " } else { "" },
                 r.id,
                 r.position.0,
                 r.position.1,
+                r.home(&app.stations)
+                    .map_or("-".to_string(), |site| format!("station {}", site.id)),
                 modules,
                 r.energy_collected,
                 r.mineral_collected,
@@ -137,6 +642,16 @@ pub fn render(f: &mut Frame, app: &App) {
         })
         .collect::<Vec<_>>()
         .join("\n");
+    let (title, body) = match app.selected() {
+        Some(robot) if app.show_detail => {
+            (format!("Robot #{}", robot.id), robot_detail(app, robot))
+        }
+        _ => ("Robots Info".to_string(), robot_list),
+    };
+    let robot_info_text = match app.cursor {
+        Some(cell) => format!("{}\n{}", tile_info(app, cell), body),
+        None => body,
+    };
 
     let mut robots_state = make_state(
         app.robots.len(),
@@ -145,7 +660,7 @@ pub fn render(f: &mut Frame, app: &App) {
     );
 
     let robot_panel = Paragraph::new(robot_info_text)
-        .block(Block::default().title("Robots Info").borders(Borders::ALL))
+        .block(Block::default().title(title).borders(Borders::ALL))
         .style(Style::default().fg(Color::Cyan))
         .wrap(ratatui::widgets::Wrap { trim: false })
         .scroll((app.robots_scroll, 0));
@@ -160,24 +675,33 @@ pub fn render(f: &mut Frame, app: &App) {
 
     f.render_stateful_widget(robots_bar, right_chunks[0], &mut robots_state);
 
-    let log_lines: Vec<Line> = app
-        .logs
+    let visible = app.visible_logs();
+    let height = log_view_height(f.size());
+    let first = app.log_scroll.first_line(visible.len(), height);
+    let log_lines: Vec<Line> = visible
         .iter()
-        .rev()
-        .take(15)
-        .map(|l| Line::from(l.clone()))
+        .skip(first)
+        .take(height)
+        .map(|record| log_line(record))
         .collect();
 
-    let mut logs_state = make_state(
-        app.logs.len(),
-        right_chunks[1].height,
-        app.logs_scroll as usize,
-    );
+    let mut logs_state = make_state(visible.len(), height as u16, first);
 
-    let logs_widget = Paragraph::new(log_lines)
-        .block(Block::default().title("Station Logs").borders(Borders::ALL))
-        .wrap(ratatui::widgets::Wrap { trim: false })
-        .scroll((app.logs_scroll, 0));
+    let logs_widget = Paragraph::new(log_lines).block(
+        Block::default()
+            .title(format!(
+                "Logs {}/{} [{}]{}",
+                visible.len(),
+                app.logs.records().len(),
+                app.log_filter.summary(),
+                if app.log_scroll.follow {
+                    " (follow)"
+                } else {
+                    ""
+                }
+            ))
+            .borders(Borders::ALL),
+    );
 
     f.render_widget(logs_widget, right_chunks[1]);
 
@@ -187,17 +711,71 @@ pub fn render(f: &mut Frame, app: &App) {
 
     f.render_stateful_widget(logs_bar, right_chunks[1], &mut logs_state);
 
-    let legend_lines = vec![
+    let mut legend_lines = vec![
         Line::from(" 🤖  - Robot"),
+        Line::from(" ⌂  - Station"),
         Line::from(" #  - Obstacle"),
         Line::from(" E  - Energy"),
         Line::from(" M  - Mineral"),
         Line::from(" S  - Science"),
         Line::from(" ·  - Empty"),
     ];
+    legend_lines.extend(Biome::ALL.iter().map(|biome| {
+        Line::from(vec![
+            Span::styled("   ", Style::default().bg(biome_color(*biome))),
+            Span::raw(format!(" - {}", biome.name())),
+        ])
+    }));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(238))),
+        Span::raw(" - Field of view (v, Tab)"),
+    ]));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(53))),
+        Span::raw(" - Docking zone"),
+    ]));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(24))),
+        Span::raw(" - Planned path (i, c + Enter)"),
+    ]));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(28))),
+        Span::raw(" - Target"),
+    ]));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(52))),
+        Span::raw(" - Station wrong (diff)"),
+    ]));
+    legend_lines.push(Line::from(vec![
+        Span::styled("   ", Style::default().bg(Color::Indexed(94))),
+        Span::raw(" - Robot wrong (diff)"),
+    ]));
+    legend_lines.push(Line::from(format!(" b  - Layer: {}", app.layer.name())));
+    legend_lines.push(Line::from(" hjkl/wasd pan, +/- zoom, f follow"));
+    legend_lines.push(Line::from(" t  - Charts"));
+    legend_lines.push(Line::from(" :  - Console (help)"));
+    legend_lines.push(Line::from(" L/R/C log level/robot/category"));
+    legend_lines.push(Line::from(" / search logs, F follow logs"));
+    legend_lines.push(Line::from(
+        [0.0, 0.33, 0.67, 1.0]
+            .iter()
+            .map(|&heat| Span::styled(" ", Style::default().bg(heat_color(heat))))
+            .chain([Span::raw(format!(
+                " g - Heat: {}",
+                app.heat_layer.map_or("off", |layer| layer.name())
+            ))])
+            .collect::<Vec<_>>(),
+    ));
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(right_chunks[2]);
+
     let legend =
         Paragraph::new(legend_lines).block(Block::default().title("Legend").borders(Borders::ALL));
-    f.render_widget(legend, right_chunks[2]);
+    f.render_widget(legend, bottom_chunks[0]);
+
+    render_economy(f, app, bottom_chunks[1]);
 
     let status_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -206,12 +784,21 @@ pub fn render(f: &mut Frame, app: &App) {
 
     let status = Paragraph::new(Line::from(vec![Span::styled(
         format!(
-            "Tick: {} | Robots: {} | Energy: {} | Mineral: {} | Master ver: {}",
+            "Tick: {}{} | Robots: {} | Energy: {} | Mineral: {} | Master ver: {} | Pending: {} | Late: {} | Dropped: {} | Dock queue: {} | Avg wait: {:.1} | Stalls: {}/{} ({} recovered)",
             app.tick_count,
+            if app.paused { " (paused)" } else { "" },
             app.robots.len(),
             app.collected_energy,
             app.collected_mineral,
             app.master_version,
+            app.merge_metrics.pending,
+            app.merge_metrics.late,
+            app.merge_metrics.dropped(),
+            app.traffic.queued,
+            app.traffic.avg_wait(),
+            app.watchdog.stuck,
+            app.watchdog.oscillating,
+            app.watchdog.recovered,
         ),
         Style::default().fg(Color::White),
     )]))
@@ -219,5 +806,18 @@ pub fn render(f: &mut Frame, app: &App) {
 
     f.render_widget(status, status_chunks[1]);
 
-    thread::sleep(Duration::from_millis(150));
+    if let Some(input) = &app.console {
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled(":", Style::default().fg(Color::Yellow)),
+            Span::raw(input.as_str()),
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+        ]))
+        .block(
+            Block::default()
+                .title("Console (Enter: run, Esc: close, help: commands)")
+                .borders(Borders::ALL),
+        );
+        f.render_widget(Clear, status_chunks[1]);
+        f.render_widget(prompt, status_chunks[1]);
+    }
 }
@@ -0,0 +1,165 @@
+use crate::map::Tile;
+use std::collections::HashMap;
+
+/// Map cells per screen cell along each side.
+pub const ZOOM_LEVELS: [usize; 3] = [1, 2, 4];
+
+/// The part of the map the TUI shows. Screen cells cover `zoom`×`zoom`
+/// map cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Viewport {
+    /// Top-left map cell shown.
+    pub origin: (usize, usize),
+    pub zoom: usize,
+    /// Screen cells available, rows by columns.
+    pub view: (usize, usize),
+    /// Keeps the selected robot centred.
+    pub follow: bool,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Self {
+            origin: (0, 0),
+            zoom: ZOOM_LEVELS[0],
+            view: (0, 0),
+            follow: false,
+        }
+    }
+}
+
+impl Viewport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map cells covered, rows by columns.
+    pub fn span(&self) -> (usize, usize) {
+        (self.view.0 * self.zoom, self.view.1 * self.zoom)
+    }
+
+    /// True when the whole `rows`×`cols` map fits on screen.
+    pub fn shows_all(&self, rows: usize, cols: usize) -> bool {
+        self.origin == (0, 0) && self.span().0 >= rows && self.span().1 >= cols
+    }
+
+    pub fn resize(&mut self, view: (usize, usize), rows: usize, cols: usize) {
+        self.view = view;
+        self.clamp(rows, cols);
+    }
+
+    /// Keeps the viewport on the map, showing as much of it as fits.
+    pub fn clamp(&mut self, rows: usize, cols: usize) {
+        let (span_rows, span_cols) = self.span();
+        self.origin = (
+            self.origin.0.min(rows.saturating_sub(span_rows)),
+            self.origin.1.min(cols.saturating_sub(span_cols)),
+        );
+    }
+
+    /// Moves by whole screen cells and stops following.
+    pub fn pan(&mut self, dr: isize, dc: isize, rows: usize, cols: usize) {
+        self.follow = false;
+        let shift =
+            |pos: usize, delta: isize| pos.saturating_add_signed(delta * self.zoom as isize);
+        self.origin = (shift(self.origin.0, dr), shift(self.origin.1, dc));
+        self.clamp(rows, cols);
+    }
+
+    pub fn center_on(&mut self, (row, col): (usize, usize), rows: usize, cols: usize) {
+        let (span_rows, span_cols) = self.span();
+        self.origin = (
+            row.saturating_sub(span_rows / 2),
+            col.saturating_sub(span_cols / 2),
+        );
+        self.clamp(rows, cols);
+    }
+
+    /// Pans just enough to bring `cell` on screen.
+    pub fn reveal(&mut self, (row, col): (usize, usize), rows: usize, cols: usize) {
+        let (span_rows, span_cols) = self.span();
+        let fit = |origin: usize, pos: usize, span: usize| {
+            if pos < origin {
+                pos
+            } else if span > 0 && pos >= origin + span {
+                pos + 1 - span
+            } else {
+                origin
+            }
+        };
+        self.origin = (
+            fit(self.origin.0, row, span_rows),
+            fit(self.origin.1, col, span_cols),
+        );
+        self.clamp(rows, cols);
+    }
+
+    /// Steps through `ZOOM_LEVELS`, out when `out` is set, keeping the
+    /// centre of the view in place.
+    pub fn zoom(&mut self, out: bool, rows: usize, cols: usize) {
+        let idx = ZOOM_LEVELS
+            .iter()
+            .position(|&zoom| zoom == self.zoom)
+            .unwrap_or(0);
+        let idx = if out {
+            (idx + 1).min(ZOOM_LEVELS.len() - 1)
+        } else {
+            idx.saturating_sub(1)
+        };
+        let (span_rows, span_cols) = self.span();
+        let center = (self.origin.0 + span_rows / 2, self.origin.1 + span_cols / 2);
+        self.zoom = ZOOM_LEVELS[idx];
+        self.center_on(center, rows, cols);
+    }
+
+    /// Screen size actually used for a `rows`×`cols` map, rows by columns.
+    pub fn screen_size(&self, rows: usize, cols: usize) -> (usize, usize) {
+        let cover = |len: usize, origin: usize, view: usize| {
+            len.saturating_sub(origin).div_ceil(self.zoom).min(view)
+        };
+        (
+            cover(rows, self.origin.0, self.view.0),
+            cover(cols, self.origin.1, self.view.1),
+        )
+    }
+
+    /// Map cells behind screen cell `(row, col)`, clipped to the map.
+    pub fn block(
+        &self,
+        (row, col): (usize, usize),
+        rows: usize,
+        cols: usize,
+    ) -> Vec<(usize, usize)> {
+        let top = self.origin.0 + row * self.zoom;
+        let left = self.origin.1 + col * self.zoom;
+        (top..(top + self.zoom).min(rows))
+            .flat_map(|r| (left..(left + self.zoom).min(cols)).map(move |c| (r, c)))
+            .collect()
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        let (span_rows, span_cols) = self.span();
+        (self.origin.0..self.origin.0 + span_rows).contains(&row)
+            && (self.origin.1..self.origin.1 + span_cols).contains(&col)
+    }
+}
+
+/// Most common tile, ties going to the rarer kind so resources stay
+/// visible when zoomed out.
+pub fn dominant_tile(tiles: impl IntoIterator<Item = Tile>) -> Option<Tile> {
+    let mut counts: HashMap<Tile, usize> = HashMap::new();
+    for tile in tiles {
+        *counts.entry(tile).or_insert(0) += 1;
+    }
+    let rarity = |tile: &Tile| match tile {
+        Tile::Empty => 0,
+        Tile::Obstacle => 1,
+        Tile::Energy => 2,
+        Tile::Mineral => 3,
+        Tile::Science => 4,
+    };
+    counts
+        .into_iter()
+        .max_by_key(|(tile, count)| (*count, rarity(tile)))
+        .map(|(tile, _)| tile)
+}
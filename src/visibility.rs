@@ -0,0 +1,148 @@
+use crate::map::{Map, Tile};
+use std::collections::HashSet;
+
+/// Slope `num / den` of a ray leaving the origin, `den` always positive.
+#[derive(Debug, Clone, Copy)]
+struct Slope {
+    num: i64,
+    den: i64,
+}
+
+impl Slope {
+    fn new(num: i64, den: i64) -> Self {
+        Self { num, den }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Quadrant {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Quadrant {
+    const ALL: [Quadrant; 4] = [
+        Quadrant::North,
+        Quadrant::East,
+        Quadrant::South,
+        Quadrant::West,
+    ];
+
+    /// Map cell at `depth` rows away from the origin and `col` across.
+    fn transform(&self, origin: (usize, usize), depth: i64, col: i64) -> (i64, i64) {
+        let (r, c) = (origin.0 as i64, origin.1 as i64);
+        match self {
+            Quadrant::North => (r - depth, c + col),
+            Quadrant::South => (r + depth, c + col),
+            Quadrant::East => (r + col, c + depth),
+            Quadrant::West => (r + col, c - depth),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ScanRow {
+    depth: i64,
+    start: Slope,
+    end: Slope,
+}
+
+impl ScanRow {
+    fn cols(&self) -> std::ops::RangeInclusive<i64> {
+        // round half up / half down of depth * slope
+        let min = (2 * self.depth * self.start.num + self.start.den).div_euclid(2 * self.start.den);
+        let max = -(-(2 * self.depth * self.end.num - self.end.den)).div_euclid(2 * self.end.den);
+        min..=max
+    }
+
+    fn next(&self) -> Self {
+        Self {
+            depth: self.depth + 1,
+            ..*self
+        }
+    }
+
+    /// Whether the centre of `col` lies inside the row's slopes, which keeps
+    /// the result symmetric for floor cells.
+    fn is_symmetric(&self, col: i64) -> bool {
+        col * self.start.den >= self.depth * self.start.num
+            && col * self.end.den <= self.depth * self.end.num
+    }
+}
+
+struct Fov<'a> {
+    map: &'a Map,
+    origin: (usize, usize),
+    radius: i64,
+    quadrant: Quadrant,
+    visible: HashSet<(usize, usize)>,
+}
+
+impl Fov<'_> {
+    fn cell(&self, depth: i64, col: i64) -> Option<(usize, usize)> {
+        let (r, c) = self.quadrant.transform(self.origin, depth, col);
+        (r >= 0 && c >= 0 && (r as usize) < self.map.grid.len() && (c as usize) < self.map.cols)
+            .then_some((r as usize, c as usize))
+    }
+
+    /// Cells outside the map block sight like obstacles.
+    fn is_wall(&self, depth: i64, col: i64) -> bool {
+        self.cell(depth, col)
+            .is_none_or(|(r, c)| self.map.grid[r][c] == Tile::Obstacle)
+    }
+
+    fn scan(&mut self, mut row: ScanRow) {
+        if row.depth > self.radius {
+            return;
+        }
+        let mut prev_wall = None;
+        for col in row.cols() {
+            let wall = self.is_wall(row.depth, col);
+            if wall || row.is_symmetric(col) {
+                if let Some(cell) = self.cell(row.depth, col) {
+                    self.visible.insert(cell);
+                }
+            }
+            if prev_wall == Some(true) && !wall {
+                row.start = Slope::new(2 * col - 1, 2 * row.depth);
+            }
+            if prev_wall == Some(false) && wall {
+                let mut next = row.next();
+                next.end = Slope::new(2 * col - 1, 2 * row.depth);
+                self.scan(next);
+            }
+            prev_wall = Some(wall);
+        }
+        if prev_wall == Some(false) {
+            self.scan(row.next());
+        }
+    }
+}
+
+/// Cells visible from `origin` within `radius` rows or columns, computed with
+/// symmetric shadowcasting: obstacles are seen but block what lies behind
+/// them, and floor cells see each other both ways.
+pub fn field_of_view(map: &Map, origin: (usize, usize), radius: usize) -> HashSet<(usize, usize)> {
+    let mut fov = Fov {
+        map,
+        origin,
+        radius: radius as i64,
+        quadrant: Quadrant::North,
+        visible: HashSet::new(),
+    };
+    if origin.0 >= map.grid.len() || origin.1 >= map.cols {
+        return fov.visible;
+    }
+    fov.visible.insert(origin);
+    for quadrant in Quadrant::ALL {
+        fov.quadrant = quadrant;
+        fov.scan(ScanRow {
+            depth: 1,
+            start: Slope::new(-1, 1),
+            end: Slope::new(1, 1),
+        });
+    }
+    fov.visible
+}
@@ -0,0 +1,219 @@
+use crate::robot::RobotStatus;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchdogConfig {
+    /// Ticks on the same cell before a robot counts as stuck.
+    pub stuck_after: u64,
+    /// Longest cycle, in ticks, reported as oscillation.
+    pub max_period: usize,
+    /// Times a cycle must repeat before it counts as oscillation.
+    pub repeats: usize,
+    /// Ticks a recovery behaviour runs before normal movement resumes.
+    pub recovery_ticks: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            stuck_after: 10,
+            max_period: 4,
+            repeats: 3,
+            recovery_ticks: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stall {
+    Stuck { ticks: u64 },
+    Oscillating { period: usize },
+}
+
+/// What a stalled robot does instead of its usual move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Random free neighbours, drawn from an rng seeded with `seed`.
+    RandomWalk { seed: u64 },
+    /// Shortest path to the docking target instead of greedy steps.
+    Replan,
+    /// Back away from the robot standing at `blocker`.
+    Yield { blocker: (usize, usize) },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Incident {
+    pub robot: usize,
+    pub tick: u64,
+    pub position: (usize, usize),
+    pub stall: Stall,
+    pub recovery: Recovery,
+}
+
+impl Incident {
+    pub fn describe(&self) -> String {
+        let stall = match self.stall {
+            Stall::Stuck { ticks } => format!("stuck for {} ticks", ticks),
+            Stall::Oscillating { period } => format!("oscillating with period {}", period),
+        };
+        let recovery = match self.recovery {
+            Recovery::RandomWalk { .. } => "random walk".to_string(),
+            Recovery::Replan => "replanning".to_string(),
+            Recovery::Yield { blocker } => {
+                format!("yielding to ({}, {})", blocker.0, blocker.1)
+            }
+        };
+        format!(
+            "Robot {} {} at ({}, {}), {}",
+            self.robot, stall, self.position.0, self.position.1, recovery
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchdogStats {
+    pub stuck: u64,
+    pub oscillating: u64,
+    /// Recoveries after which the robot had left the cells it was caught on.
+    pub recovered: u64,
+}
+
+/// Watches robot positions for deadlocks and livelocks. Fed by the
+/// coordinator every tick, which forwards the chosen recoveries.
+pub struct Watchdog {
+    config: WatchdogConfig,
+    rng: StdRng,
+    history: HashMap<usize, VecDeque<(usize, usize)>>,
+    /// Cell each robot stands on and the tick it got there.
+    still_since: HashMap<usize, ((usize, usize), u64)>,
+    /// Robots in recovery: the tick it ends and the cells they were caught on.
+    recovering: HashMap<usize, (u64, HashSet<(usize, usize)>)>,
+    stats: WatchdogStats,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig, seed: u64) -> Self {
+        Self {
+            config,
+            rng: StdRng::seed_from_u64(seed),
+            history: HashMap::new(),
+            still_since: HashMap::new(),
+            recovering: HashMap::new(),
+            stats: WatchdogStats::default(),
+        }
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    pub fn stats(&self) -> WatchdogStats {
+        self.stats
+    }
+
+    /// Records this tick's positions and returns the robots found stalled.
+    /// Robots in `excused` are told to stand still and are not checked.
+    pub fn observe(
+        &mut self,
+        robots: &[RobotStatus],
+        excused: &HashSet<usize>,
+        tick: u64,
+    ) -> Vec<Incident> {
+        let present: HashSet<usize> = robots.iter().map(|r| r.id).collect();
+        self.history.retain(|id, _| present.contains(id));
+        self.still_since.retain(|id, _| present.contains(id));
+        self.recovering.retain(|id, _| present.contains(id));
+
+        let window = self.config.max_period * self.config.repeats;
+        let mut sorted: Vec<&RobotStatus> = robots.iter().collect();
+        sorted.sort_by_key(|r| r.id);
+        let mut incidents = Vec::new();
+
+        for robot in sorted {
+            let since = match self.still_since.get(&robot.id) {
+                Some(&(cell, since)) if cell == robot.position => since,
+                _ => tick,
+            };
+            self.still_since.insert(robot.id, (robot.position, since));
+            let history = self.history.entry(robot.id).or_default();
+            history.push_back(robot.position);
+            if history.len() > window {
+                history.pop_front();
+            }
+
+            if excused.contains(&robot.id) {
+                history.clear();
+                self.still_since.insert(robot.id, (robot.position, tick));
+                continue;
+            }
+            if let Some((until, cells)) = self.recovering.get(&robot.id) {
+                if tick < *until {
+                    continue;
+                }
+                if !cells.contains(&robot.position) {
+                    self.stats.recovered += 1;
+                }
+                self.recovering.remove(&robot.id);
+                history.clear();
+                history.push_back(robot.position);
+                self.still_since.insert(robot.id, (robot.position, tick));
+                continue;
+            }
+
+            let stall = if tick - since >= self.config.stuck_after {
+                Stall::Stuck {
+                    ticks: tick - since,
+                }
+            } else if let Some(period) = cycle_period(history, self.config.max_period, window) {
+                Stall::Oscillating { period }
+            } else {
+                continue;
+            };
+
+            let blocker = robots
+                .iter()
+                .filter(|other| other.id < robot.id)
+                .map(|other| other.position)
+                .find(|&(r, c)| r.abs_diff(robot.position.0) + c.abs_diff(robot.position.1) == 1);
+            let recovery = match (stall, blocker) {
+                (Stall::Stuck { .. }, Some(blocker)) => Recovery::Yield { blocker },
+                (Stall::Stuck { .. }, None) if robot.returning_to.is_some() => Recovery::Replan,
+                _ => Recovery::RandomWalk {
+                    seed: self.rng.gen(),
+                },
+            };
+
+            match stall {
+                Stall::Stuck { .. } => self.stats.stuck += 1,
+                Stall::Oscillating { .. } => self.stats.oscillating += 1,
+            }
+            let cells = history.drain(..).chain([robot.position]).collect();
+            self.recovering
+                .insert(robot.id, (tick + self.config.recovery_ticks, cells));
+            incidents.push(Incident {
+                robot: robot.id,
+                tick,
+                position: robot.position,
+                stall,
+                recovery,
+            });
+        }
+
+        incidents
+    }
+}
+
+/// Shortest period the full history repeats with, if the robot moved at all.
+fn cycle_period(
+    history: &VecDeque<(usize, usize)>,
+    max_period: usize,
+    window: usize,
+) -> Option<usize> {
+    if history.len() < window || history.iter().all(|&cell| cell == history[0]) {
+        return None;
+    }
+    (2..=max_period)
+        .find(|&period| (period..history.len()).all(|i| history[i] == history[i - period]))
+}
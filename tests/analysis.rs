@@ -0,0 +1,107 @@
+use rust_project::analysis::{repair, MapAnalysis};
+use rust_project::map::{Map, Tile};
+
+fn walled_map() -> Map {
+    Map::from_ascii(
+        "..#EM\n\
+         .E#..\n\
+         #####\n\
+         S.#M.\n\
+         ..#..\n",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_components_and_unreachable_resources() {
+    let map = walled_map();
+    let analysis = MapAnalysis::analyze(&map, (0, 0));
+
+    assert_eq!(analysis.component_count, 4);
+    assert!(analysis.is_reachable((1, 1)));
+    assert!(!analysis.is_reachable((0, 3)));
+    assert_eq!(analysis.distances[&(1, 1)], 2);
+
+    let mut unreachable = analysis.unreachable_resources.clone();
+    unreachable.sort();
+    assert_eq!(unreachable, vec![(0, 3), (0, 4), (3, 0), (3, 3)]);
+}
+
+#[test]
+fn test_stats_report_counts_and_distances() {
+    let map = walled_map();
+    let stats = MapAnalysis::analyze(&map, (0, 0)).stats(&map);
+
+    assert_eq!(stats.tile_counts[&Tile::Obstacle], 9);
+    assert_eq!(stats.tile_counts[&Tile::Energy], 2);
+    assert_eq!(stats.passable_cells, 16);
+    assert_eq!(stats.reachable_cells, 4);
+    assert!((stats.reachable_fraction - 4.0 / 16.0).abs() < 1e-9);
+    assert_eq!(stats.unreachable_resources, 4);
+    assert_eq!(stats.resource_distances.get(&2), Some(&1));
+}
+
+#[test]
+fn test_station_on_obstacle_reaches_nothing() {
+    let map = Map {
+        grid: vec![
+            vec![Tile::Obstacle, Tile::Energy],
+            vec![Tile::Energy, Tile::Empty],
+        ],
+        cols: 2,
+    };
+    let analysis = MapAnalysis::analyze(&map, (0, 0));
+    assert!(analysis.distances.is_empty());
+    assert_eq!(analysis.unreachable_resources.len(), 2);
+}
+
+#[test]
+fn test_repair_connects_every_resource() {
+    let mut map = walled_map();
+    let carved = repair(&mut map, (0, 0), 7);
+
+    assert!(!carved.is_empty());
+    for &(r, c) in &carved {
+        assert_eq!(map.grid[r][c], Tile::Empty);
+    }
+    let analysis = MapAnalysis::analyze(&map, (0, 0));
+    assert!(analysis.unreachable_resources.is_empty());
+}
+
+#[test]
+fn test_repair_clears_station_tile() {
+    let mut map = Map {
+        grid: vec![
+            vec![Tile::Obstacle, Tile::Obstacle],
+            vec![Tile::Obstacle, Tile::Energy],
+        ],
+        cols: 2,
+    };
+    let carved = repair(&mut map, (0, 0), 1);
+
+    assert!(carved.contains(&(0, 0)));
+    assert!(MapAnalysis::analyze(&map, (0, 0)).is_reachable((1, 1)));
+}
+
+#[test]
+fn test_repair_is_deterministic() {
+    let mut map1 = Map::generate(20, 20, 3);
+    let mut map2 = Map::generate(20, 20, 3);
+    assert_eq!(repair(&mut map1, (0, 0), 3), repair(&mut map2, (0, 0), 3));
+    assert_eq!(map1.grid, map2.grid);
+    assert!(MapAnalysis::analyze(&map1, (0, 0))
+        .unreachable_resources
+        .is_empty());
+}
+
+#[test]
+fn test_repair_leaves_connected_map_untouched() {
+    let mut map = Map {
+        grid: vec![
+            vec![Tile::Empty, Tile::Energy],
+            vec![Tile::Mineral, Tile::Science],
+        ],
+        cols: 2,
+    };
+    assert!(repair(&mut map, (0, 0), 9).is_empty());
+}
@@ -0,0 +1,102 @@
+use rust_project::ascii::{AsciiMap, MapFileError, ParseErrorKind};
+use rust_project::map::{Map, Tile};
+
+#[test]
+fn test_parse_glyphs_and_markers() {
+    let layout = AsciiMap::parse("H.#E\n·MSR\n").unwrap();
+    assert_eq!(layout.map.cols, 4);
+    assert_eq!(
+        layout.map.grid,
+        vec![
+            vec![Tile::Empty, Tile::Empty, Tile::Obstacle, Tile::Energy],
+            vec![Tile::Empty, Tile::Mineral, Tile::Science, Tile::Empty],
+        ]
+    );
+    assert_eq!(layout.station, Some((0, 0)));
+    assert_eq!(layout.robots, vec![(1, 3)]);
+}
+
+#[test]
+fn test_parse_reports_line_and_column() {
+    let err = AsciiMap::parse("....\n..x.\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(err.kind, ParseErrorKind::UnknownGlyph('x'));
+    assert_eq!(err.to_string(), "line 2, column 3: unknown glyph 'x'");
+}
+
+#[test]
+fn test_parse_rejects_ragged_rows() {
+    let err = AsciiMap::parse("....\n..\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(
+        err.kind,
+        ParseErrorKind::RaggedRow {
+            expected: 4,
+            found: 2
+        }
+    );
+}
+
+#[test]
+fn test_parse_rejects_second_station_and_empty_input() {
+    let err = AsciiMap::parse("H..\n..H\n").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(err.kind, ParseErrorKind::DuplicateStation);
+
+    let err = AsciiMap::parse("\n\n").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::EmptyMap);
+}
+
+#[test]
+fn test_round_trip_through_text() {
+    let text = "H.#E\n.MSR\n";
+    let layout: AsciiMap = text.parse().unwrap();
+    assert_eq!(layout.to_string(), text);
+
+    let map = Map::generate(12, 9, 5);
+    assert_eq!(Map::from_ascii(&map.to_ascii()).unwrap(), map);
+}
+
+#[test]
+fn test_robot_start_on_station_survives_round_trip() {
+    let mut layout = AsciiMap::parse("H.\n.R\n").unwrap();
+    layout.robots.push((0, 0));
+    let text = layout.to_string();
+    assert_eq!(text, "@.\n.R\n");
+
+    let loaded = AsciiMap::parse(&text).unwrap();
+    assert_eq!(loaded.station, Some((0, 0)));
+    assert_eq!(loaded.robots, vec![(0, 0), (1, 1)]);
+
+    let err = AsciiMap::parse("H.\n.@\n").unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::DuplicateStation);
+}
+
+#[test]
+fn test_layout_seed_is_stable() {
+    let layout = AsciiMap::parse("H.#E\n.MSR\n").unwrap();
+    assert_eq!(
+        layout.seed(),
+        AsciiMap::parse("H.#E\n.MSR\n").unwrap().seed()
+    );
+    assert_ne!(
+        layout.seed(),
+        AsciiMap::parse("H.#E\n.MS.\n").unwrap().seed()
+    );
+}
+
+#[test]
+fn test_save_and_load_file() {
+    let path = std::env::temp_dir().join(format!("ascii_map_{}.txt", std::process::id()));
+    let layout = AsciiMap::parse("H#\nRE\n").unwrap();
+
+    layout.save(&path).unwrap();
+    let loaded = AsciiMap::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, layout);
+
+    assert!(matches!(
+        AsciiMap::load(path.with_extension("missing")),
+        Err(MapFileError::Io(_))
+    ));
+}
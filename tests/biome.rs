@@ -0,0 +1,69 @@
+use rust_project::biome::{Biome, BiomeGenerator};
+use rust_project::generator::MapGenerator;
+use rust_project::map::Tile;
+use std::collections::HashMap;
+
+#[test]
+fn test_biome_classification() {
+    assert_eq!(Biome::classify(0.8, 0.5), Biome::GeothermalVents);
+    assert_eq!(Biome::classify(0.3, 0.7), Biome::IceSheet);
+    assert_eq!(Biome::classify(0.5, 0.3), Biome::Highlands);
+    assert_eq!(Biome::classify(0.5, 0.6), Biome::Plains);
+}
+
+#[test]
+fn test_biome_map_matches_tile_map() {
+    let generator = BiomeGenerator::default();
+    let (map, biomes) = generator.generate_with_biomes(30, 40, 12);
+
+    assert_eq!(biomes.grid.len(), map.grid.len());
+    assert_eq!(biomes.cols, map.cols);
+    assert_eq!(generator.generate(30, 40, 12).grid, map.grid);
+    assert_eq!(generator.biomes(30, 40, 12), biomes);
+    assert_eq!(biomes.get(30, 0), None);
+}
+
+#[test]
+fn test_biome_generation_is_deterministic() {
+    let generator = BiomeGenerator::default();
+    let (map1, biomes1) = generator.generate_with_biomes(20, 20, 77);
+    let (map2, biomes2) = generator.generate_with_biomes(20, 20, 77);
+    assert_eq!(map1.grid, map2.grid);
+    assert_eq!(biomes1, biomes2);
+}
+
+#[test]
+fn test_biomes_have_regional_resource_mix() {
+    let generator = BiomeGenerator {
+        climate_frequency: 0.02,
+        ..BiomeGenerator::default()
+    };
+
+    let mut counts: HashMap<(Biome, Tile), usize> = HashMap::new();
+    for seed in 0..4 {
+        let (map, biomes) = generator.generate_with_biomes(120, 120, seed);
+        for (r, row) in map.grid.iter().enumerate() {
+            for (c, &tile) in row.iter().enumerate() {
+                *counts.entry((biomes.grid[r][c], tile)).or_insert(0) += 1;
+            }
+        }
+    }
+    let count = |biome, tile| *counts.get(&(biome, tile)).unwrap_or(&0) as f64;
+    let mineral_share = |biome| {
+        count(biome, Tile::Mineral)
+            / (count(biome, Tile::Mineral) + count(biome, Tile::Energy)).max(1.0)
+    };
+
+    assert!(mineral_share(Biome::Highlands) > 0.7);
+    assert!(mineral_share(Biome::GeothermalVents) < 0.3);
+}
+
+#[test]
+fn test_biome_thresholds_differ() {
+    let highlands = Biome::Highlands.thresholds();
+    let vents = Biome::GeothermalVents.thresholds();
+    let ice = Biome::IceSheet.thresholds();
+
+    assert!(highlands.mineral_chance > vents.mineral_chance);
+    assert!(ice.science_chance > Biome::Plains.thresholds().science_chance);
+}
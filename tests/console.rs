@@ -0,0 +1,135 @@
+use rust_project::console::{Command, LogCommand};
+use rust_project::log::{Category, Level};
+use rust_project::map::Tile;
+use rust_project::robot::RobotModule;
+use rust_project::station::{StationQuery, StationRequest};
+
+#[test]
+fn test_spawn_defaults_to_a_full_loadout() {
+    assert_eq!(
+        Command::parse("spawn 3 4"),
+        Ok(Command::Station(StationRequest::Spawn {
+            modules: vec![
+                RobotModule::Explorer,
+                RobotModule::Collector,
+                RobotModule::Scanner,
+                RobotModule::Sensor,
+            ],
+            position: (3, 4),
+        }))
+    );
+    assert_eq!(
+        Command::parse("  spawn 0 1 es "),
+        Ok(Command::Station(StationRequest::Spawn {
+            modules: vec![RobotModule::Explorer, RobotModule::Scanner],
+            position: (0, 1),
+        }))
+    );
+}
+
+#[test]
+fn test_map_stock_and_query_commands() {
+    assert_eq!(
+        Command::parse("tile 2 5 #"),
+        Ok(Command::SetTile {
+            cell: (2, 5),
+            tile: Tile::Obstacle,
+        })
+    );
+    assert_eq!(
+        Command::parse("clear 2 5"),
+        Ok(Command::SetTile {
+            cell: (2, 5),
+            tile: Tile::Empty,
+        })
+    );
+    assert_eq!(
+        Command::parse("stock +50 -10"),
+        Ok(Command::Station(StationRequest::AdjustStocks {
+            energy: 50,
+            mineral: -10,
+        }))
+    );
+    assert_eq!(
+        Command::parse("query cell 1 2"),
+        Ok(Command::Station(StationRequest::Query(StationQuery::Cell(
+            (1, 2)
+        ))))
+    );
+    assert_eq!(Command::parse("recall 7"), Ok(Command::Recall(7)));
+    assert_eq!(
+        Command::parse("assign 7 2"),
+        Ok(Command::Assign {
+            robot: 7,
+            station: Some(2)
+        })
+    );
+    assert_eq!(
+        Command::parse("assign 7 nearest"),
+        Ok(Command::Assign {
+            robot: 7,
+            station: None
+        })
+    );
+    assert_eq!(Command::parse("speed 40"), Ok(Command::Speed(40)));
+    assert_eq!(
+        Command::parse("retrofit 4 remove s"),
+        Ok(Command::Station(StationRequest::Remove {
+            robot_id: 4,
+            module: RobotModule::Scanner,
+        }))
+    );
+}
+
+#[test]
+fn test_bad_commands_are_explained() {
+    for line in [
+        "",
+        "fly 1 2",
+        "spawn 1",
+        "spawn 1 2 EXE",
+        "spawn 1 2 Q",
+        "tile 1 2 X",
+        "tile -1 2 E",
+        "speed fast",
+        "query weather",
+        "pause now",
+        "assign 7",
+        "assign 7 far",
+        "retrofit 4 remove",
+        "retrofit 4 remove ES",
+        "retrofit 4 install S",
+    ] {
+        assert!(Command::parse(line).is_err(), "{:?} parsed", line);
+    }
+    assert_eq!(
+        Command::parse("recall"),
+        Err("wrong arguments for recall, see help".to_string())
+    );
+}
+
+#[test]
+fn test_log_filter_commands() {
+    assert_eq!(
+        Command::parse("log level warn"),
+        Ok(Command::Log(LogCommand::Level(Level::Warn)))
+    );
+    assert_eq!(
+        Command::parse("log robot all"),
+        Ok(Command::Log(LogCommand::Robot(None)))
+    );
+    assert_eq!(
+        Command::parse("log category dock"),
+        Ok(Command::Log(LogCommand::Category(Some(Category::Dock))))
+    );
+    assert_eq!(
+        Command::parse("log search docked at"),
+        Ok(Command::Log(LogCommand::Search("docked at".to_string())))
+    );
+    assert_eq!(
+        Command::parse("log search"),
+        Ok(Command::Log(LogCommand::Search(String::new())))
+    );
+    assert!(Command::parse("log level loud").is_err());
+    assert!(Command::parse("log category weather").is_err());
+}
@@ -0,0 +1,172 @@
+use rust_project::map::Map;
+use rust_project::robot::{Robot, RobotActor, RobotCmd, RobotModule};
+use rust_project::station::{DockPhase, RobotReport, StationSite};
+use rust_project::traffic::DockOrder;
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+fn site(position: (usize, usize), dock_radius: usize) -> StationSite {
+    StationSite {
+        id: 4,
+        position,
+        dock_radius,
+    }
+}
+
+struct Harness {
+    tx: mpsc::Sender<RobotCmd>,
+    rx_report: mpsc::Receiver<RobotReport>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl Harness {
+    /// Full robot at `start` heading home to `station` on an open 5x5 map.
+    fn returning(start: (usize, usize), station: StationSite) -> Self {
+        let map = Arc::new(RwLock::new(Map::from_ascii(&".....\n".repeat(5)).unwrap()));
+        let mut robot = Robot::new(1, start, vec![RobotModule::Collector]);
+        robot.energy_collected = 10;
+
+        let (tx, rx) = mpsc::channel();
+        let (tx_report, rx_report) = mpsc::channel();
+        let actor = RobotActor::new(robot, map, rx, tx_report);
+        let handle = thread::spawn(move || actor.run());
+        tx.send(RobotCmd::Stations(vec![station])).unwrap();
+        Self {
+            tx,
+            rx_report,
+            handle,
+        }
+    }
+
+    /// Runs one tick and returns the robot's position afterwards.
+    fn tick(&self, tick_count: u64, occupied: &[(usize, usize)]) -> (usize, usize) {
+        self.tick_with(tick_count, occupied, DockOrder::Free)
+    }
+
+    fn tick_with(
+        &self,
+        tick_count: u64,
+        occupied: &[(usize, usize)],
+        dock: DockOrder,
+    ) -> (usize, usize) {
+        self.tx
+            .send(RobotCmd::Tick {
+                tick_count,
+                occupied_positions: occupied.iter().copied().collect::<HashSet<_>>(),
+                blinded: false,
+                dock,
+            })
+            .unwrap();
+        let (respond_to, statuses) = mpsc::channel();
+        self.tx.send(RobotCmd::ReportStatus { respond_to }).unwrap();
+        statuses
+            .recv_timeout(Duration::from_secs(1))
+            .unwrap()
+            .position
+    }
+
+    fn stop(self) {
+        self.tx.send(RobotCmd::Shutdown).unwrap();
+        self.handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_zone_is_clipped_to_the_map() {
+    assert_eq!(site((0, 0), 1).zone(5, 5).len(), 4);
+    assert_eq!(site((2, 2), 1).zone(5, 5).len(), 9);
+    assert_eq!(site((4, 4), 2).zone(5, 5).len(), 9);
+    assert_eq!(site((2, 2), 0).zone(5, 5), vec![(2, 2)]);
+}
+
+#[test]
+fn test_in_zone_uses_chebyshev_distance() {
+    let station = site((3, 3), 1);
+    assert!(station.in_zone((2, 4)));
+    assert!(station.in_zone((3, 3)));
+    assert!(!station.in_zone((1, 3)));
+    assert!(!station.in_zone((3, 5)));
+}
+
+#[test]
+fn test_robot_docks_on_entering_the_zone() {
+    let harness = Harness::returning((0, 4), site((0, 0), 2));
+
+    assert_eq!(harness.tick(1, &[]), (0, 3));
+    assert!(harness.rx_report.try_recv().is_err());
+
+    assert_eq!(harness.tick(2, &[]), (0, 2));
+    let report = harness.rx_report.try_recv().unwrap();
+    assert_eq!(report.station, 4);
+    assert_eq!(report.dock, DockPhase::Upload);
+    assert_eq!(report.energy, 10);
+    harness.stop();
+}
+
+#[test]
+fn test_robot_queues_while_the_zone_is_full() {
+    let harness = Harness::returning((0, 3), site((0, 0), 1));
+    let full = [(0, 0), (0, 1), (1, 0), (1, 1)];
+
+    assert_eq!(harness.tick(1, &full), (0, 3));
+    assert_eq!(harness.tick(2, &full), (0, 3));
+    assert!(harness.rx_report.try_recv().is_err());
+
+    // the last free cell is (1, 1): the robot goes round the taken ones
+    assert_eq!(harness.tick(3, &full[..3]), (1, 3));
+    assert_eq!(harness.tick(4, &full[..3]), (1, 2));
+    assert_eq!(harness.tick(5, &full[..3]), (1, 1));
+    assert_eq!(harness.rx_report.try_recv().unwrap().tick, 5);
+    harness.stop();
+}
+
+#[test]
+fn test_waiting_robot_stays_on_its_ring_cell() {
+    let harness = Harness::returning((0, 4), site((0, 0), 1));
+    let order = DockOrder::Wait((0, 2));
+
+    assert_eq!(harness.tick_with(1, &[], order), (0, 3));
+    assert_eq!(harness.tick_with(2, &[], order), (0, 2));
+    assert_eq!(harness.tick_with(3, &[], order), (0, 2));
+    assert!(harness.rx_report.try_recv().is_err());
+
+    assert_eq!(harness.tick_with(4, &[], DockOrder::Hold), (0, 2));
+    assert_eq!(harness.tick_with(5, &[], DockOrder::Slot((1, 1))), (1, 2));
+    assert_eq!(harness.tick_with(6, &[], DockOrder::Slot((1, 1))), (1, 1));
+    assert_eq!(harness.rx_report.try_recv().unwrap().tick, 6);
+    harness.stop();
+}
+
+#[test]
+fn test_assigned_robot_passes_a_closer_station() {
+    let own = StationSite {
+        id: 1,
+        position: (0, 0),
+        dock_radius: 0,
+    };
+    let closer = StationSite {
+        id: 2,
+        position: (0, 2),
+        dock_radius: 0,
+    };
+    let harness = Harness::returning((0, 4), own);
+    harness
+        .tx
+        .send(RobotCmd::Stations(vec![own, closer]))
+        .unwrap();
+    harness.tx.send(RobotCmd::Assign(Some(1))).unwrap();
+
+    let mut position = (0, 4);
+    for tick in 1..=8 {
+        position = harness.tick(tick, &[]);
+        if let Ok(report) = harness.rx_report.try_recv() {
+            assert_eq!(report.station, 1);
+            break;
+        }
+    }
+    assert_eq!(position, (0, 0));
+    harness.stop();
+}
@@ -0,0 +1,194 @@
+use rust_project::dynamics::{DynamicsConfig, EventKind, WorldDynamics, WorldEvent};
+use rust_project::map::{Map, MapDiff, Tile};
+use std::collections::HashSet;
+
+fn quiet_config() -> DynamicsConfig {
+    DynamicsConfig {
+        regrowth_chance: 1.0,
+        regrowth_delay: 5,
+        event_interval: 0,
+        ..DynamicsConfig::default()
+    }
+}
+
+#[test]
+fn test_collected_resources_regrow_after_delay() {
+    let mut map = Map::from_ascii("...\n...\n").unwrap();
+    let mut dynamics = WorldDynamics::new(quiet_config(), 1);
+    dynamics.record_collection(
+        &MapDiff(vec![
+            ((0, 1), Some(Tile::Energy), Tile::Empty),
+            ((1, 2), Some(Tile::Mineral), Tile::Empty),
+            ((1, 0), None, Tile::Empty),
+        ]),
+        10,
+    );
+
+    let (diff, events) = dynamics.tick(&mut map, 14, &HashSet::new());
+    assert!(diff.0.is_empty());
+    assert!(events.is_empty());
+
+    let (diff, events) = dynamics.tick(&mut map, 15, &HashSet::new());
+    assert_eq!(
+        diff,
+        MapDiff(vec![
+            ((0, 1), Some(Tile::Empty), Tile::Energy),
+            ((1, 2), Some(Tile::Empty), Tile::Mineral),
+        ])
+    );
+    assert_eq!(
+        events,
+        vec![WorldEvent::Regrowth {
+            cells: vec![(0, 1), (1, 2)]
+        }]
+    );
+    assert_eq!(map.grid[0][1], Tile::Energy);
+    assert_eq!(map.grid[1][0], Tile::Empty);
+}
+
+#[test]
+fn test_regrowth_skips_occupied_cells() {
+    let mut map = Map::from_ascii("..\n").unwrap();
+    let mut dynamics = WorldDynamics::new(quiet_config(), 1);
+    dynamics.record_collection(&MapDiff(vec![((0, 0), Some(Tile::Energy), Tile::Empty)]), 0);
+
+    let protected = HashSet::from([(0, 0)]);
+    let (diff, _) = dynamics.tick(&mut map, 20, &protected);
+    assert!(diff.0.is_empty());
+    assert_eq!(map.grid[0][0], Tile::Empty);
+}
+
+#[test]
+fn test_rock_slide_blocks_empty_cells_only() {
+    let mut map = Map::from_ascii("...\n.E.\n...\n").unwrap();
+    let mut dynamics = WorldDynamics::new(quiet_config(), 1);
+    let protected = HashSet::from([(0, 0)]);
+
+    let (diff, event) = dynamics.trigger(EventKind::RockSlide, (1, 1), &mut map, 3, &protected);
+
+    assert_eq!(diff.0.len(), 7);
+    assert_eq!(map.grid[0][0], Tile::Empty);
+    assert_eq!(map.grid[1][1], Tile::Energy);
+    assert_eq!(map.grid[2][2], Tile::Obstacle);
+    match event {
+        WorldEvent::RockSlide { blocked, .. } => assert_eq!(blocked.len(), 7),
+        other => panic!("unexpected event {:?}", other),
+    }
+}
+
+#[test]
+fn test_meteor_strike_reveals_science() {
+    let mut map = Map::from_ascii("#..\n.M.\n..E\n").unwrap();
+    let config = DynamicsConfig {
+        meteor_science_chance: 1.0,
+        meteor_radius: 1,
+        ..quiet_config()
+    };
+    let mut dynamics = WorldDynamics::new(config, 1);
+
+    let (diff, _) = dynamics.trigger(
+        EventKind::MeteorStrike,
+        (1, 1),
+        &mut map,
+        3,
+        &HashSet::from([(0, 1)]),
+    );
+
+    // obstacles and protected cells are left alone
+    assert_eq!(diff.0.len(), 7);
+    assert_eq!(map.grid[0][0], Tile::Obstacle);
+    assert_eq!(map.grid[0][1], Tile::Empty);
+    assert!(diff
+        .0
+        .contains(&((1, 1), Some(Tile::Mineral), Tile::Science)));
+    assert!(diff
+        .0
+        .contains(&((2, 2), Some(Tile::Energy), Tile::Science)));
+    assert_eq!(map.grid[2][1], Tile::Science);
+}
+
+#[test]
+fn test_out_of_range_chances_are_clamped() {
+    let mut map = Map::from_ascii("...\n...\n").unwrap();
+    let config = DynamicsConfig {
+        regrowth_chance: 7.0,
+        meteor_science_chance: -1.0,
+        meteor_radius: 1,
+        ..quiet_config()
+    };
+    let mut dynamics = WorldDynamics::new(config, 1);
+    dynamics.record_collection(&MapDiff(vec![((0, 0), Some(Tile::Energy), Tile::Empty)]), 0);
+
+    let (diff, _) = dynamics.tick(&mut map, 10, &HashSet::new());
+    assert_eq!(diff.0, vec![((0, 0), Some(Tile::Empty), Tile::Energy)]);
+
+    let (diff, _) = dynamics.trigger(
+        EventKind::MeteorStrike,
+        (1, 1),
+        &mut map,
+        11,
+        &HashSet::new(),
+    );
+    assert!(diff.0.is_empty());
+
+    dynamics = WorldDynamics::new(
+        DynamicsConfig {
+            meteor_science_chance: f64::NAN,
+            ..dynamics.config().clone()
+        },
+        1,
+    );
+    let (diff, _) = dynamics.trigger(
+        EventKind::MeteorStrike,
+        (1, 1),
+        &mut map,
+        12,
+        &HashSet::new(),
+    );
+    assert!(diff.0.is_empty());
+}
+
+#[test]
+fn test_dust_storm_blinds_region_until_it_ends() {
+    let mut map = Map::from_ascii(&".........\n".repeat(9)).unwrap();
+    let config = DynamicsConfig {
+        storm_radius: 2,
+        storm_duration: 4,
+        ..quiet_config()
+    };
+    let mut dynamics = WorldDynamics::new(config, 1);
+
+    let (diff, _) = dynamics.trigger(EventKind::DustStorm, (4, 4), &mut map, 10, &HashSet::new());
+    assert!(diff.0.is_empty());
+
+    assert!(dynamics.is_blinded((2, 6), 12));
+    assert!(!dynamics.is_blinded((1, 4), 12));
+    assert!(dynamics.is_blinded((4, 4), 14));
+
+    dynamics.tick(&mut map, 15, &HashSet::new());
+    assert!(!dynamics.is_blinded((4, 4), 15));
+}
+
+#[test]
+fn test_periodic_events_are_seeded() {
+    let config = DynamicsConfig {
+        event_interval: 5,
+        ..DynamicsConfig::default()
+    };
+    let run = || {
+        let mut map = Map::generate(15, 15, 8);
+        let mut dynamics = WorldDynamics::new(config.clone(), 99);
+        let mut all_events = Vec::new();
+        for tick in 1..=30 {
+            let (_, events) = dynamics.tick(&mut map, tick, &HashSet::new());
+            all_events.extend(events);
+        }
+        (map, all_events)
+    };
+
+    let (map1, events1) = run();
+    let (map2, events2) = run();
+    assert_eq!(events1.len(), 6);
+    assert_eq!(events1, events2);
+    assert_eq!(map1, map2);
+}
@@ -0,0 +1,248 @@
+use rust_project::economy::{
+    Balanced, BuildItem, Cost, CostTable, Economy, Greedy, SavingForUpgrade, SpendingPolicy,
+    Stocks, Upgrade,
+};
+use rust_project::map::MapDiff;
+use rust_project::robot::{Retrofit, RobotModule};
+use rust_project::station::{DockPhase, RobotReport, Station, StationCmd, StationRequest};
+use std::sync::mpsc;
+
+fn stocks(energy: u32, mineral: u32) -> Stocks {
+    Stocks { energy, mineral }
+}
+
+#[test]
+fn test_robot_cost_adds_modules_to_chassis() {
+    let costs = CostTable::default();
+    assert_eq!(costs.cost(&BuildItem::full_robot()), Cost::new(10, 10, 12));
+    assert_eq!(
+        costs.cost(&BuildItem::Robot(vec![RobotModule::Explorer])),
+        Cost::new(4, 4, 6)
+    );
+    assert_eq!(CostTable::instant().cost(&BuildItem::full_robot()).ticks, 0);
+}
+
+#[test]
+fn test_build_items_round_trip_through_text() {
+    for item in [
+        BuildItem::full_robot(),
+        BuildItem::Module(RobotModule::Sensor),
+        BuildItem::Upgrade(Upgrade::Payload),
+        BuildItem::Upgrade(Upgrade::ScanRadius),
+    ] {
+        assert_eq!(item.to_string().parse(), Ok(item));
+    }
+    assert!("robot:EX".parse::<BuildItem>().is_err());
+}
+
+#[test]
+fn test_policies_spend_differently() {
+    let costs = CostTable::default();
+
+    assert_eq!(
+        Greedy.next_build(stocks(12, 10), &costs),
+        Some(BuildItem::full_robot())
+    );
+    assert_eq!(Greedy.next_build(stocks(12, 9), &costs), None);
+
+    let mut saving = SavingForUpgrade {
+        target: Upgrade::Payload,
+    };
+    assert_eq!(
+        saving.next_build(stocks(10, 10), &costs),
+        Some(BuildItem::Upgrade(Upgrade::Payload))
+    );
+    assert_eq!(saving.next_build(stocks(5, 20), &costs), None);
+    assert_eq!(
+        saving.next_build(stocks(16, 18), &costs),
+        Some(BuildItem::full_robot())
+    );
+
+    let mut balanced = Balanced::default();
+    assert_eq!(
+        balanced.next_build(stocks(10, 10), &costs),
+        Some(BuildItem::full_robot())
+    );
+    assert_eq!(balanced.next_build(stocks(20, 7), &costs), None);
+    assert_eq!(
+        balanced.next_build(stocks(20, 8), &costs),
+        Some(BuildItem::Upgrade(Upgrade::Payload))
+    );
+}
+
+#[test]
+fn test_queue_builds_one_item_at_a_time() {
+    let mut economy = Economy::new(CostTable::default(), Box::new(Greedy)).with_max_queue(2);
+    let mut wallet = stocks(35, 30);
+
+    let queued = economy.plan(&mut wallet);
+    assert_eq!(queued.len(), 2);
+    assert_eq!(wallet, stocks(15, 10));
+
+    assert!(economy.advance(11).is_empty());
+    assert_eq!(economy.advance(1), vec![BuildItem::full_robot()]);
+    assert_eq!(economy.queue()[0].remaining, 12);
+    assert_eq!(economy.advance(20), vec![BuildItem::full_robot()]);
+    assert!(economy.queue().is_empty());
+
+    assert_eq!(
+        economy.enqueue(BuildItem::Upgrade(Upgrade::ScanRadius), &mut stocks(1, 1)),
+        Err(Cost::new(8, 6, 8))
+    );
+}
+
+#[test]
+fn test_instant_economy_builds_one_robot_per_plan() {
+    let mut economy = Economy::instant();
+    let mut wallet = stocks(25, 25);
+
+    assert_eq!(economy.plan(&mut wallet).len(), 1);
+    assert_eq!(economy.advance(0), vec![BuildItem::full_robot()]);
+    assert_eq!(wallet, stocks(15, 15));
+}
+
+fn upload(tick: u64, energy: u32, mineral: u32) -> RobotReport {
+    RobotReport {
+        robot_id: 1,
+        station: 0,
+        tick,
+        base_version: 0,
+        map_diff: MapDiff(Vec::new()),
+        energy,
+        mineral,
+        dock: DockPhase::Upload,
+        loadout: Vec::new(),
+    }
+}
+
+fn heartbeat(tick: u64) -> RobotReport {
+    RobotReport {
+        robot_id: 0,
+        dock: DockPhase::None,
+        ..upload(tick, 0, 0)
+    }
+}
+
+#[test]
+fn test_station_spawns_robot_after_build_time() {
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let mut station =
+        Station::new(rx, tx_cmd).with_economy(Economy::new(CostTable::default(), Box::new(Greedy)));
+
+    station.handle_report(upload(1, 10, 10));
+    let spawned = |rx: &mpsc::Receiver<StationCmd>| {
+        rx.try_iter()
+            .filter(|cmd| matches!(cmd, StationCmd::Spawn { .. }))
+            .count()
+    };
+    assert_eq!(spawned(&rx_cmd), 0);
+    assert_eq!(station.state().queue, vec![BuildItem::full_robot()]);
+
+    station.handle_report(heartbeat(12));
+    assert_eq!(spawned(&rx_cmd), 0);
+    station.handle_report(heartbeat(13));
+    assert_eq!(spawned(&rx_cmd), 1);
+    assert!(station.state().queue.is_empty());
+}
+
+#[test]
+fn test_finished_upgrades_are_kept_as_parts() {
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, _rx_cmd) = mpsc::channel();
+    let mut station = Station::new(rx, tx_cmd).with_economy(Economy::new(
+        CostTable::instant(),
+        Box::new(SavingForUpgrade {
+            target: Upgrade::ScanRadius,
+        }),
+    ));
+
+    station.handle_report(upload(1, 9, 7));
+    assert_eq!(station.parts, vec![BuildItem::Upgrade(Upgrade::ScanRadius)]);
+    assert_eq!(station.state().energy_stock, 1);
+}
+
+#[test]
+fn test_docked_robot_is_fitted_with_matching_parts() {
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let mut station = Station::new(rx, tx_cmd);
+    station.parts = vec![
+        BuildItem::Upgrade(Upgrade::ScanRadius),
+        BuildItem::Upgrade(Upgrade::Speed),
+        BuildItem::Upgrade(Upgrade::ScanRadius),
+        BuildItem::Module(RobotModule::Sensor),
+    ];
+
+    station.handle_report(RobotReport {
+        loadout: vec![(RobotModule::Scanner, 2), (RobotModule::Collector, 1)],
+        ..upload(1, 0, 0)
+    });
+
+    let fitted: Vec<Retrofit> = rx_cmd
+        .try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Retrofit { id: 1, op } => Some(op),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        fitted,
+        vec![
+            Retrofit::Upgrade(RobotModule::Scanner),
+            Retrofit::Install(RobotModule::Sensor),
+        ]
+    );
+    // scanner is maxed out and there is no explorer to speed up
+    assert_eq!(
+        station.parts,
+        vec![
+            BuildItem::Upgrade(Upgrade::Speed),
+            BuildItem::Upgrade(Upgrade::ScanRadius),
+        ]
+    );
+}
+
+#[test]
+fn test_removed_module_is_refunded_with_its_upgrades() {
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let mut station = Station::new(rx, tx_cmd);
+    let remove = |robot_id, module| StationRequest::Remove { robot_id, module };
+    let removals = |rx: &mpsc::Receiver<StationCmd>| -> Vec<(usize, Retrofit)> {
+        rx.try_iter()
+            .filter_map(|cmd| match cmd {
+                StationCmd::Retrofit { id, op } => Some((id, op)),
+                _ => None,
+            })
+            .collect()
+    };
+
+    // nothing is known about a robot before it docks
+    station.handle_request(remove(1, RobotModule::Scanner));
+    assert!(station.parts.is_empty());
+    assert!(removals(&rx_cmd).is_empty());
+
+    station.handle_report(RobotReport {
+        loadout: vec![(RobotModule::Scanner, 2), (RobotModule::Explorer, 1)],
+        ..upload(1, 0, 0)
+    });
+    station.handle_request(remove(1, RobotModule::Scanner));
+    assert_eq!(
+        station.parts,
+        vec![
+            BuildItem::Module(RobotModule::Scanner),
+            BuildItem::Upgrade(Upgrade::ScanRadius),
+        ]
+    );
+    assert_eq!(
+        removals(&rx_cmd),
+        vec![(1, Retrofit::Remove(RobotModule::Scanner))]
+    );
+
+    // already gone: no second refund
+    station.handle_request(remove(1, RobotModule::Scanner));
+    station.handle_request(remove(1, RobotModule::Sensor));
+    assert_eq!(station.parts.len(), 2);
+    assert!(removals(&rx_cmd).is_empty());
+}
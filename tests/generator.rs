@@ -0,0 +1,146 @@
+use rust_project::ascii::tile_glyph;
+use rust_project::generator::{
+    ClassicPerlin, CraterField, InvalidChance, MapGenerator, NoiseConfig, OpenSimplexNoise,
+    PerlinFbm, RidgedNoise, Thresholds,
+};
+use rust_project::map::{Map, Tile};
+
+fn all_generators() -> Vec<Box<dyn MapGenerator>> {
+    vec![
+        Box::new(ClassicPerlin::default()),
+        Box::new(PerlinFbm::default()),
+        Box::new(PerlinFbm::new(NoiseConfig {
+            octaves: 5,
+            ..NoiseConfig::default()
+        })),
+        Box::new(OpenSimplexNoise::default()),
+        Box::new(RidgedNoise::default()),
+        Box::new(CraterField::default()),
+    ]
+}
+
+#[test]
+fn test_generators_dimensions() {
+    for generator in all_generators() {
+        let map = generator.generate(12, 17, 7);
+        assert_eq!(map.grid.len(), 12);
+        assert_eq!(map.cols, 17);
+        for row in &map.grid {
+            assert_eq!(row.len(), 17);
+        }
+    }
+}
+
+#[test]
+fn test_generators_are_deterministic() {
+    for generator in all_generators() {
+        let map1 = generator.generate(20, 20, 4242);
+        let map2 = generator.generate(20, 20, 4242);
+        assert_eq!(map1.grid, map2.grid);
+    }
+}
+
+#[test]
+fn test_height_field_is_normalized() {
+    for generator in all_generators() {
+        for row in generator.height_field(15, 15, 99) {
+            for val in row {
+                assert!((0.0..=1.0).contains(&val), "value out of range: {val}");
+            }
+        }
+    }
+}
+
+/// `Map::generate(10, 16, 7)` as produced before the generator trait existed.
+const CLASSIC_SEED_7: [&str; 10] = [
+    "MEEMS....EMEE###",
+    "EEE......MEEM###",
+    ".S..S.SSEEMEM###",
+    ".SS....EMMMMM###",
+    ".....SEMMMME####",
+    ".....EMEEMMM####",
+    "S.SSMMEMMEMM####",
+    "..SEEEEEMMMME###",
+    ".MEEEEEEEMEEMM##",
+    "MEEEEEMEMEEEEEM#",
+];
+
+#[test]
+fn test_map_generate_matches_original_output() {
+    let map = Map::generate(10, 16, 7);
+    let rows: Vec<String> = map
+        .grid
+        .iter()
+        .map(|row| row.iter().map(|&tile| tile_glyph(tile)).collect())
+        .collect();
+    assert_eq!(rows, CLASSIC_SEED_7);
+}
+
+#[test]
+fn test_thresholds_drive_classification() {
+    let walls = PerlinFbm::new(NoiseConfig {
+        thresholds: Thresholds {
+            obstacle: -1.0,
+            ..Thresholds::default()
+        },
+        ..NoiseConfig::default()
+    });
+    let map = walls.generate(8, 8, 5);
+    assert!(map.grid.iter().flatten().all(|t| *t == Tile::Obstacle));
+
+    let open = CraterField::new(NoiseConfig {
+        thresholds: Thresholds {
+            obstacle: 2.0,
+            resource: 2.0,
+            science: 2.0,
+            ..Thresholds::default()
+        },
+        ..NoiseConfig::default()
+    });
+    let map = open.generate(8, 8, 5);
+    assert!(map.grid.iter().flatten().all(|t| *t == Tile::Empty));
+}
+
+#[test]
+fn test_octaves_change_the_terrain() {
+    let single = PerlinFbm::default().height_field(20, 20, 11);
+    let layered = PerlinFbm::new(NoiseConfig {
+        octaves: 6,
+        ..NoiseConfig::default()
+    })
+    .height_field(20, 20, 11);
+    assert_ne!(single, layered);
+}
+
+#[test]
+fn test_out_of_range_chances_are_rejected() {
+    let bad = Thresholds {
+        mineral_chance: 1.5,
+        ..Thresholds::default()
+    };
+    assert_eq!(
+        bad.validated(),
+        Err(InvalidChance {
+            name: "mineral_chance",
+            value: 1.5
+        })
+    );
+    let negative = Thresholds {
+        science_chance: -0.1,
+        ..Thresholds::default()
+    };
+    assert!(negative.validated().is_err());
+    assert_eq!(Thresholds::default().validated(), Ok(Thresholds::default()));
+
+    // thresholds that skipped validation are clamped instead of panicking
+    let map = PerlinFbm::new(NoiseConfig {
+        thresholds: Thresholds {
+            science: -1.0,
+            ..negative
+        },
+        ..NoiseConfig::default()
+    })
+    .generate(8, 8, 3);
+    assert!(!map.grid.iter().flatten().any(|t| *t == Tile::Science));
+    assert!(map.grid.iter().flatten().any(|t| *t == Tile::Empty));
+}
@@ -0,0 +1,48 @@
+use rust_project::heatmap::{HeatLayer, Heatmaps};
+
+#[test]
+fn test_cycle_goes_through_every_layer_and_off() {
+    let mut layer = None;
+    let mut seen = Vec::new();
+    loop {
+        layer = HeatLayer::cycle(layer);
+        match layer {
+            Some(layer) => seen.push(layer),
+            None => break,
+        }
+    }
+
+    assert_eq!(seen, HeatLayer::ALL);
+}
+
+#[test]
+fn test_counts_are_log_scaled_to_the_busiest_cell() {
+    let mut heat = Heatmaps::new();
+    for _ in 0..15 {
+        heat.record_visit((0, 0));
+    }
+    heat.record_visit((0, 1));
+    heat.record_collection((2, 2));
+
+    let visits = heat.intensities(HeatLayer::Visits);
+    assert_eq!(visits[&(0, 0)], 1.0);
+    assert_eq!(visits[&(0, 1)], 2f64.ln() / 16f64.ln());
+    assert!(!visits.contains_key(&(2, 2)));
+
+    assert_eq!(heat.intensities(HeatLayer::Collections)[&(2, 2)], 1.0);
+    assert!(heat.intensities(HeatLayer::Blocked).is_empty());
+}
+
+#[test]
+fn test_age_grows_until_a_cell_is_seen_again() {
+    let mut heat = Heatmaps::new();
+    heat.record_observed([(0, 0), (0, 1)], 1);
+    heat.record_observed([(0, 1)], 5);
+    heat.record_observed([(1, 1)], 9);
+
+    let age = heat.intensities(HeatLayer::Age);
+    assert_eq!(age[&(0, 0)], 1.0);
+    assert_eq!(age[&(0, 1)], 0.5);
+    assert_eq!(age[&(1, 1)], 0.0);
+    assert!(!age.contains_key(&(2, 2)));
+}
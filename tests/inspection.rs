@@ -0,0 +1,117 @@
+use rust_project::map::Map;
+use rust_project::robot::{find_path, Robot, RobotActor, RobotCmd, RobotModule, ACTION_HISTORY};
+use rust_project::traffic::DockOrder;
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_action_history_keeps_the_latest() {
+    let mut robot = Robot::new(1, (0, 0), vec![]);
+
+    for tick in 0..20 {
+        robot.record(tick, format!("action {}", tick));
+    }
+
+    assert_eq!(robot.actions.len(), ACTION_HISTORY);
+    assert_eq!(robot.actions.back().unwrap().0, 19);
+    assert_eq!(robot.actions.front().unwrap().0, 20 - ACTION_HISTORY as u64);
+}
+
+#[test]
+fn test_smart_move_plans_a_path_to_the_resource() {
+    let map = Map::from_ascii("....E\n.###.\n.....\n").unwrap();
+    let mut robot = Robot::new(1, (2, 4), vec![]);
+
+    robot.smart_move(&map, &HashSet::new());
+
+    assert_eq!(robot.position, (1, 4));
+    assert_eq!(robot.target, Some((0, 4)));
+    assert_eq!(robot.path, vec![(1, 4), (0, 4)]);
+}
+
+#[test]
+fn test_find_path_skips_the_start() {
+    let map = Map::from_ascii("...\n.#.\n...\n").unwrap();
+
+    assert_eq!(
+        find_path((0, 0), (2, 0), &map, &HashSet::new()),
+        Some(vec![(1, 0), (2, 0)])
+    );
+    assert_eq!(
+        find_path((1, 0), (1, 0), &map, &HashSet::new()),
+        Some(vec![])
+    );
+    assert_eq!(find_path((0, 0), (1, 1), &map, &HashSet::new()), None);
+}
+
+#[test]
+fn test_inspect_returns_the_live_robot() {
+    let map = Arc::new(RwLock::new(Map::from_ascii("E...\n").unwrap()));
+    let robot = Robot::new(
+        3,
+        (0, 0),
+        vec![RobotModule::Collector, RobotModule::Explorer],
+    );
+    let (tx, rx) = mpsc::channel();
+    let (tx_report, _rx_report) = mpsc::channel();
+    let actor = RobotActor::new(robot, map, rx, tx_report);
+    let handle = thread::spawn(move || actor.run());
+
+    // the coordinator picks the energy up and loads it
+    tx.send(RobotCmd::Cargo {
+        energy: 1,
+        mineral: 0,
+    })
+    .unwrap();
+    tx.send(RobotCmd::Tick {
+        tick_count: 1,
+        occupied_positions: HashSet::new(),
+        blinded: false,
+        dock: DockOrder::Free,
+    })
+    .unwrap();
+    let (respond_to, robots) = mpsc::channel();
+    tx.send(RobotCmd::Inspect { respond_to }).unwrap();
+    let inspected = robots.recv_timeout(Duration::from_secs(1)).unwrap();
+
+    assert_eq!(inspected.id, 3);
+    assert_eq!(inspected.energy_collected, 1);
+    let actions: Vec<_> = inspected.actions.iter().map(|(_, a)| a.as_str()).collect();
+    assert_eq!(
+        actions,
+        vec!["collected Energy at (0, 0)", "moved to (0, 1)"]
+    );
+
+    tx.send(RobotCmd::Shutdown).unwrap();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_status_tells_whether_the_robot_collects() {
+    let map = Arc::new(RwLock::new(Map::from_ascii("E.\n").unwrap()));
+    for (modules, collects) in [
+        (vec![RobotModule::Explorer], false),
+        (vec![RobotModule::Collector], true),
+    ] {
+        let (tx, rx) = mpsc::channel();
+        let (tx_report, _rx_report) = mpsc::channel();
+        let actor = RobotActor::new(
+            Robot::new(1, (0, 0), modules),
+            Arc::clone(&map),
+            rx,
+            tx_report,
+        );
+        let handle = thread::spawn(move || actor.run());
+
+        let (respond_to, statuses) = mpsc::channel();
+        tx.send(RobotCmd::ReportStatus { respond_to }).unwrap();
+        let status = statuses.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(status.collects, collects);
+
+        tx.send(RobotCmd::Shutdown).unwrap();
+        handle.join().unwrap();
+    }
+}
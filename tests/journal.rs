@@ -0,0 +1,354 @@
+use rust_project::economy::{BuildItem, Economy, Upgrade};
+use rust_project::journal::{Journal, JournalEntry, JournalError, StationState};
+use rust_project::map::{MapDiff, Tile};
+use rust_project::robot::RobotModule;
+use rust_project::station::{DockPhase, RobotReport, Station, StationCmd, StationRequest};
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("journal_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn upload(robot_id: usize, tick: u64, diff: MapDiff, energy: u32, mineral: u32) -> RobotReport {
+    RobotReport {
+        robot_id,
+        station: 0,
+        tick,
+        base_version: 0,
+        map_diff: diff,
+        energy,
+        mineral,
+        dock: DockPhase::Upload,
+        loadout: Vec::new(),
+    }
+}
+
+#[test]
+fn test_entries_round_trip_through_text() {
+    let entries = vec![
+        JournalEntry::Merge {
+            version: 4,
+            tick: 17,
+            diff: MapDiff(vec![
+                ((0, 1), None, Tile::Energy),
+                ((12, 3), Some(Tile::Mineral), Tile::Empty),
+            ]),
+        },
+        JournalEntry::Deposit {
+            energy: 3,
+            mineral: 0,
+        },
+        JournalEntry::Adjust {
+            energy: -40,
+            mineral: i64::MAX,
+        },
+        JournalEntry::Queue {
+            item: BuildItem::full_robot(),
+            energy: 10,
+            mineral: 10,
+        },
+        JournalEntry::Built {
+            item: BuildItem::full_robot(),
+            robot_id: Some(5),
+        },
+        JournalEntry::Built {
+            item: BuildItem::Upgrade(Upgrade::ScanRadius),
+            robot_id: None,
+        },
+        JournalEntry::Removed {
+            item: BuildItem::Module(RobotModule::Scanner),
+            robot_id: 2,
+        },
+        JournalEntry::Spawned { robot_id: 6 },
+    ];
+    for entry in entries {
+        assert_eq!(JournalEntry::parse(&entry.to_string()), Ok(entry));
+    }
+    assert!(JournalEntry::parse("merge 1 2 0,0,x,E").is_err());
+}
+
+#[test]
+fn test_removed_parts_return_to_stock_on_replay() {
+    let mut state = StationState::default();
+    let item = BuildItem::Upgrade(Upgrade::Speed);
+    state.apply(&JournalEntry::Removed {
+        item: item.clone(),
+        robot_id: 1,
+    });
+    assert_eq!(state.parts, vec![item.clone()]);
+    state.apply(&JournalEntry::Fitted { item, robot_id: 2 });
+    assert!(state.parts.is_empty());
+}
+
+#[test]
+fn test_restarted_station_rebuilds_exact_state() {
+    let dir = temp_dir("restart");
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, _rx_cmd) = mpsc::channel();
+    let mut station = Station::new(rx, tx_cmd)
+        .with_journal(Journal::open(&dir).unwrap().with_compact_every(4))
+        .unwrap();
+
+    for tick in 1..=6 {
+        let diff = MapDiff(vec![((tick as usize, 0), None, Tile::Mineral)]);
+        station.handle_report(upload(1, tick, diff, 2, 3));
+    }
+    station.handle_report(upload(
+        2,
+        7,
+        MapDiff(vec![((1, 0), Some(Tile::Mineral), Tile::Empty)]),
+        0,
+        0,
+    ));
+    let before = station.state();
+    assert_eq!(before.next_robot_id, 4, "one robot was built");
+    drop(station);
+
+    let (_tx, rx) = mpsc::channel();
+    let (tx_cmd, _rx_cmd) = mpsc::channel();
+    let restored = Station::new(rx, tx_cmd)
+        .with_journal(Journal::open(&dir).unwrap())
+        .unwrap();
+    assert_eq!(restored.state(), before);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_restarted_station_keeps_outpost_allowance() {
+    let dir = temp_dir("outposts");
+    let open = |dir: &PathBuf| {
+        let (_tx, rx) = mpsc::channel();
+        let (tx_cmd, _rx_cmd) = mpsc::channel();
+        Station::new(rx, tx_cmd)
+            .with_economy(Economy::instant().with_outposts(1))
+            .with_journal(Journal::open(dir).unwrap().with_compact_every(1))
+            .unwrap()
+    };
+
+    let mut station = open(&dir);
+    station.handle_report(upload(1, 1, MapDiff(Vec::new()), 30, 30));
+    assert_eq!(station.take_outposts(), 1);
+    assert_eq!(station.state().outposts_queued, 1);
+    drop(station);
+
+    let mut restored = open(&dir);
+    assert_eq!(restored.state().outposts_queued, 1);
+    restored.handle_report(upload(1, 2, MapDiff(Vec::new()), 30, 30));
+    assert_eq!(restored.take_outposts(), 0);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compaction_folds_log_into_checkpoint() {
+    let dir = temp_dir("compact");
+    let mut journal = Journal::open(&dir).unwrap().with_compact_every(2);
+    let mut state = StationState::default();
+
+    for entry in [
+        JournalEntry::Merge {
+            version: 1,
+            tick: 1,
+            diff: MapDiff(vec![((2, 2), None, Tile::Science)]),
+        },
+        JournalEntry::Deposit {
+            energy: 5,
+            mineral: 1,
+        },
+    ] {
+        journal.append(&entry).unwrap();
+        state.apply(&entry);
+    }
+    assert!(journal.needs_compaction());
+    journal.compact(&state).unwrap();
+    assert!(!journal.needs_compaction());
+    assert!(journal.entries().unwrap().is_empty());
+
+    let queue = JournalEntry::Queue {
+        item: BuildItem::Upgrade(Upgrade::Payload),
+        energy: 5,
+        mineral: 1,
+    };
+    journal.append(&queue).unwrap();
+    state.apply(&queue);
+    journal.compact(&state).unwrap();
+    assert_eq!(Journal::open(&dir).unwrap().load().unwrap(), state);
+
+    let built = JournalEntry::Built {
+        item: BuildItem::Upgrade(Upgrade::Payload),
+        robot_id: None,
+    };
+    journal.append(&built).unwrap();
+    state.apply(&built);
+    assert!(state.queue.is_empty());
+    assert_eq!(state.parts, vec![BuildItem::Upgrade(Upgrade::Payload)]);
+
+    assert_eq!(Journal::open(&dir).unwrap().load().unwrap(), state);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_torn_record_is_discarded_and_corruption_reported() {
+    let dir = temp_dir("torn");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("journal.log"), "1 deposit 4 2\n2 deposit 9").unwrap();
+
+    let mut journal = Journal::open(&dir).unwrap();
+    assert_eq!(journal.load().unwrap().energy_stock, 4);
+    journal
+        .append(&JournalEntry::Deposit {
+            energy: 1,
+            mineral: 1,
+        })
+        .unwrap();
+    assert_eq!(journal.load().unwrap().energy_stock, 5);
+
+    std::fs::write(dir.join("journal.log"), "1 deposit 4 2\n2 what 1\n").unwrap();
+    match Journal::open(&dir).unwrap().load() {
+        Err(JournalError::Corrupt { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected corruption, got {:?}", other),
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_crash_between_checkpoint_and_truncate_does_not_replay_log() {
+    let dir = temp_dir("crash");
+    let mut journal = Journal::open(&dir).unwrap();
+    let mut state = StationState::default();
+    for entry in [
+        JournalEntry::Deposit {
+            energy: 12,
+            mineral: 10,
+        },
+        JournalEntry::Queue {
+            item: BuildItem::full_robot(),
+            energy: 10,
+            mineral: 10,
+        },
+    ] {
+        journal.append(&entry).unwrap();
+        state.apply(&entry);
+    }
+
+    // the checkpoint is renamed into place but the log is never truncated
+    let log = std::fs::read(dir.join("journal.log")).unwrap();
+    journal.compact(&state).unwrap();
+    drop(journal);
+    std::fs::write(dir.join("journal.log"), log).unwrap();
+
+    let mut journal = Journal::open(&dir).unwrap();
+    assert!(journal.entries().unwrap().is_empty());
+    assert!(!journal.needs_compaction());
+    assert_eq!(journal.load().unwrap(), state);
+
+    let deposit = JournalEntry::Deposit {
+        energy: 1,
+        mineral: 0,
+    };
+    journal.append(&deposit).unwrap();
+    state.apply(&deposit);
+    assert_eq!(Journal::open(&dir).unwrap().load().unwrap(), state);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unreadable_log_is_reported_not_truncated() {
+    let dir = temp_dir("utf8");
+    std::fs::create_dir_all(&dir).unwrap();
+    let log = b"1 deposit 4 2\n2 deposit \xff 1\n".to_vec();
+    std::fs::write(dir.join("journal.log"), &log).unwrap();
+
+    match Journal::open(&dir) {
+        Err(JournalError::Corrupt { line, .. }) => assert_eq!(line, 2),
+        Err(other) => panic!("expected corruption, got {:?}", other),
+        Ok(_) => panic!("expected corruption"),
+    }
+    assert_eq!(std::fs::read(dir.join("journal.log")).unwrap(), log);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_console_spawn_ids_survive_a_restart() {
+    let dir = temp_dir("spawns");
+    let open = |compact_every| {
+        let (_tx, rx) = mpsc::channel();
+        let (tx_cmd, rx_cmd) = mpsc::channel();
+        let station = Station::new(rx, tx_cmd)
+            .with_journal(
+                Journal::open(&dir)
+                    .unwrap()
+                    .with_compact_every(compact_every),
+            )
+            .unwrap();
+        let spawned: Vec<_> = rx_cmd
+            .try_iter()
+            .filter_map(|cmd| match cmd {
+                StationCmd::Spawn { id, start_pos, .. } => Some((id, start_pos)),
+                _ => None,
+            })
+            .collect();
+        (station, spawned)
+    };
+    let spawn = |position| StationRequest::Spawn {
+        modules: vec![RobotModule::Collector],
+        position,
+    };
+
+    let (mut station, spawned) = open(64);
+    assert!(spawned.is_empty());
+    station.handle_request(spawn((2, 3)));
+    drop(station);
+
+    // from the log, then from the checkpoint; the robots are not relaunched
+    let (mut station, spawned) = open(1);
+    assert!(spawned.is_empty());
+    assert_eq!(station.state().next_robot_id, 4);
+    station.handle_request(spawn((5, 1)));
+    assert_eq!(station.state().next_robot_id, 5);
+    drop(station);
+
+    let (station, spawned) = open(1);
+    assert!(spawned.is_empty());
+    assert_eq!(station.state().next_robot_id, 5);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_stock_adjustments_replay_with_the_clamp() {
+    let dir = temp_dir("adjust");
+    let open = |compact_every| {
+        let (_tx, rx) = mpsc::channel();
+        let (tx_cmd, _rx_cmd) = mpsc::channel();
+        Station::new(rx, tx_cmd)
+            .with_journal(
+                Journal::open(&dir)
+                    .unwrap()
+                    .with_compact_every(compact_every),
+            )
+            .unwrap()
+    };
+    let adjust = |energy, mineral| StationRequest::AdjustStocks { energy, mineral };
+
+    let mut station = open(64);
+    station.handle_request(adjust(7, 3));
+    station.handle_request(adjust(-10, i64::MAX));
+    station.handle_request(adjust(2, -1));
+    let expected = station.stocks();
+    assert_eq!((expected.energy, expected.mineral), (2, u32::MAX - 1));
+    drop(station);
+
+    // the log alone holds the adjustments, no checkpoint was written
+    assert!(!dir.join("checkpoint.txt").exists());
+    let mut station = open(1);
+    assert_eq!(station.stocks(), expected);
+    station.handle_request(adjust(-5, 1));
+    drop(station);
+
+    let station = open(1);
+    assert_eq!(station.state().energy_stock, 0);
+    assert_eq!(station.state().mineral_stock, u32::MAX);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,43 @@
+use rust_project::knowledge::{stale_cells, unexplored, Belief, MapLayer};
+use rust_project::map::{Map, Tile};
+use std::collections::HashSet;
+
+#[test]
+fn test_layers_cycle_back_to_truth() {
+    let mut layer = MapLayer::default();
+    let mut seen = Vec::new();
+    for _ in 0..MapLayer::ALL.len() {
+        seen.push(layer);
+        layer = layer.next();
+    }
+
+    assert_eq!(seen, MapLayer::ALL);
+    assert_eq!(layer, MapLayer::Truth);
+}
+
+#[test]
+fn test_stale_cells_ignore_unknown_and_correct_cells() {
+    let map = Map::from_ascii(".E.\n#..\n").unwrap();
+    let belief = Belief::from([
+        ((0, 0), Tile::Empty),
+        ((0, 1), Tile::Empty),
+        ((1, 0), Tile::Obstacle),
+        ((1, 2), Tile::Mineral),
+        ((9, 9), Tile::Empty),
+    ]);
+
+    assert_eq!(stale_cells(&map, &belief), HashSet::from([(0, 1), (1, 2)]));
+}
+
+#[test]
+fn test_unexplored_is_what_nobody_knows() {
+    let map = Map::from_ascii("...\n...\n").unwrap();
+    let station = Belief::from([((0, 0), Tile::Empty), ((0, 1), Tile::Empty)]);
+    let robot = Belief::from([((0, 1), Tile::Empty), ((1, 2), Tile::Empty)]);
+
+    assert_eq!(
+        unexplored(&map, [&station, &robot]),
+        HashSet::from([(0, 2), (1, 0), (1, 1)])
+    );
+    assert_eq!(unexplored(&map, []).len(), 6);
+}
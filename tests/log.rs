@@ -0,0 +1,154 @@
+use rust_project::log::{
+    Category, Level, LogBook, LogFilter, LogRecord, LogScroll, Source, LOG_CAPACITY,
+};
+use rust_project::map::Map;
+use rust_project::robot::{Robot, RobotActor, RobotCmd, RobotModule};
+use rust_project::traffic::DockOrder;
+use std::collections::HashSet;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+fn record(level: Level, source: Source, category: Category, message: &str) -> LogRecord {
+    LogRecord::new(1, level, source, category, message)
+}
+
+#[test]
+fn test_filter_by_level_robot_category_and_text() {
+    let docked = record(
+        Level::Info,
+        Source::Station(0),
+        Category::Dock,
+        "Robot 4 docked",
+    )
+    .about(4);
+    let moved = record(
+        Level::Debug,
+        Source::Robot(4),
+        Category::Movement,
+        "moved to (1, 2)",
+    );
+    let failed = record(
+        Level::Error,
+        Source::Station(0),
+        Category::Journal,
+        "Journal write failed",
+    );
+
+    let mut filter = LogFilter::default();
+    assert!(filter.matches(&docked));
+    assert!(!filter.matches(&moved));
+    assert!(filter.matches(&failed));
+
+    filter.min_level = Level::Debug;
+    filter.robot = Some(4);
+    assert!(filter.matches(&docked));
+    assert!(filter.matches(&moved));
+    assert!(!filter.matches(&failed));
+
+    filter.category = Some(Category::Dock);
+    filter.search = "DOCKED".to_string();
+    assert!(filter.matches(&docked));
+    assert!(!filter.matches(&moved));
+    assert_eq!(filter.summary(), ">=debug robot 4 dock /DOCKED");
+}
+
+#[test]
+fn test_logbook_keeps_the_latest_and_mirrors_everything() {
+    let path = std::env::temp_dir().join(format!("log_mirror_{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut book = LogBook::new().with_mirror(&path).unwrap();
+
+    for tick in 0..LOG_CAPACITY as u64 + 10 {
+        book.push(LogRecord::new(
+            tick,
+            Level::Info,
+            Source::Coordinator,
+            Category::World,
+            format!("event {}", tick),
+        ));
+    }
+
+    assert_eq!(book.records().len(), LOG_CAPACITY);
+    assert_eq!(book.records().front().unwrap().tick, 10);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), LOG_CAPACITY + 10);
+    assert_eq!(
+        text.lines().next(),
+        Some("   0 info  coordinator [world] event 0")
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test_scrolling_up_stops_following() {
+    let mut scroll = LogScroll::default();
+    assert_eq!(scroll.first_line(30, 10), 20);
+
+    scroll.scroll(-3, 30, 10);
+    assert!(!scroll.follow);
+    assert_eq!(scroll.first_line(30, 10), 17);
+    // new records don't move the view
+    assert_eq!(scroll.first_line(40, 10), 17);
+
+    scroll.scroll(100, 40, 10);
+    assert!(scroll.follow);
+    assert_eq!(scroll.first_line(45, 10), 35);
+}
+
+#[test]
+fn test_cycles_wrap_around() {
+    assert_eq!(Level::Error.next(), Level::Debug);
+    let mut category = None;
+    for _ in 0..Category::ALL.len() {
+        category = Category::cycle(category);
+        assert!(category.is_some());
+    }
+    assert_eq!(Category::cycle(category), None);
+}
+
+#[test]
+fn test_robot_actions_are_logged() {
+    let map = Arc::new(RwLock::new(Map::from_ascii("E...\n").unwrap()));
+    let robot = Robot::new(
+        5,
+        (0, 0),
+        vec![RobotModule::Collector, RobotModule::Explorer],
+    );
+    let (tx, rx) = mpsc::channel();
+    let (tx_report, _rx_report) = mpsc::channel();
+    let (tx_log, rx_log) = mpsc::channel();
+    let actor = RobotActor::new(robot, map, rx, tx_report).with_log(tx_log);
+    let handle = thread::spawn(move || actor.run());
+
+    tx.send(RobotCmd::Cargo {
+        energy: 1,
+        mineral: 0,
+    })
+    .unwrap();
+    tx.send(RobotCmd::Tick {
+        tick_count: 7,
+        occupied_positions: HashSet::new(),
+        blinded: false,
+        dock: DockOrder::Free,
+    })
+    .unwrap();
+    tx.send(RobotCmd::Shutdown).unwrap();
+    handle.join().unwrap();
+
+    let records: Vec<_> = rx_log.try_iter().collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(
+        records[0],
+        LogRecord::new(
+            7,
+            Level::Info,
+            Source::Robot(5),
+            Category::Collection,
+            "collected Energy at (0, 0)",
+        )
+    );
+    assert_eq!(records[0].robot, Some(5));
+    assert_eq!(records[1].level, Level::Debug);
+    assert_eq!(records[1].category, Category::Movement);
+}
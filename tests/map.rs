@@ -1,6 +1,6 @@
-use rust_project::map::Tile;
 use rust_project::map::Map;
-
+use rust_project::map::MapDiff;
+use rust_project::map::Tile;
 
 #[cfg(test)]
 mod tests {
@@ -57,4 +57,21 @@ mod tests {
             assert_eq!(row.len(), 5);
         }
     }
+
+    #[test]
+    fn test_compact_keeps_first_before_and_last_after() {
+        let diff = MapDiff(vec![
+            ((1, 1), Some(Tile::Energy), Tile::Empty),
+            ((2, 2), None, Tile::Science),
+            ((1, 1), Some(Tile::Empty), Tile::Obstacle),
+        ])
+        .compact();
+        assert_eq!(
+            diff,
+            MapDiff(vec![
+                ((1, 1), Some(Tile::Energy), Tile::Obstacle),
+                ((2, 2), None, Tile::Science),
+            ])
+        );
+    }
 }
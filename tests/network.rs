@@ -0,0 +1,209 @@
+use rust_project::economy::Economy;
+use rust_project::map::{MapDiff, Tile};
+use rust_project::network::StationNetwork;
+use rust_project::robot::{Robot, RobotModule};
+use rust_project::station::{
+    DockPhase, RobotReport, Station, StationCmd, StationRequest, StationSite,
+};
+use std::sync::mpsc;
+
+fn upload(robot_id: usize, station: usize, tick: u64, diff: MapDiff) -> RobotReport {
+    RobotReport {
+        robot_id,
+        station,
+        tick,
+        base_version: 0,
+        map_diff: diff,
+        energy: 0,
+        mineral: 0,
+        dock: DockPhase::Upload,
+        loadout: Vec::new(),
+    }
+}
+
+fn two_stations() -> (StationNetwork, mpsc::Receiver<StationCmd>) {
+    let (_, rx_report) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let main = Station::detached(tx_cmd.clone());
+    let far = Station::detached(tx_cmd.clone())
+        .with_id(1)
+        .with_position((10, 10));
+    let network = StationNetwork::new(rx_report, tx_cmd, main).with_station(far);
+    (network, rx_cmd)
+}
+
+fn spawned_ids(rx: &mpsc::Receiver<StationCmd>) -> Vec<usize> {
+    rx.try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Spawn { id, .. } => Some(id),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_uploads_go_to_the_docking_station() {
+    let (mut network, _rx) = two_stations();
+
+    network.handle_report(RobotReport {
+        energy: 3,
+        mineral: 1,
+        ..upload(1, 1, 1, MapDiff(Vec::new()))
+    });
+
+    assert_eq!(network.station(1).unwrap().stocks().energy, 3);
+    assert_eq!(network.station(1).unwrap().stocks().mineral, 1);
+    assert_eq!(network.station(0).unwrap().stocks().energy, 0);
+}
+
+#[test]
+fn test_merges_are_relayed_over_the_link() {
+    let (mut network, _rx) = two_stations();
+
+    network.handle_report(upload(
+        1,
+        0,
+        1,
+        MapDiff(vec![((2, 3), None, Tile::Mineral)]),
+    ));
+
+    let far = network.station(1).unwrap();
+    assert_eq!(far.master_map.get(&(2, 3)), Some(&Tile::Mineral));
+    assert_eq!(far.map_version(), 1);
+    // the relayed change is not echoed back as a new version
+    assert_eq!(network.station(0).unwrap().map_version(), 1);
+
+    network.handle_report(upload(
+        2,
+        1,
+        2,
+        MapDiff(vec![((2, 3), Some(Tile::Mineral), Tile::Empty)]),
+    ));
+    assert_eq!(
+        network.station(0).unwrap().master_map.get(&(2, 3)),
+        Some(&Tile::Empty)
+    );
+}
+
+#[test]
+fn test_coordinator_updates_reach_every_station() {
+    let (mut network, _rx) = two_stations();
+
+    network.handle_report(RobotReport {
+        dock: DockPhase::None,
+        ..upload(0, 0, 1, MapDiff(vec![((5, 5), None, Tile::Obstacle)]))
+    });
+
+    for station in network.stations() {
+        assert_eq!(station.master_map.get(&(5, 5)), Some(&Tile::Obstacle));
+    }
+}
+
+#[test]
+fn test_stations_share_robot_ids() {
+    let (mut network, rx) = two_stations();
+
+    for (robot_id, station) in [(1, 0), (2, 1)] {
+        network.handle_report(RobotReport {
+            energy: 10,
+            mineral: 10,
+            ..upload(robot_id, station, robot_id as u64, MapDiff(Vec::new()))
+        });
+    }
+
+    assert_eq!(spawned_ids(&rx), vec![3, 4]);
+}
+
+#[test]
+fn test_outpost_is_placed_far_from_the_station() {
+    let (_, rx_report) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let main = Station::detached(tx_cmd.clone()).with_economy(Economy::instant().with_outposts(1));
+    let mut network = StationNetwork::new(rx_report, tx_cmd, main);
+
+    let known = (0..13).map(|c| ((0, c), None, Tile::Empty)).collect();
+    network.handle_report(RobotReport {
+        energy: 30,
+        mineral: 30,
+        ..upload(1, 0, 1, MapDiff(known))
+    });
+
+    assert_eq!(network.stations().len(), 2);
+    let outpost = network.station(1).unwrap();
+    assert_eq!(outpost.position(), (0, 12));
+    assert_eq!(outpost.master_map.len(), 13);
+    assert!(rx_cmd.try_iter().any(|cmd| matches!(
+        cmd,
+        StationCmd::StationOnline(StationSite {
+            id: 1,
+            position: (0, 12),
+            ..
+        })
+    )));
+}
+
+#[test]
+fn test_outpost_waits_for_a_site() {
+    let (_, rx_report) = mpsc::channel();
+    let (tx_cmd, _rx_cmd) = mpsc::channel();
+    let main = Station::detached(tx_cmd.clone()).with_economy(Economy::instant().with_outposts(1));
+    let mut network = StationNetwork::new(rx_report, tx_cmd, main);
+
+    let near = (0..4).map(|c| ((0, c), None, Tile::Empty)).collect();
+    network.handle_report(RobotReport {
+        energy: 30,
+        mineral: 30,
+        ..upload(1, 0, 1, MapDiff(near))
+    });
+    assert_eq!(network.stations().len(), 1);
+
+    network.handle_report(upload(1, 0, 2, MapDiff(vec![((9, 9), None, Tile::Empty)])));
+    assert_eq!(network.stations().len(), 2);
+    assert_eq!(network.station(1).unwrap().position(), (9, 9));
+}
+
+#[test]
+fn test_robot_returns_to_nearest_or_assigned_station() {
+    let site = |id, position| StationSite {
+        id,
+        position,
+        dock_radius: 1,
+    };
+    let stations = [site(0, (0, 0)), site(1, (10, 10))];
+    let mut robot = Robot::new(1, (8, 7), vec![]);
+
+    assert_eq!(robot.home(&stations).map(|home| home.id), Some(1));
+
+    robot.station = Some(0);
+    assert_eq!(robot.home(&stations).map(|home| home.id), Some(0));
+
+    robot.station = Some(7);
+    assert_eq!(robot.home(&stations).map(|home| home.id), Some(1));
+    assert_eq!(robot.home(&[]), None);
+}
+
+#[test]
+fn test_requests_go_to_the_main_station() {
+    let (network, rx_cmd) = two_stations();
+    let (tx_request, rx_request) = mpsc::channel();
+    let mut network = network.with_requests(rx_request);
+    rx_cmd.try_iter().for_each(drop);
+
+    tx_request
+        .send(StationRequest::Spawn {
+            modules: vec![RobotModule::Explorer],
+            position: (3, 3),
+        })
+        .unwrap();
+    assert!(spawned_ids(&rx_cmd).is_empty());
+
+    // requests are picked up with the next report
+    network.handle_report(RobotReport {
+        dock: DockPhase::None,
+        ..upload(0, 0, 1, MapDiff(Vec::new()))
+    });
+    let spawned = spawned_ids(&rx_cmd);
+    assert_eq!(spawned.len(), 1);
+    assert_eq!(network.stations()[0].awaiting_ack(spawned[0]), Some(0));
+    assert_eq!(network.stations()[1].awaiting_ack(spawned[0]), None);
+}
@@ -1,13 +1,14 @@
-use rust_project::map::Tile;
 use rust_project::map::Map;
-use rust_project::robot::RobotModule;
+use rust_project::map::MapDiff;
+use rust_project::map::Tile;
 use rust_project::robot::Robot;
+use rust_project::robot::RobotModule;
 use rust_project::robot::RobotState;
-use rust_project::map::MapDiff;
+use rust_project::robot::{Retrofit, RetrofitError};
+use rust_project::scanner::{ScanShape, ScannerConfig};
+use rust_project::station::DockPhase;
 use std::collections::HashSet;
 
-
-
 #[cfg(test)]
 mod tests {
 
@@ -15,7 +16,7 @@ mod tests {
 
     // TEST ROBOT CREATION -------------------------------------------------------------------------------------
     #[test]
-    fn test_robot_creation() { 
+    fn test_robot_creation() {
         let robot_id = 1;
         let start_pos = (5, 10);
         let modules = vec![RobotModule::Explorer, RobotModule::Collector];
@@ -34,23 +35,21 @@ mod tests {
     }
 
     fn create_test_map() -> Map {
-        let grid = vec![
-            vec![Tile::Energy, Tile::Mineral, Tile::Obstacle, Tile::Empty, Tile::Science],
-            vec![Tile::Empty,  Tile::Energy,  Tile::Mineral, Tile::Empty, Tile::Obstacle],
-            vec![Tile::Empty,  Tile::Science, Tile::Empty,   Tile::Energy, Tile::Empty],
-            vec![Tile::Mineral, Tile::Empty,  Tile::Obstacle, Tile::Empty, Tile::Science],
-            vec![Tile::Empty,  Tile::Empty,   Tile::Empty,   Tile::Empty, Tile::Empty],
-        ];
-        Map { grid, cols: 5 }
+        Map::from_ascii(
+            "EM#.S\n\
+             .EM.#\n\
+             .S.E.\n\
+             M.#.S\n\
+             .....\n",
+        )
+        .unwrap()
     }
 
-
-
     // TEST SCAN SURROUNDINGS -------------------------------------------------------------------------------------
     #[test]
     fn test_scan_surroundings_center() {
         let map = create_test_map();
-        let mut robot = Robot::new(1, (2, 2), vec![RobotModule::Explorer]);
+        let mut robot = Robot::new(1, (2, 2), vec![RobotModule::Explorer, RobotModule::Scanner]);
         robot.scan_surroundings(&map);
         let expected = vec![
             ((1, 1), Tile::Energy),
@@ -66,14 +65,20 @@ mod tests {
 
         assert_eq!(robot.known_map.len(), 9);
         for (pos, expected_tile) in expected.iter() {
-            assert_eq!(robot.known_map.get(pos), Some(expected_tile), "Wrong or missing tile at {:?}", pos);
+            assert_eq!(
+                robot.known_map.get(pos),
+                Some(expected_tile),
+                "Wrong or missing tile at {:?}",
+                pos
+            );
         }
 
         assert_eq!(robot.dirty_tiles.len(), 9);
         for (pos, expected_tile) in expected {
             assert!(
                 robot.dirty_tiles.contains(&(pos, None, expected_tile)),
-                "Missing dirty tile at {:?}", pos
+                "Missing dirty tile at {:?}",
+                pos
             );
         }
     }
@@ -81,7 +86,11 @@ mod tests {
     #[test]
     fn test_scan_surroundings_top_left_corner() {
         let map = create_test_map();
-        let mut robot = Robot::new(2, (0, 0), vec![RobotModule::Collector]);
+        let mut robot = Robot::new(
+            2,
+            (0, 0),
+            vec![RobotModule::Collector, RobotModule::Scanner],
+        );
 
         robot.scan_surroundings(&map);
 
@@ -104,7 +113,11 @@ mod tests {
     #[test]
     fn test_scan_surroundings_bottom_right_corner() {
         let map = create_test_map();
-        let mut robot = Robot::new(3, (4, 4), vec![RobotModule::Collector]);
+        let mut robot = Robot::new(
+            3,
+            (4, 4),
+            vec![RobotModule::Collector, RobotModule::Scanner],
+        );
 
         robot.scan_surroundings(&map);
 
@@ -124,8 +137,6 @@ mod tests {
         }
     }
 
-
-
     //TEST SMART MOVE -------------------------------------------------------------------------------------
     #[test]
     fn test_robot_moves_to_nearest_resource() {
@@ -142,7 +153,11 @@ mod tests {
         let mut robot = Robot::new(1, (0, 1), vec![RobotModule::Explorer]);
         let occupied = HashSet::new();
         robot.smart_move(&map, &occupied);
-        assert_ne!(robot.position, (0, 2), "Robot should not move into an obstacle");
+        assert_ne!(
+            robot.position,
+            (0, 2),
+            "Robot should not move into an obstacle"
+        );
     }
 
     #[test]
@@ -160,7 +175,7 @@ mod tests {
         let map = Map {
             grid: vec![
                 vec![Tile::Obstacle, Tile::Obstacle, Tile::Obstacle],
-                vec![Tile::Obstacle, Tile::Empty,    Tile::Obstacle],
+                vec![Tile::Obstacle, Tile::Empty, Tile::Obstacle],
                 vec![Tile::Obstacle, Tile::Obstacle, Tile::Obstacle],
             ],
             cols: 3,
@@ -168,43 +183,51 @@ mod tests {
         let mut robot = Robot::new(1, (1, 1), vec![RobotModule::Explorer]);
         let occupied = HashSet::new();
         robot.smart_move(&map, &occupied);
-        assert_eq!(robot.position, (1, 1), "Robot should not move if surrounded");
+        assert_eq!(
+            robot.position,
+            (1, 1),
+            "Robot should not move if surrounded"
+        );
     }
 
-
     // TEST SCAN FOR ROBOTS -------------------------------------------------------------------------------------
     fn create_robot_at(id: usize, position: (usize, usize)) -> Robot {
         Robot::new(id, position, vec![RobotModule::Explorer])
     }
-    
+
     #[test]
     fn test_no_robots_nearby() {
         let robot = create_robot_at(1, (2, 2));
         let other_robots = vec![
-            (2, (0, 0)), 
-            (3, (0, 1)), 
+            (2, (0, 0)),
+            (3, (0, 1)),
             (4, (1, 0)),
-            (7, (4, 4)), 
-            (8, (5, 5)), 
-            (9, (6, 6)), 
-            (10, (7, 7)), 
-            (3, (4, 4)), 
+            (7, (4, 4)),
+            (8, (5, 5)),
+            (9, (6, 6)),
+            (10, (7, 7)),
+            (3, (4, 4)),
         ];
         let nearby = robot.scan_for_robots(&other_robots);
         println!("Nearby robots: {:?}", nearby);
         assert!(nearby.is_empty(), "No robots should be detected nearby.");
     }
-    
+
     #[test]
     fn test_robot_detects_all_adjacent_robots() {
         let robot = create_robot_at(1, (2, 2));
         let mut snapshots = Vec::new();
         let surrounding_coords = vec![
-            (1, 1), (1, 2), (1, 3),
-            (2, 1),         (2, 3),
-            (3, 1), (3, 2), (3, 3),
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 1),
+            (2, 3),
+            (3, 1),
+            (3, 2),
+            (3, 3),
         ];
-    
+
         for (i, pos) in surrounding_coords.iter().enumerate() {
             snapshots.push((i + 2, *pos)); // we do not push i=1
         }
@@ -212,46 +235,34 @@ mod tests {
         let expected: HashSet<_> = surrounding_coords.into_iter().collect();
         assert_eq!(nearby, expected);
     }
-    
+
     #[test]
     fn test_robot_does_not_detect_itself() {
         let robot = create_robot_at(1, (2, 2));
-        let snapshots = vec![
-            (1, (2, 1)), 
-            (2, (2, 3)), 
-        ];
+        let snapshots = vec![(1, (2, 1)), (2, (2, 3))];
         let nearby = robot.scan_for_robots(&snapshots);
         assert_eq!(nearby.len(), 1);
         assert!(nearby.contains(&(2, 3)));
         assert!(!nearby.contains(&(2, 1)), "Robot should not detect itself.");
     }
-    
+
     #[test]
     fn test_edge_wrapping_is_handled() {
         let robot = create_robot_at(1, (0, 0));
-        let snapshots = vec![
-            (2, (0, 1)),
-            (3, (1, 0)),
-            (4, (1, 1)),
-        ];
+        let snapshots = vec![(2, (0, 1)), (3, (1, 0)), (4, (1, 1))];
         let nearby = robot.scan_for_robots(&snapshots);
         let expected: HashSet<_> = vec![(0, 1), (1, 0), (1, 1)].into_iter().collect();
         assert_eq!(nearby, expected);
     }
 
-
-
     // TEST MAKE REPORT -------------------------------------------------------------------------------------
     #[test]
     fn test_make_report_returns_correct_data_and_resets_robot() {
         let mut robot = Robot::new(42, (3, 3), vec![RobotModule::Collector]);
         robot.energy_collected = 7;
         robot.mineral_collected = 3;
-        robot.dirty_tiles = vec![
-            ((2, 2), None, Tile::Energy),
-            ((3, 3), None, Tile::Mineral),
-        ];
-        let report = robot.make_report(100);
+        robot.dirty_tiles = vec![((2, 2), None, Tile::Energy), ((3, 3), None, Tile::Mineral)];
+        let report = robot.make_report(0, 100);
 
         assert_eq!(report.robot_id, 42);
         assert_eq!(report.energy, 7);
@@ -271,15 +282,122 @@ mod tests {
     #[test]
     fn test_make_report_with_empty_fields() {
         let mut robot = Robot::new(5, (0, 0), vec![]);
-        
-        let report = robot.make_report(100);
+
+        let report = robot.make_report(0, 100);
 
         assert_eq!(report.robot_id, 5);
         assert_eq!(report.energy, 0);
         assert_eq!(report.mineral, 0);
         assert!(report.map_diff.0.is_empty());
+        assert_eq!(report.dock, DockPhase::Upload);
+    }
+
+    #[test]
+    fn test_make_ack_carries_version_and_no_cargo() {
+        let mut robot = Robot::new(5, (0, 0), vec![RobotModule::Collector]);
+        robot.energy_collected = 4;
+        robot.known_versions.insert(0, 9);
+
+        let ack = robot.make_ack(0, 9);
+
+        assert_eq!(ack.robot_id, 5);
+        assert_eq!(ack.dock, DockPhase::Ack(9));
+        assert_eq!(ack.energy, 0);
+        assert!(ack.map_diff.0.is_empty());
+        assert_eq!(robot.energy_collected, 4);
+    }
+
+    // TEST RETROFIT -------------------------------------------------------------------------------------
+    #[test]
+    fn test_capabilities_follow_module_levels() {
+        let mut robot = Robot::new(
+            1,
+            (0, 0),
+            vec![
+                RobotModule::Explorer,
+                RobotModule::Collector,
+                RobotModule::Scanner,
+            ],
+        );
+        let caps = robot.capabilities();
+        assert_eq!((caps.scan_radius, caps.payload, caps.speed), (1, 10, 1));
+
+        robot
+            .retrofit(&Retrofit::Upgrade(RobotModule::Scanner))
+            .unwrap();
+        robot
+            .retrofit(&Retrofit::Upgrade(RobotModule::Collector))
+            .unwrap();
+        robot
+            .retrofit(&Retrofit::Upgrade(RobotModule::Explorer))
+            .unwrap();
+        let caps = robot.capabilities();
+        assert_eq!((caps.scan_radius, caps.payload, caps.speed), (2, 15, 2));
+
+        robot
+            .retrofit(&Retrofit::Remove(RobotModule::Explorer))
+            .unwrap();
+        assert_eq!(robot.capabilities().speed, 0);
+        assert_eq!(robot.level(&RobotModule::Explorer), 0);
+    }
+
+    #[test]
+    fn test_retrofit_rejects_invalid_changes() {
+        let mut robot = Robot::new(1, (0, 0), vec![RobotModule::Explorer]);
+
+        assert_eq!(
+            robot.retrofit(&Retrofit::Install(RobotModule::Explorer)),
+            Err(RetrofitError::AlreadyInstalled)
+        );
+        assert_eq!(
+            robot.retrofit(&Retrofit::Upgrade(RobotModule::Scanner)),
+            Err(RetrofitError::NotInstalled)
+        );
+        robot
+            .retrofit(&Retrofit::Upgrade(RobotModule::Explorer))
+            .unwrap();
+        assert_eq!(
+            robot.retrofit(&Retrofit::Upgrade(RobotModule::Explorer)),
+            Err(RetrofitError::MaxLevel)
+        );
+
+        robot
+            .retrofit(&Retrofit::Install(RobotModule::Sensor))
+            .unwrap();
+        assert_eq!(
+            robot.loadout(),
+            vec![(RobotModule::Explorer, 2), (RobotModule::Sensor, 1)]
+        );
     }
 
+    #[test]
+    fn test_upgraded_scanner_sees_further() {
+        let map = create_test_map();
+        let mut robot = Robot::new(1, (2, 2), vec![RobotModule::Scanner]);
+        robot.scanner = ScannerConfig {
+            shape: ScanShape::Square,
+            line_of_sight: false,
+            ..ScannerConfig::default()
+        };
+        robot
+            .retrofit(&Retrofit::Upgrade(RobotModule::Scanner))
+            .unwrap();
+
+        robot.scan_surroundings(&map);
+
+        assert_eq!(robot.known_map.len(), 25);
+        assert_eq!(robot.known_map.get(&(0, 4)), Some(&Tile::Science));
+    }
+
+    #[test]
+    fn test_robot_without_scanner_sees_nothing() {
+        let map = create_test_map();
+        let mut robot = Robot::new(1, (2, 2), vec![RobotModule::Explorer]);
+
+        assert!(robot.field_of_view(&map).is_empty());
+        robot.scan_surroundings(&map);
+        assert!(robot.known_map.is_empty());
+    }
 
     // TEST STEP TOWARDS -------------------------------------------------------------------------------------
     #[test]
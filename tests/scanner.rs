@@ -0,0 +1,77 @@
+use rust_project::map::{Map, Tile};
+use rust_project::robot::{Robot, RobotModule};
+use rust_project::scanner::{ScanShape, ScannerConfig};
+
+fn open_map() -> Map {
+    Map::from_ascii(&".......\n".repeat(7)).unwrap()
+}
+
+fn config(shape: ScanShape, line_of_sight: bool) -> ScannerConfig {
+    ScannerConfig {
+        radius: 2,
+        shape,
+        line_of_sight,
+    }
+}
+
+#[test]
+fn test_shapes_cover_expected_cells() {
+    let map = open_map();
+    let count = |shape| config(shape, false).cells(&map, (3, 3), 2).len();
+
+    assert_eq!(count(ScanShape::Square), 25);
+    assert_eq!(count(ScanShape::Diamond), 13);
+    assert_eq!(count(ScanShape::Circle), 21);
+    assert_eq!(
+        config(ScanShape::Circle, false)
+            .cells(&map, (3, 3), 1)
+            .len(),
+        9
+    );
+}
+
+#[test]
+fn test_scan_is_clipped_at_map_edges() {
+    let map = open_map();
+    let cells = config(ScanShape::Square, false).cells(&map, (0, 6), 2);
+    assert_eq!(cells.len(), 9);
+    assert!(cells.contains(&(2, 4)));
+}
+
+#[test]
+fn test_obstacles_hide_cells_behind_them() {
+    let map = Map::from_ascii(
+        ".....\n\
+         .....\n\
+         ..#..\n\
+         .....\n\
+         .....\n",
+    )
+    .unwrap();
+
+    let cells = config(ScanShape::Square, true).cells(&map, (2, 0), 4);
+    assert!(cells.contains(&(2, 2)), "the obstacle itself is seen");
+    assert!(!cells.contains(&(2, 3)));
+    assert!(!cells.contains(&(2, 4)));
+    assert!(cells.contains(&(0, 4)));
+
+    let open = config(ScanShape::Square, false).cells(&map, (2, 0), 4);
+    assert!(open.contains(&(2, 4)));
+}
+
+#[test]
+fn test_only_new_or_changed_cells_are_dirty() {
+    let mut map = open_map();
+    let mut robot = Robot::new(1, (3, 3), vec![RobotModule::Scanner]);
+
+    robot.scan_surroundings(&map);
+    assert_eq!(robot.dirty_tiles.len(), 9);
+    robot.dirty_tiles.clear();
+
+    robot.scan_surroundings(&map);
+    assert!(robot.dirty_tiles.is_empty());
+
+    map.grid[2][4] = Tile::Energy;
+    robot.scan_surroundings(&map);
+    assert_eq!(robot.dirty_tiles, vec![((2, 4), None, Tile::Energy)]);
+}
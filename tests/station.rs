@@ -1,6 +1,8 @@
 use rust_project::map::{MapDiff, Tile};
 use rust_project::robot::RobotModule;
-use rust_project::station::{RobotReport, Station, StationCmd};
+use rust_project::station::{
+    DockPhase, Resolution, RobotReport, Station, StationCmd, StationQuery, StationRequest,
+};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 
@@ -22,10 +24,14 @@ fn test_station_report_processing_and_spawning() {
     tx_report
         .send(RobotReport {
             robot_id: 1,
+            station: 0,
             tick: 1,
+            base_version: 0,
             map_diff,
             energy: 10,
             mineral: 10,
+            dock: DockPhase::Upload,
+            loadout: Vec::new(),
         })
         .unwrap();
 
@@ -40,14 +46,14 @@ fn test_station_report_processing_and_spawning() {
             match msg {
                 StationCmd::Log(log) => {
                     println!("Log: {log}");
-                    if log.contains("Merged") {
+                    if log.message.contains("Merged") {
                         log_found = true;
                     }
                 }
                 StationCmd::Spawn {
                     id,
                     modules,
-                    start_pos,
+                    start_pos: _,
                 } => {
                     println!("Spawn received: id={}", id);
                     assert_eq!(
@@ -109,10 +115,14 @@ fn test_station_does_not_spawn_with_insufficient_resources() {
     tx_report
         .send(RobotReport {
             robot_id: 2,
+            station: 0,
             tick: 1,
+            base_version: 0,
             map_diff,
             energy: 5,
             mineral: 3,
+            dock: DockPhase::Upload,
+            loadout: Vec::new(),
         })
         .unwrap();
 
@@ -121,10 +131,8 @@ fn test_station_does_not_spawn_with_insufficient_resources() {
     let mut received_spawn = false;
 
     for _ in 0..2 {
-        if let Ok(cmd) = rx_cmd.recv() {
-            if let StationCmd::Spawn { .. } = cmd {
-                received_spawn = true;
-            }
+        if let Ok(StationCmd::Spawn { .. }) = rx_cmd.recv() {
+            received_spawn = true;
         }
     }
 
@@ -135,3 +143,475 @@ fn test_station_does_not_spawn_with_insufficient_resources() {
         "Robot was spawned with insufficient resources"
     );
 }
+
+fn report(robot_id: usize, tick: u64, base_version: u64, map_diff: MapDiff) -> RobotReport {
+    RobotReport {
+        robot_id,
+        station: 0,
+        tick,
+        base_version,
+        map_diff,
+        energy: 0,
+        mineral: 0,
+        dock: if robot_id == 0 {
+            DockPhase::None
+        } else {
+            DockPhase::Upload
+        },
+        loadout: Vec::new(),
+    }
+}
+
+fn test_station() -> (Station, Sender<RobotReport>, Receiver<StationCmd>) {
+    let (tx_report, rx_report) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    (Station::new(rx_report, tx_cmd), tx_report, rx_cmd)
+}
+
+#[test]
+fn test_stale_observation_does_not_resurrect_collected_resource() {
+    let (mut station, _tx, _rx) = test_station();
+
+    station.handle_report(report(1, 1, 0, MapDiff(vec![((0, 0), None, Tile::Energy)])));
+    station.handle_report(report(
+        0,
+        2,
+        0,
+        MapDiff(vec![((0, 0), Some(Tile::Energy), Tile::Empty)]),
+    ));
+    station.handle_report(report(2, 3, 0, MapDiff(vec![((0, 0), None, Tile::Energy)])));
+
+    assert_eq!(station.master_map.get(&(0, 0)), Some(&Tile::Empty));
+    let conflict = station.conflicts.back().unwrap();
+    assert_eq!(conflict.robot_id, 2);
+    assert_eq!(conflict.found, Tile::Empty);
+    assert_eq!(conflict.proposed, Tile::Energy);
+    assert_eq!(conflict.resolution, Resolution::Rejected);
+}
+
+#[test]
+fn test_collection_beats_mismatching_observation() {
+    let (mut station, _tx, _rx) = test_station();
+
+    station.handle_report(report(
+        1,
+        1,
+        0,
+        MapDiff(vec![((2, 2), None, Tile::Mineral)]),
+    ));
+    station.handle_report(report(
+        0,
+        2,
+        0,
+        MapDiff(vec![((2, 2), Some(Tile::Energy), Tile::Empty)]),
+    ));
+
+    assert_eq!(station.master_map.get(&(2, 2)), Some(&Tile::Empty));
+    let conflict = station.conflicts.back().unwrap();
+    assert_eq!(conflict.expected, Some(Tile::Energy));
+    assert_eq!(conflict.found, Tile::Mineral);
+    assert_eq!(conflict.resolution, Resolution::Applied);
+}
+
+#[test]
+fn test_newest_tick_wins_between_observations() {
+    let (mut station, _tx, _rx) = test_station();
+
+    station.handle_report(report(
+        1,
+        5,
+        0,
+        MapDiff(vec![((1, 1), None, Tile::Science)]),
+    ));
+    station.handle_report(report(2, 7, 0, MapDiff(vec![((1, 1), None, Tile::Empty)])));
+    assert_eq!(station.master_map.get(&(1, 1)), Some(&Tile::Empty));
+    assert_eq!(
+        station.conflicts.back().unwrap().resolution,
+        Resolution::Applied
+    );
+
+    station.handle_report(report(
+        3,
+        6,
+        0,
+        MapDiff(vec![((1, 1), None, Tile::Science)]),
+    ));
+    assert_eq!(station.master_map.get(&(1, 1)), Some(&Tile::Empty));
+    assert_eq!(
+        station.conflicts.back().unwrap().resolution,
+        Resolution::Rejected
+    );
+    assert_eq!(station.conflicts.len(), 2);
+}
+
+#[test]
+fn test_up_to_date_observation_is_not_a_conflict() {
+    let (mut station, _tx, _rx) = test_station();
+
+    station.handle_report(report(1, 1, 0, MapDiff(vec![((3, 3), None, Tile::Energy)])));
+    let version = station.map_version();
+    station.handle_report(report(
+        2,
+        2,
+        version,
+        MapDiff(vec![((3, 3), None, Tile::Empty)]),
+    ));
+
+    assert_eq!(station.master_map.get(&(3, 3)), Some(&Tile::Empty));
+    assert!(station.conflicts.is_empty());
+}
+
+fn snapshots(rx: &Receiver<StationCmd>) -> Vec<(u32, u64, MapDiff)> {
+    rx.try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Snapshot {
+                id, version, diff, ..
+            } => Some((id, version, diff)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_reporting_robot_receives_one_batched_delta() {
+    let (mut station, _tx, rx) = test_station();
+
+    station.handle_report(report(0, 1, 0, MapDiff(vec![((0, 0), None, Tile::Energy)])));
+    station.handle_report(report(
+        0,
+        2,
+        0,
+        MapDiff(vec![
+            ((0, 0), Some(Tile::Energy), Tile::Empty),
+            ((0, 1), None, Tile::Mineral),
+        ]),
+    ));
+    assert!(
+        snapshots(&rx).is_empty(),
+        "coordinator reports are not synced"
+    );
+
+    station.handle_report(report(
+        1,
+        3,
+        0,
+        MapDiff(vec![((5, 5), None, Tile::Science)]),
+    ));
+
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    let (id, version, diff) = &sent[0];
+    assert_eq!(*id, 1);
+    assert_eq!(*version, station.map_version());
+    assert_eq!(diff.0.len(), 3);
+    assert!(diff.0.contains(&((0, 0), None, Tile::Empty)));
+    assert!(diff.0.contains(&((5, 5), None, Tile::Science)));
+
+    station.handle_report(report(1, 4, 3, MapDiff(vec![((6, 6), None, Tile::Empty)])));
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].2, MapDiff(vec![((6, 6), None, Tile::Empty)]));
+}
+
+#[test]
+fn test_robot_too_far_behind_gets_full_snapshot() {
+    let (station, _tx, rx) = test_station();
+    let mut station = station.with_history_len(2);
+
+    for tick in 1..=5 {
+        station.handle_report(report(
+            0,
+            tick,
+            0,
+            MapDiff(vec![((0, tick as usize), None, Tile::Mineral)]),
+        ));
+    }
+    assert!(station.delta_since(0).is_none());
+    assert_eq!(station.delta_since(3).unwrap().0.len(), 2);
+    assert!(station.delta_since(5).unwrap().0.is_empty());
+
+    station.handle_report(report(7, 6, 0, MapDiff(vec![])));
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].2 .0.len(), 5);
+}
+
+fn ack(robot_id: usize, version: u64) -> RobotReport {
+    RobotReport {
+        dock: DockPhase::Ack(version),
+        ..report(robot_id, 0, version, MapDiff(Vec::new()))
+    }
+}
+
+#[test]
+fn test_docking_handshake_upload_download_ack() {
+    let (mut station, _tx, rx) = test_station();
+
+    station.handle_report(report(0, 1, 0, MapDiff(vec![((2, 2), None, Tile::Energy)])));
+    station.handle_report(report(
+        1,
+        2,
+        0,
+        MapDiff(vec![((4, 4), None, Tile::Mineral)]),
+    ));
+
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].1, 2);
+    assert_eq!(station.awaiting_ack(1), Some(2));
+    assert_eq!(station.acked_version(1), 0);
+
+    station.handle_report(ack(1, 2));
+    assert_eq!(station.awaiting_ack(1), None);
+    assert_eq!(station.acked_version(1), 2);
+    assert_eq!(station.map_version(), 2, "acks are not merged");
+
+    // nothing new since the ack: the download is empty but still sent
+    station.handle_report(report(1, 3, 2, MapDiff(Vec::new())));
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].2, MapDiff(Vec::new()));
+}
+
+#[test]
+fn test_unacknowledged_download_is_resent_on_next_dock() {
+    let (mut station, _tx, rx) = test_station();
+
+    station.handle_report(report(
+        1,
+        1,
+        0,
+        MapDiff(vec![((1, 1), None, Tile::Science)]),
+    ));
+    assert_eq!(snapshots(&rx).len(), 1);
+
+    // the download got lost, the robot docks again still at version 0
+    station.handle_report(report(1, 2, 0, MapDiff(vec![((1, 2), None, Tile::Empty)])));
+    let sent = snapshots(&rx);
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].2 .0.contains(&((1, 1), None, Tile::Science)));
+    assert_eq!(station.awaiting_ack(1), Some(2));
+
+    // a stale ack does not clear the newer pending download
+    station.handle_report(ack(1, 1));
+    assert_eq!(station.awaiting_ack(1), Some(2));
+    assert_eq!(station.acked_version(1), 1);
+}
+
+fn windowed_station(lateness: u64) -> (Station, Sender<RobotReport>, Receiver<StationCmd>) {
+    let (station, tx, rx) = test_station();
+    (station.with_lateness(lateness), tx, rx)
+}
+
+#[test]
+fn test_ticks_are_finalised_once_the_watermark_passes() {
+    let (mut station, _tx, _rx) = windowed_station(2);
+
+    station.handle_report(report(0, 5, 0, MapDiff(vec![((0, 0), None, Tile::Energy)])));
+    station.handle_report(report(
+        1,
+        4,
+        0,
+        MapDiff(vec![((0, 1), None, Tile::Mineral)]),
+    ));
+    assert_eq!(station.watermark(), Some(3));
+    assert_eq!(station.map_version(), 0);
+    assert_eq!(station.metrics().pending, 2);
+
+    station.handle_report(report(0, 6, 0, MapDiff(Vec::new())));
+    assert_eq!(station.map_version(), 1);
+    assert_eq!(station.master_map.get(&(0, 1)), Some(&Tile::Mineral));
+    assert_eq!(station.master_map.get(&(0, 0)), None);
+
+    station.handle_report(report(0, 7, 0, MapDiff(Vec::new())));
+    assert_eq!(station.map_version(), 2);
+    assert_eq!(station.metrics().late, 0);
+}
+
+#[test]
+fn test_late_report_is_merged_and_counted() {
+    let (mut station, _tx, _rx) = windowed_station(1);
+
+    station.handle_report(report(0, 10, 0, MapDiff(vec![((2, 2), None, Tile::Empty)])));
+    station.handle_report(report(0, 11, 0, MapDiff(Vec::new())));
+    station.handle_report(report(
+        2,
+        8,
+        0,
+        MapDiff(vec![((2, 2), None, Tile::Science)]),
+    ));
+
+    let metrics = station.metrics();
+    assert_eq!(metrics.late, 1);
+    assert_eq!(metrics.merged, 2);
+    assert_eq!(metrics.pending, 1, "tick 11 is still inside the window");
+    // merged at its own tick, so the newer observation still wins
+    assert_eq!(station.master_map.get(&(2, 2)), Some(&Tile::Empty));
+    assert_eq!(
+        station.conflicts.back().unwrap().resolution,
+        Resolution::Rejected
+    );
+}
+
+#[test]
+fn test_duplicate_reports_are_merged_exactly_once() {
+    let (mut station, _tx, _rx) = windowed_station(0);
+    let upload = RobotReport {
+        energy: 4,
+        ..report(1, 3, 0, MapDiff(vec![((1, 1), None, Tile::Energy)]))
+    };
+
+    station.handle_report(upload.clone());
+    station.handle_report(upload);
+
+    let metrics = station.metrics();
+    assert_eq!(metrics.merged, 1);
+    assert_eq!(metrics.duplicates, 1);
+    assert_eq!(metrics.dropped(), 1);
+    assert_eq!(station.map_version(), 1);
+}
+
+#[test]
+fn test_spawn_announcement_does_not_shadow_same_tick_upload() {
+    let (mut station, _tx, _rx) = windowed_station(0);
+    let announcement = RobotReport {
+        dock: DockPhase::None,
+        ..report(1, 3, 0, MapDiff(Vec::new()))
+    };
+    let upload = RobotReport {
+        energy: 4,
+        ..report(1, 3, 0, MapDiff(vec![((1, 1), None, Tile::Energy)]))
+    };
+
+    station.handle_report(announcement);
+    station.handle_report(upload);
+
+    let metrics = station.metrics();
+    assert_eq!(metrics.duplicates, 0);
+    assert_eq!(station.master_map.get(&(1, 1)), Some(&Tile::Energy));
+}
+
+#[test]
+fn test_flush_merges_reports_inside_the_window() {
+    let (tx_report, rx_report) = mpsc::channel();
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let handle = thread::spawn(move || {
+        let mut station = Station::new(rx_report, tx_cmd).with_lateness(50);
+        station.run();
+        station.metrics()
+    });
+
+    for tick in 1..=5 {
+        tx_report
+            .send(report(
+                0,
+                tick,
+                0,
+                MapDiff(vec![((0, tick as usize), None, Tile::Mineral)]),
+            ))
+            .unwrap();
+    }
+    drop(tx_report);
+
+    let metrics = handle.join().unwrap();
+    assert_eq!(metrics.merged, 5);
+    assert_eq!(metrics.pending, 0);
+    let versions: Vec<u64> = rx_cmd
+        .try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Version(v) => Some(v),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(versions, vec![1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_master_map_changes_are_announced() {
+    let (mut station, _tx, rx) = test_station();
+
+    station.handle_report(report(0, 1, 0, MapDiff(vec![((0, 0), None, Tile::Energy)])));
+    station.handle_report(report(
+        0,
+        2,
+        0,
+        MapDiff(vec![((0, 0), Some(Tile::Energy), Tile::Empty)]),
+    ));
+
+    let announced: Vec<(u64, MapDiff)> = rx
+        .try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::MasterDiff {
+                station: 0,
+                version,
+                diff,
+            } => Some((version, diff)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        announced,
+        vec![
+            (1, MapDiff(vec![((0, 0), None, Tile::Energy)])),
+            (2, MapDiff(vec![((0, 0), Some(Tile::Energy), Tile::Empty)])),
+        ]
+    );
+}
+
+#[test]
+fn test_operator_requests() {
+    let (tx_cmd, rx_cmd) = mpsc::channel();
+    let mut station = Station::detached(tx_cmd);
+
+    station.handle_request(StationRequest::Spawn {
+        modules: vec![RobotModule::Explorer],
+        position: (4, 2),
+    });
+    let spawned: Vec<_> = rx_cmd
+        .try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Spawn { id, start_pos, .. } => Some((id, start_pos)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(spawned, vec![(3, (4, 2))]);
+    assert_eq!(station.awaiting_ack(3), Some(0));
+
+    station.handle_request(StationRequest::AdjustStocks {
+        energy: 1,
+        mineral: -5,
+    });
+    assert_eq!(station.stocks().energy, 1);
+    assert_eq!(station.stocks().mineral, 0);
+
+    station.handle_request(StationRequest::AdjustStocks {
+        energy: i64::MAX,
+        mineral: i64::MIN,
+    });
+    assert_eq!(station.stocks().energy, u32::MAX);
+    assert_eq!(station.stocks().mineral, 0);
+    station.handle_request(StationRequest::AdjustStocks {
+        energy: -(i64::from(u32::MAX) - 1),
+        mineral: 0,
+    });
+    assert_eq!(station.stocks().energy, 1);
+    assert_eq!(
+        station.answer(StationQuery::Stocks),
+        "Station 0: stocks 1E 0M"
+    );
+    assert_eq!(
+        station.answer(StationQuery::Cell((9, 9))),
+        "Station 0: (9, 9) is unknown"
+    );
+
+    rx_cmd.try_iter().for_each(drop);
+    station.handle_request(StationRequest::Save);
+    let logs: Vec<_> = rx_cmd
+        .try_iter()
+        .filter_map(|cmd| match cmd {
+            StationCmd::Log(record) => Some(record.message),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(logs, vec!["No journal to save to".to_string()]);
+}
@@ -0,0 +1,96 @@
+use rust_project::timeline::{Sample, Timeline, TIMELINE_LEN};
+
+fn sample(tick: u64) -> Sample {
+    Sample {
+        tick,
+        ..Sample::default()
+    }
+}
+
+#[test]
+fn test_record_turns_cumulative_merges_into_deltas() {
+    let mut timeline = Timeline::new();
+    timeline.record(sample(1), 3);
+    timeline.record(sample(2), 3);
+    timeline.record(sample(3), 10);
+
+    let merges: Vec<u64> = timeline.samples().iter().map(|s| s.merges).collect();
+    assert_eq!(merges, vec![3, 0, 7]);
+    assert_eq!(timeline.latest().map(|s| s.tick), Some(3));
+}
+
+#[test]
+fn test_oldest_samples_are_dropped() {
+    let mut timeline = Timeline::new();
+    for tick in 0..TIMELINE_LEN as u64 + 5 {
+        timeline.push(sample(tick));
+    }
+
+    assert_eq!(timeline.samples().len(), TIMELINE_LEN);
+    assert_eq!(timeline.samples().front().map(|s| s.tick), Some(5));
+    assert_eq!(timeline.tick_bounds(), [5.0, TIMELINE_LEN as f64 + 4.0]);
+}
+
+#[test]
+fn test_points_and_bounds() {
+    let mut timeline = Timeline::new();
+    assert_eq!(timeline.tick_bounds(), [0.0, 1.0]);
+
+    timeline.push(Sample {
+        explored: 12.5,
+        ..sample(4)
+    });
+    assert_eq!(timeline.tick_bounds(), [4.0, 5.0]);
+    timeline.push(Sample {
+        explored: 20.0,
+        ..sample(6)
+    });
+
+    assert_eq!(
+        timeline.points(|s| s.explored),
+        vec![(4.0, 12.5), (6.0, 20.0)]
+    );
+    assert_eq!(timeline.tick_bounds(), [4.0, 6.0]);
+}
+
+#[test]
+fn test_recording_replays_the_same_samples() {
+    let path = std::env::temp_dir().join(format!("timeline_{}.txt", std::process::id()));
+    let mut timeline = Timeline::new().with_recording(&path).unwrap();
+    timeline.record(
+        Sample {
+            energy_stock: 12,
+            mineral_stock: 4,
+            energy_collected: 30,
+            mineral_collected: 9,
+            explored: 37.5,
+            robots: 3,
+            ..sample(1)
+        },
+        2,
+    );
+    timeline.record(sample(2), 5);
+    drop(timeline);
+
+    let recorded = Timeline::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[0].explored, 37.5);
+    assert_eq!(recorded[1].merges, 3);
+
+    let mut replay = Timeline::new();
+    for sample in recorded.iter().copied() {
+        replay.push(sample);
+    }
+    assert_eq!(replay.samples(), &recorded);
+}
+
+#[test]
+fn test_bad_sample_line_is_reported() {
+    assert_eq!(Sample::parse(&sample(4).to_string()), Ok(sample(4)));
+    assert_eq!(
+        Sample::parse("4 1 2 3"),
+        Err("missing mineral collected".to_string())
+    );
+    assert!(Sample::parse("4 1 2 3 4 lots 1 1").is_err());
+}
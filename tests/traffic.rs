@@ -0,0 +1,129 @@
+use rust_project::map::Map;
+use rust_project::robot::RobotStatus;
+use rust_project::station::StationSite;
+use rust_project::traffic::{exit_lane, waiting_ring, DockOrder, TrafficController};
+
+fn open_map() -> Map {
+    Map::from_ascii(&".......\n".repeat(7)).unwrap()
+}
+
+fn site(position: (usize, usize), dock_radius: usize) -> StationSite {
+    StationSite {
+        id: 0,
+        position,
+        dock_radius,
+    }
+}
+
+fn returning(id: usize, position: (usize, usize), cargo: u32) -> RobotStatus {
+    RobotStatus {
+        id,
+        position,
+        returning_to: Some(0),
+        cargo,
+        collects: true,
+    }
+}
+
+#[test]
+fn test_exit_lane_points_into_the_map() {
+    assert_eq!(exit_lane(&site((0, 0), 1), 7, 7), vec![(1, 0), (2, 0)]);
+    assert_eq!(exit_lane(&site((6, 3), 0), 7, 7), vec![(5, 3)]);
+    assert_eq!(exit_lane(&site((1, 6), 1), 3, 7), vec![(1, 5), (1, 4)]);
+    assert_eq!(waiting_ring(&site((0, 0), 1), 7, 7).len(), 5);
+    assert_eq!(waiting_ring(&site((3, 3), 1), 7, 7).len(), 16);
+}
+
+#[test]
+fn test_admission_by_cargo_then_distance() {
+    let mut traffic = TrafficController::new();
+    let robots = [
+        returning(1, (6, 6), 10),
+        returning(2, (0, 0), 15),
+        returning(3, (3, 5), 10),
+    ];
+
+    let orders = traffic.plan(&[site((3, 3), 0)], &robots, &open_map(), 1);
+
+    assert_eq!(orders[&2], DockOrder::Slot((3, 3)));
+    assert_eq!(orders[&3], DockOrder::Wait((3, 4)));
+    assert_eq!(orders[&1], DockOrder::Wait((4, 4)));
+    let stats = traffic.stats();
+    assert_eq!((stats.queued, stats.docking, stats.admitted), (2, 1, 1));
+}
+
+#[test]
+fn test_longest_wait_goes_first_and_is_averaged() {
+    let mut traffic = TrafficController::new();
+    let station = [site((3, 3), 0)];
+    let map = open_map();
+
+    traffic.plan(
+        &station,
+        &[returning(2, (3, 2), 10), returning(1, (4, 4), 10)],
+        &map,
+        1,
+    );
+
+    // robot 2 docked; robot 4 arrives closer than robot 1 but has not waited
+    let orders = traffic.plan(
+        &station,
+        &[returning(1, (4, 4), 10), returning(4, (2, 3), 10)],
+        &map,
+        4,
+    );
+    assert_eq!(orders[&1], DockOrder::Slot((3, 3)));
+    assert!(matches!(orders[&4], DockOrder::Wait(_)));
+
+    let stats = traffic.stats();
+    assert_eq!(stats.admitted, 2);
+    assert_eq!(stats.total_wait, 3);
+    assert_eq!(stats.avg_wait(), 1.5);
+}
+
+#[test]
+fn test_exit_lane_is_never_assigned() {
+    let mut traffic = TrafficController::new();
+    let station = site((0, 0), 1);
+    let robots: Vec<_> = (1..=8).map(|id| returning(id, (6, id - 1), 5)).collect();
+    let lane = exit_lane(&station, 7, 7);
+
+    let orders = traffic.plan(&[station], &robots, &open_map(), 1);
+
+    let slots = orders
+        .values()
+        .filter(|order| matches!(order, DockOrder::Slot(_)))
+        .count();
+    assert_eq!(slots, 3);
+    assert_eq!(
+        orders
+            .values()
+            .filter(|order| matches!(order, DockOrder::Hold))
+            .count(),
+        1
+    );
+    for order in orders.values() {
+        if let DockOrder::Slot(cell) | DockOrder::Wait(cell) = order {
+            assert!(!lane.contains(cell), "{:?} is on the exit lane", cell);
+        }
+    }
+}
+
+#[test]
+fn test_robot_on_a_slot_blocks_it() {
+    let mut traffic = TrafficController::new();
+    let explorer = RobotStatus {
+        returning_to: None,
+        ..returning(9, (3, 3), 0)
+    };
+
+    let orders = traffic.plan(
+        &[site((3, 3), 0)],
+        &[explorer, returning(1, (0, 0), 10)],
+        &open_map(),
+        1,
+    );
+
+    assert!(matches!(orders[&1], DockOrder::Wait(_)));
+    assert!(!orders.contains_key(&9));
+}
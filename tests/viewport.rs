@@ -0,0 +1,94 @@
+use rust_project::map::Tile;
+use rust_project::viewport::{dominant_tile, Viewport};
+
+fn viewport(view: (usize, usize)) -> Viewport {
+    let mut viewport = Viewport::new();
+    viewport.resize(view, 40, 60);
+    viewport
+}
+
+#[test]
+fn test_pan_stays_on_the_map() {
+    let mut view = viewport((10, 20));
+
+    view.pan(-1, -1, 40, 60);
+    assert_eq!(view.origin, (0, 0));
+
+    view.pan(100, 100, 40, 60);
+    assert_eq!(view.origin, (30, 40));
+    assert!(view.contains((39, 59)));
+    assert!(!view.contains((29, 59)));
+}
+
+#[test]
+fn test_small_map_is_shown_whole() {
+    let mut view = Viewport::new();
+    view.resize((25, 30), 10, 10);
+
+    view.pan(3, 3, 10, 10);
+    assert!(view.shows_all(10, 10));
+    assert_eq!(view.screen_size(10, 10), (10, 10));
+}
+
+#[test]
+fn test_zoom_aggregates_blocks_around_the_centre() {
+    let mut view = viewport((10, 10));
+    view.center_on((20, 30), 40, 60);
+    assert_eq!(view.origin, (15, 25));
+
+    view.zoom(true, 40, 60);
+    assert_eq!(view.zoom, 2);
+    assert_eq!(view.origin, (10, 20));
+    assert_eq!(
+        view.block((1, 2), 40, 60),
+        vec![(12, 24), (12, 25), (13, 24), (13, 25)]
+    );
+
+    view.zoom(true, 40, 60);
+    view.zoom(true, 40, 60);
+    assert_eq!(view.zoom, 4);
+    assert_eq!(view.origin, (0, 10));
+    assert_eq!(view.screen_size(40, 60), (10, 10));
+
+    view.zoom(false, 40, 60);
+    assert_eq!(view.zoom, 2);
+}
+
+#[test]
+fn test_reveal_and_pan_stop_following() {
+    let mut view = viewport((10, 10));
+    view.follow = true;
+
+    view.reveal((25, 3), 40, 60);
+    assert_eq!(view.origin, (16, 0));
+    assert!(view.follow);
+
+    view.pan(0, 1, 40, 60);
+    assert!(!view.follow);
+}
+
+#[test]
+fn test_blocks_are_clipped_at_the_edge() {
+    let mut view = Viewport::new();
+    view.resize((4, 4), 5, 5);
+    view.zoom(true, 5, 5);
+
+    assert_eq!(view.screen_size(5, 5), (3, 3));
+    assert_eq!(view.block((2, 2), 5, 5), vec![(4, 4)]);
+}
+
+#[test]
+fn test_dominant_tile_prefers_rare_kinds_on_ties() {
+    use Tile::*;
+
+    assert_eq!(dominant_tile([Empty, Empty, Obstacle, Energy]), Some(Empty));
+    assert_eq!(
+        dominant_tile([Empty, Energy, Obstacle, Mineral]),
+        Some(Mineral)
+    );
+    assert_eq!(
+        dominant_tile([Obstacle, Obstacle, Science, Science]),
+        Some(Science)
+    );
+    assert_eq!(dominant_tile([]), None);
+}
@@ -0,0 +1,77 @@
+use rust_project::map::{Map, Tile};
+use rust_project::visibility::field_of_view;
+
+fn map(text: &str) -> Map {
+    Map::from_ascii(text).unwrap()
+}
+
+#[test]
+fn test_open_room_is_fully_visible() {
+    let room = map(&".....\n".repeat(5));
+    let fov = field_of_view(&room, (2, 2), 2);
+    assert_eq!(fov.len(), 25);
+}
+
+#[test]
+fn test_radius_limits_view() {
+    let room = map(&".........\n".repeat(9));
+    let fov = field_of_view(&room, (4, 4), 1);
+    assert_eq!(fov.len(), 9);
+    assert!(!fov.contains(&(2, 4)));
+}
+
+#[test]
+fn test_pillar_casts_shadow() {
+    let room = map(".......\n.......\n.......\n...#...\n.......\n.......\n.......\n");
+    let fov = field_of_view(&room, (5, 3), 5);
+
+    assert!(fov.contains(&(3, 3)), "the pillar itself is seen");
+    assert!(!fov.contains(&(2, 3)));
+    assert!(!fov.contains(&(1, 3)));
+    assert!(!fov.contains(&(0, 3)));
+    assert!(fov.contains(&(3, 2)));
+    assert!(fov.contains(&(0, 0)));
+}
+
+#[test]
+fn test_corridor_hides_side_rooms() {
+    let corridor = map("#######\n#.....#\n###.###\n###.###\n###.###\n");
+    let fov = field_of_view(&corridor, (4, 3), 4);
+
+    assert!(fov.contains(&(1, 3)));
+    assert!(fov.contains(&(2, 2)), "corridor walls are visible");
+    assert!(!fov.contains(&(1, 1)));
+    assert!(!fov.contains(&(1, 5)));
+}
+
+#[test]
+fn test_walls_block_room_behind() {
+    let rooms = map("...#...\n...#...\n...#...\n");
+    let fov = field_of_view(&rooms, (1, 1), 6);
+
+    assert!(fov.contains(&(1, 3)));
+    assert!((0..3).all(|r| (4..7).all(|c| !fov.contains(&(r, c)))));
+}
+
+#[test]
+fn test_view_is_symmetric_between_floor_cells() {
+    let map = Map::generate(20, 20, 42);
+    let floors: Vec<(usize, usize)> = (0..20)
+        .flat_map(|r| (0..20).map(move |c| (r, c)))
+        .filter(|&(r, c)| map.grid[r][c] != Tile::Obstacle)
+        .collect();
+
+    for &a in floors.iter().step_by(7) {
+        let from_a = field_of_view(&map, a, 6);
+        for &b in &floors {
+            if from_a.contains(&b) {
+                assert!(
+                    field_of_view(&map, b, 6).contains(&a),
+                    "{:?} sees {:?} but not the reverse",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use rust_project::map::Map;
+use rust_project::robot::{Robot, RobotStatus};
+use rust_project::watchdog::{Recovery, Stall, Watchdog, WatchdogConfig};
+use std::collections::HashSet;
+
+fn watchdog() -> Watchdog {
+    Watchdog::new(
+        WatchdogConfig {
+            stuck_after: 3,
+            max_period: 2,
+            repeats: 3,
+            recovery_ticks: 2,
+        },
+        7,
+    )
+}
+
+fn at(id: usize, position: (usize, usize)) -> RobotStatus {
+    RobotStatus {
+        id,
+        position,
+        returning_to: None,
+        cargo: 0,
+        collects: true,
+    }
+}
+
+#[test]
+fn test_robot_on_one_cell_is_stuck() {
+    let mut watchdog = watchdog();
+    let returning = RobotStatus {
+        returning_to: Some(0),
+        ..at(1, (2, 2))
+    };
+
+    for tick in 1..=3 {
+        assert!(watchdog
+            .observe(&[returning, at(2, (4, 4))], &HashSet::new(), tick)
+            .is_empty());
+    }
+    let incidents = watchdog.observe(&[returning, at(2, (4, 4))], &HashSet::new(), 4);
+
+    assert_eq!(incidents.len(), 2);
+    assert_eq!(incidents[0].robot, 1);
+    assert_eq!(incidents[0].stall, Stall::Stuck { ticks: 3 });
+    assert_eq!(incidents[0].recovery, Recovery::Replan);
+    assert!(matches!(incidents[1].recovery, Recovery::RandomWalk { .. }));
+    assert_eq!(watchdog.stats().stuck, 2);
+}
+
+#[test]
+fn test_back_and_forth_is_oscillation() {
+    let mut watchdog = watchdog();
+    let cells = [(1, 1), (1, 2)];
+
+    let mut incidents = Vec::new();
+    for tick in 0..6 {
+        incidents = watchdog.observe(&[at(1, cells[tick % 2])], &HashSet::new(), tick as u64);
+        if tick < 5 {
+            assert!(incidents.is_empty());
+        }
+    }
+
+    assert_eq!(incidents.len(), 1);
+    assert_eq!(incidents[0].stall, Stall::Oscillating { period: 2 });
+    assert!(incidents[0].describe().contains("oscillating"));
+    assert_eq!(watchdog.stats().oscillating, 1);
+}
+
+#[test]
+fn test_queued_robots_are_excused() {
+    let mut watchdog = watchdog();
+    let excused = HashSet::from([1]);
+
+    for tick in 1..=10 {
+        assert!(watchdog
+            .observe(&[at(1, (0, 0))], &excused, tick)
+            .is_empty());
+    }
+}
+
+#[test]
+fn test_higher_id_yields_to_its_neighbour() {
+    let mut watchdog = watchdog();
+    let robots = [at(1, (2, 2)), at(2, (2, 3))];
+
+    let incidents: Vec<_> = (1..=4)
+        .flat_map(|tick| watchdog.observe(&robots, &HashSet::new(), tick))
+        .collect();
+
+    assert_eq!(incidents.len(), 2);
+    assert!(matches!(incidents[0].recovery, Recovery::RandomWalk { .. }));
+    assert_eq!(incidents[1].recovery, Recovery::Yield { blocker: (2, 2) });
+}
+
+#[test]
+fn test_recovery_is_counted_once_the_robot_escapes() {
+    let mut watchdog = watchdog();
+    for tick in 1..=4 {
+        watchdog.observe(&[at(1, (2, 2))], &HashSet::new(), tick);
+    }
+    assert_eq!(watchdog.stats().stuck, 1);
+
+    // no new incident while the recovery runs
+    assert!(watchdog
+        .observe(&[at(1, (2, 2))], &HashSet::new(), 5)
+        .is_empty());
+    assert!(watchdog
+        .observe(&[at(1, (3, 2))], &HashSet::new(), 6)
+        .is_empty());
+
+    assert_eq!(watchdog.stats().recovered, 1);
+}
+
+#[test]
+fn test_path_towards_goes_round_walls() {
+    let map = Map::from_ascii(".....\n.###.\n.#...\n.#...\n.....\n").unwrap();
+    let mut robot = Robot::new(1, (2, 2), vec![]);
+
+    robot.step_towards((0, 2), &map, &HashSet::new());
+    assert_eq!(robot.position, (2, 2));
+
+    assert!(robot.path_towards((0, 2), &map, &HashSet::new()));
+    assert_eq!(robot.position, (2, 3));
+
+    let walled_in = HashSet::from([(2, 2), (2, 4), (3, 3)]);
+    assert!(!robot.path_towards((0, 2), &map, &walled_in));
+    assert_eq!(robot.position, (2, 3));
+}
+
+#[test]
+fn test_step_away_backs_off() {
+    let map = Map::from_ascii(".....\n").unwrap();
+    let mut robot = Robot::new(1, (0, 2), vec![]);
+
+    robot.step_away((0, 1), &map, &HashSet::new());
+    assert_eq!(robot.position, (0, 3));
+
+    robot.step_away((0, 4), &map, &HashSet::from([(0, 2)]));
+    assert_eq!(robot.position, (0, 3));
+}